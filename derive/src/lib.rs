@@ -0,0 +1,140 @@
+//! Derive macros for [uniflow](https://docs.rs/uniflow). Use them through the
+//! `derive` feature of `uniflow` rather than depending on this crate directly.
+
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Error, Fields, parse_macro_input};
+
+/// Derives typed lenses for the fields of a struct.
+///
+/// Generates `Type::lenses()`, returning a `TypeLenses` value with one method
+/// per field, each returning a `uniflow::Lens` onto that field. Mark a field
+/// `#[lens(nested)]` when its type derives `Lenses` too, so that its own
+/// fields can be chained: `State::lenses().settings().theme()`.
+#[proc_macro_derive(Lenses, attributes(lens))]
+pub fn derive_lenses(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "Lenses cannot be derived for generic types",
+        ));
+    }
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "Lenses can only be derived for structs with named fields",
+                ));
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "Lenses can only be derived for structs",
+            ));
+        }
+    };
+
+    let vis = &input.vis;
+    let name = &input.ident;
+    let lenses = format_ident!("{}Lenses", name);
+
+    let mut methods = Vec::new();
+    for field in fields {
+        let nested = nested(&field.attrs)?;
+        let ident = field.ident.as_ref().expect("named field");
+        let ty = &field.ty;
+        let field_vis = &field.vis;
+        let lens = quote! {
+            self.0.then(::uniflow::Lens::new(
+                |s: &#name| &s.#ident,
+                |s: &mut #name| &mut s.#ident,
+            ))
+        };
+        methods.push(if nested {
+            quote! {
+                #field_vis fn #ident(&self) -> <#ty as ::uniflow::Lenses>::Fields<Root> {
+                    ::core::convert::From::from(#lens)
+                }
+            }
+        } else {
+            quote! {
+                #field_vis fn #ident(&self) -> ::uniflow::Lens<Root, #ty> {
+                    #lens
+                }
+            }
+        });
+    }
+
+    let doc = format!("Lenses onto the fields of [`{name}`], from a root state `Root`.");
+    Ok(quote! {
+        #[doc = #doc]
+        #vis struct #lenses<Root: 'static>(::uniflow::Lens<Root, #name>);
+
+        impl<Root: 'static> #lenses<Root> {
+            #(#methods)*
+        }
+
+        impl<Root: 'static> ::core::clone::Clone for #lenses<Root> {
+            fn clone(&self) -> Self {
+                #lenses(self.0.clone())
+            }
+        }
+
+        impl<Root: 'static> ::core::ops::Deref for #lenses<Root> {
+            type Target = ::uniflow::Lens<Root, #name>;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl<Root: 'static> ::core::convert::From<::uniflow::Lens<Root, #name>> for #lenses<Root> {
+            fn from(lens: ::uniflow::Lens<Root, #name>) -> Self {
+                #lenses(lens)
+            }
+        }
+
+        impl<Root: 'static> ::core::convert::From<#lenses<Root>> for ::uniflow::Lens<Root, #name> {
+            fn from(lenses: #lenses<Root>) -> Self {
+                lenses.0
+            }
+        }
+
+        impl ::uniflow::Lenses for #name {
+            type Fields<Root: 'static> = #lenses<Root>;
+        }
+
+        impl #name {
+            /// Lenses onto the fields of this type.
+            #vis fn lenses() -> #lenses<#name> {
+                #lenses(::uniflow::Lens::identity())
+            }
+        }
+    })
+}
+
+/// Whether the field is marked `#[lens(nested)]`.
+fn nested(attrs: &[syn::Attribute]) -> syn::Result<bool> {
+    let mut nested = false;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("lens")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("nested") {
+                nested = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `nested`"))
+            }
+        })?;
+    }
+    Ok(nested)
+}
@@ -0,0 +1,53 @@
+//! A counter rendered into the page, for `wasm32-unknown-unknown`.
+//!
+//! Build it with the `wasm` feature, for instance through
+//! [trunk](https://trunkrs.dev) or `wasm-bindgen`, and load it from a page
+//! with a `<button id="increment">` and a `<span id="count">`.
+
+#[cfg(target_arch = "wasm32")]
+mod app {
+    use uniflow::{Dispatch, Read, Store};
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen::prelude::*;
+
+    enum Action {
+        Increment,
+    }
+
+    fn reducer(state: u32, action: Action) -> u32 {
+        match action {
+            Action::Increment => state + 1,
+        }
+    }
+
+    #[wasm_bindgen(start)]
+    pub fn start() -> Result<(), JsValue> {
+        uniflow::any_spawner::Executor::init_wasm_bindgen().expect("initialize wasm executor");
+
+        let document = web_sys::window()
+            .and_then(|window| window.document())
+            .ok_or("no document")?;
+        let count = document.get_element_by_id("count").ok_or("no #count")?;
+        let button = document
+            .get_element_by_id("increment")
+            .ok_or("no #increment")?
+            .dyn_into::<web_sys::HtmlElement>()?;
+
+        // The store lives as long as the page.
+        let store: &'static Store<u32, Action> = Box::leak(Box::new(Store::new(0, reducer)));
+        store.bind(move |value| count.set_text_content(Some(&value.to_string())));
+
+        let onclick = Closure::<dyn Fn()>::new(move || store.dispatch(Action::Increment));
+        button.set_onclick(Some(onclick.as_ref().unchecked_ref()));
+        onclick.forget();
+        Ok(())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    eprintln!("build this example for wasm32-unknown-unknown");
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
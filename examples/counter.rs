@@ -29,9 +29,9 @@ async fn main() {
     store.dispatch(Action::Increment);
     store.dispatch(Action::Increment);
     store.dispatch(Action::Multiply(3));
-    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    store.settle().await;
 
     assert_eq!(store.get(), 6);
 
-    store.shutdown();
+    store.shutdown().await;
 }
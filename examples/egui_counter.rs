@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use eframe::egui;
+use uniflow::egui::EguiStore;
+use uniflow::{Context, Effect, Store};
+
+enum Action {
+    Increment,
+    IncrementLater,
+}
+
+fn reducer(state: u32, action: Action) -> (u32, Effect<Action>) {
+    match action {
+        Action::Increment => (state + 1, Effect::none()),
+        Action::IncrementLater => {
+            // The effect's change repaints the window without any input.
+            let effect = Effect::new(|ctx: Context<Action>| async move {
+                ctx.clock().sleep(Duration::from_secs(1)).await;
+                ctx.dispatch(Action::Increment);
+            });
+            (state, effect)
+        }
+    }
+}
+
+struct App {
+    store: EguiStore<u32, Action>,
+}
+
+impl eframe::App for App {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let count = self.store.state();
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading(format!("count: {count}"));
+            if ui.button("increment").clicked() {
+                self.store.dispatch(Action::Increment);
+            }
+            if ui.button("increment in a second").clicked() {
+                self.store.dispatch(Action::IncrementLater);
+            }
+        });
+    }
+}
+
+#[tokio::main]
+async fn main() -> eframe::Result {
+    uniflow::any_spawner::Executor::init_tokio().expect("initialize tokio executor");
+
+    eframe::run_native(
+        "uniflow counter",
+        eframe::NativeOptions::default(),
+        Box::new(|cc| {
+            let store = Store::new_with_deps(0, reducer, ());
+            Ok(Box::new(App {
+                store: EguiStore::new(store, &cc.egui_ctx),
+            }))
+        }),
+    )
+}
@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+use futures::future::{AbortHandle, AbortRegistration, Abortable};
+
+use crate::metrics::{Counters, StoreMetrics};
+use crate::trace;
+
+struct ActivityInner {
+    /// Whether the reducer task is still receiving commands.
+    running: bool,
+    /// Effects spawned by the reducer task which have not completed yet.
+    effects: usize,
+    /// Aborts the running effects, by the ID of their guard.
+    aborts: HashMap<u64, AbortHandle>,
+    next_effect: u64,
+    /// Whether effects have been aborted; later effects are aborted at once.
+    aborted: bool,
+    wakers: Vec<Waker>,
+}
+
+/// Tracks the work a store still has in flight: its reducer task, the
+/// commands waiting in its queues, and the effects the task spawned.
+#[derive(Clone)]
+pub(crate) struct Activity {
+    inner: Arc<Mutex<ActivityInner>>,
+    /// Commands sent but not yet processed by the reducer task.
+    queued: Arc<AtomicUsize>,
+    /// The store's name, which instrumentation is tagged with.
+    name: Arc<str>,
+    counters: Arc<Counters>,
+}
+
+impl Activity {
+    pub(crate) fn new(name: Arc<str>) -> Self {
+        Activity {
+            inner: Arc::new(Mutex::new(ActivityInner {
+                running: true,
+                effects: 0,
+                aborts: HashMap::new(),
+                next_effect: 0,
+                aborted: false,
+                wakers: Vec::new(),
+            })),
+            queued: Arc::new(AtomicUsize::new(0)),
+            name,
+            counters: Arc::default(),
+        }
+    }
+
+    pub(crate) fn name(&self) -> &Arc<str> {
+        &self.name
+    }
+
+    /// The number of commands queued and not yet processed.
+    pub(crate) fn queue_depth(&self) -> usize {
+        self.queued.load(Ordering::Acquire)
+    }
+
+    /// Records a command about to be sent to one of the store's queues.
+    pub(crate) fn queue(&self) {
+        self.queued.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Records that the queue accepted a [`queue`](Activity::queue)d command.
+    pub(crate) fn dispatched(&self) {
+        self.counters.dispatched(&self.name);
+        trace::dispatched(&self.name);
+    }
+
+    /// Takes back a [`queue`](Activity::queue)d command which the queue
+    /// rejected.
+    pub(crate) fn unqueue(&self) {
+        self.queued.fetch_sub(1, Ordering::AcqRel);
+    }
+
+    /// Records how long the reducer took to apply one command.
+    pub(crate) fn reduced(&self, latency: Duration) {
+        self.counters.reduced(&self.name, latency);
+    }
+
+    pub(crate) fn metrics(&self) -> StoreMetrics {
+        self.counters.snapshot(self.queued.load(Ordering::Acquire))
+    }
+
+    /// Records that the reducer task has processed `commands` queued commands
+    /// and spawned their effects.
+    pub(crate) fn processed(&self, commands: usize) {
+        let queued = self.queued.fetch_sub(commands, Ordering::AcqRel) - commands;
+        self.counters.processed(&self.name, commands, queued);
+        if queued == 0 {
+            self.update(|_| {});
+        }
+    }
+
+    /// Records a spawned effect, until the returned guard is dropped.
+    pub(crate) fn effect(&self) -> EffectGuard {
+        let (abort, registration) = AbortHandle::new_pair();
+        let mut inner = self.inner.lock().unwrap();
+        inner.effects += 1;
+        let id = inner.next_effect;
+        inner.next_effect += 1;
+        if inner.aborted {
+            abort.abort();
+        }
+        inner.aborts.insert(id, abort);
+        EffectGuard {
+            activity: self.clone(),
+            id,
+            registration: Some(registration),
+        }
+    }
+
+    /// Aborts every running effect, and every effect spawned from now on.
+    pub(crate) fn abort_effects(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.aborted = true;
+        for abort in inner.aborts.values() {
+            abort.abort();
+        }
+    }
+
+    /// Records that the reducer task has exited.
+    pub(crate) fn finish(&self) {
+        self.update(|inner| inner.running = false);
+    }
+
+    fn update(&self, f: impl FnOnce(&mut ActivityInner)) {
+        let wakers = {
+            let mut guard = self.inner.lock().unwrap();
+            f(&mut guard);
+            std::mem::take(&mut guard.wakers)
+        };
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Ready once no command is queued and no effect is running.
+    pub(crate) fn poll_settled(&self, cx: &mut Context<'_>) -> Poll<()> {
+        let mut guard = self.inner.lock().unwrap();
+        if guard.effects == 0 && self.queued.load(Ordering::Acquire) == 0 {
+            return Poll::Ready(());
+        }
+        guard.wakers.push(cx.waker().clone());
+        Poll::Pending
+    }
+
+    /// Ready once no effect is running.
+    pub(crate) fn poll_effects_idle(&self, cx: &mut Context<'_>) -> Poll<()> {
+        let mut guard = self.inner.lock().unwrap();
+        if guard.effects == 0 {
+            return Poll::Ready(());
+        }
+        guard.wakers.push(cx.waker().clone());
+        Poll::Pending
+    }
+
+    fn poll_stopped(&self, cx: &mut Context<'_>) -> Poll<()> {
+        let mut guard = self.inner.lock().unwrap();
+        if !guard.running && guard.effects == 0 {
+            return Poll::Ready(());
+        }
+        guard.wakers.push(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+/// Keeps an effect counted as in flight while alive.
+pub(crate) struct EffectGuard {
+    activity: Activity,
+    id: u64,
+    registration: Option<AbortRegistration>,
+}
+
+impl EffectGuard {
+    /// Makes `effect` stop at its next suspension point once the store's
+    /// effects are aborted.
+    pub(crate) fn abortable<F: Future>(&mut self, effect: F) -> Abortable<F> {
+        let registration = self.registration.take().expect("one effect per guard");
+        Abortable::new(effect, registration)
+    }
+
+    /// The name of the store which spawned the effect.
+    pub(crate) fn store(&self) -> &str {
+        &self.activity.name
+    }
+}
+
+impl Drop for EffectGuard {
+    fn drop(&mut self) {
+        let id = self.id;
+        self.activity.update(|inner| {
+            inner.effects -= 1;
+            inner.aborts.remove(&id);
+        });
+    }
+}
+
+/// Resolves once a store has shut down gracefully.
+///
+/// Returned by [`Store::shutdown`](crate::Store::shutdown). The store stops
+/// accepting actions as soon as `shutdown` is called, whether or not this
+/// future is awaited.
+pub struct Shutdown {
+    activity: Activity,
+}
+
+impl Shutdown {
+    pub(crate) fn new(activity: Activity) -> Self {
+        Shutdown { activity }
+    }
+}
+
+impl Future for Shutdown {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        self.activity.poll_stopped(cx)
+    }
+}
@@ -0,0 +1,131 @@
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// Shared, copy-on-write state.
+///
+/// Cloning an `ArcState` is a pointer copy, so snapshots handed out by
+/// [`get`](crate::Read::get), watchers and readers are cheap regardless of the
+/// size of `T`. Equality first compares pointers and only falls back to
+/// `T: PartialEq` when they differ.
+///
+/// Use it with [`Store::new_arc`](crate::Store::new_arc) so the reducer mutates
+/// the state through [`Arc::make_mut`]: as long as nobody else holds a snapshot,
+/// the state is mutated in place without any deep copy. It also works as a
+/// field type, letting selectors return cheap `ArcState` slices of a larger
+/// state.
+#[derive(Debug, Default)]
+pub struct ArcState<T>(Arc<T>);
+
+impl<T> ArcState<T> {
+    pub fn new(value: T) -> Self {
+        ArcState(Arc::new(value))
+    }
+
+    /// Returns true if both snapshots point at the same allocation.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    pub fn into_arc(self) -> Arc<T> {
+        self.0
+    }
+}
+
+impl<T: Clone> ArcState<T> {
+    /// Mutable access to the inner value, copying it first if it is shared.
+    pub fn make_mut(&mut self) -> &mut T {
+        Arc::make_mut(&mut self.0)
+    }
+}
+
+impl<T> Clone for ArcState<T> {
+    fn clone(&self) -> Self {
+        ArcState(self.0.clone())
+    }
+}
+
+impl<T: PartialEq> PartialEq for ArcState<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other) || *self.0 == *other.0
+    }
+}
+
+impl<T: Eq> Eq for ArcState<T> {}
+
+impl<T> Deref for ArcState<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> AsRef<T> for ArcState<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> From<T> for ArcState<T> {
+    fn from(value: T) -> Self {
+        ArcState::new(value)
+    }
+}
+
+impl<T> From<Arc<T>> for ArcState<T> {
+    fn from(arc: Arc<T>) -> Self {
+        ArcState(arc)
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct NeverEqual;
+
+    impl PartialEq for NeverEqual {
+        fn eq(&self, _: &Self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn equality_short_circuits_on_pointer() {
+        let a = ArcState::new(NeverEqual);
+        let b = a.clone();
+        assert!(a == b);
+        assert!(ArcState::new(NeverEqual) != ArcState::new(NeverEqual));
+    }
+
+    #[test]
+    fn equality_falls_back_to_value() {
+        assert_eq!(ArcState::new(vec![1, 2]), ArcState::new(vec![1, 2]));
+        assert_ne!(ArcState::new(vec![1]), ArcState::new(vec![2]));
+    }
+
+    #[test]
+    fn make_mut_copies_only_when_shared() {
+        static CLONES: AtomicUsize = AtomicUsize::new(0);
+
+        struct Counted;
+        impl Clone for Counted {
+            fn clone(&self) -> Self {
+                CLONES.fetch_add(1, Ordering::Relaxed);
+                Counted
+            }
+        }
+
+        let mut state = ArcState::new(Counted);
+        state.make_mut();
+        assert_eq!(CLONES.load(Ordering::Relaxed), 0);
+
+        let snapshot = state.clone();
+        state.make_mut();
+        assert_eq!(CLONES.load(Ordering::Relaxed), 1);
+        assert!(!state.ptr_eq(&snapshot));
+    }
+}
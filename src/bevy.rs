@@ -0,0 +1,94 @@
+//! A plugin running a store inside a [Bevy](https://bevy.org) app (feature
+//! `bevy`).
+
+use std::sync::Mutex;
+
+use bevy_app::{App, Plugin, PreUpdate};
+use bevy_ecs::message::{Message, MessageWriter};
+use bevy_ecs::resource::Resource;
+use bevy_ecs::system::ResMut;
+use futures::{FutureExt, StreamExt};
+
+use crate::{Action, Changes, Deps, Store, Value};
+
+/// Inserts a store into the app as a resource, and sends a [`StateChanged`]
+/// message with the new state whenever it changes.
+///
+/// Systems read the state and dispatch through `Res<Store<S, A, D>>`. Every
+/// frame, in [`PreUpdate`], the plugin polls the executor, for executors
+/// which run on the app's thread such as the
+/// [manual spawner](crate::manual_spawner), then sends the messages for the
+/// changes made since the last frame.
+pub struct UniflowPlugin<S: Value, A: Action, D: Deps = ()> {
+    store: Mutex<Option<Store<S, A, D>>>,
+}
+
+impl<S: Value, A: Action, D: Deps> UniflowPlugin<S, A, D> {
+    pub fn new(store: Store<S, A, D>) -> Self {
+        UniflowPlugin {
+            store: Mutex::new(Some(store)),
+        }
+    }
+}
+
+impl<S: Value, A: Action, D: Deps> Plugin for UniflowPlugin<S, A, D> {
+    fn build(&self, app: &mut App) {
+        let store = self
+            .store
+            .lock()
+            .unwrap()
+            .take()
+            .expect("the plugin is only built once");
+        let changes = StateChanges(store.state_stream());
+        app.insert_resource(store)
+            .insert_resource(changes)
+            .add_message::<StateChanged<S>>()
+            .add_systems(PreUpdate, pump::<S>);
+    }
+}
+
+impl<S: Value, A: Action, D: Deps> Resource for Store<S, A, D> {}
+
+/// Sent by [`UniflowPlugin`] with the new state of the store after each
+/// change.
+pub struct StateChanged<S>(pub S);
+
+impl<S: Value> Message for StateChanged<S> {}
+
+struct StateChanges<S: Value>(Changes<S>);
+
+impl<S: Value> Resource for StateChanges<S> {}
+
+fn pump<S: Value>(
+    mut changes: ResMut<StateChanges<S>>,
+    mut messages: MessageWriter<StateChanged<S>>,
+) {
+    any_spawner::Executor::poll_local();
+    while let Some(Some(state)) = changes.0.next().now_or_never() {
+        messages.write(StateChanged(state));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Dispatch;
+    use crate::executor::init as init_executor;
+    use bevy_ecs::message::Messages;
+
+    #[test]
+    fn plugin_reduces_and_reports_changes_each_frame() {
+        init_executor();
+        let mut app = App::new();
+        app.add_plugins(UniflowPlugin::new(Store::new(0, |s: i32, n: i32| s + n)));
+        app.world().resource::<Store<i32, i32>>().dispatch(2);
+        app.update();
+
+        let messages = app.world().resource::<Messages<StateChanged<i32>>>();
+        let states: Vec<i32> = messages
+            .iter_current_update_messages()
+            .map(|changed| changed.0)
+            .collect();
+        assert_eq!(states, vec![2]);
+    }
+}
@@ -0,0 +1,80 @@
+use std::future::{Future, pending};
+use std::panic::{AssertUnwindSafe, catch_unwind, resume_unwind};
+use std::thread;
+
+use futures::channel::oneshot;
+
+/// Runs `f` off the async executor, on tokio's blocking pool when called
+/// within a tokio runtime (feature `tokio`) and on a new thread otherwise. On
+/// wasm32, which has no threads, `f` runs straight away.
+///
+/// The returned future resolves with `f`'s result, and resumes a panic of
+/// `f`'s in the awaiting task. If `f` is dropped without running, as a tokio
+/// runtime shutting down drops its blocking tasks, the future never resolves.
+pub(crate) fn spawn_blocking<T, F>(f: F) -> impl Future<Output = T> + Send + 'static
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (sender, receiver) = oneshot::channel();
+    let work = move || {
+        let _ = sender.send(catch_unwind(AssertUnwindSafe(f)));
+    };
+    spawn(work);
+    outcome(receiver)
+}
+
+/// Resolves with the outcome of the work which `receiver` hears from. Work
+/// dropped unrun leaves the awaiting task pending rather than panicking it:
+/// the runtime which dropped it is going away too.
+async fn outcome<T>(receiver: oneshot::Receiver<thread::Result<T>>) -> T {
+    match receiver.await {
+        Ok(Ok(value)) => value,
+        Ok(Err(panic)) => resume_unwind(panic),
+        Err(oneshot::Canceled) => pending().await,
+    }
+}
+
+#[cfg(all(feature = "tokio", not(target_arch = "wasm32")))]
+fn spawn(work: impl FnOnce() + Send + 'static) {
+    match tokio::runtime::Handle::try_current() {
+        Ok(runtime) => drop(runtime.spawn_blocking(work)),
+        Err(_) => drop(std::thread::spawn(work)),
+    }
+}
+
+#[cfg(all(not(feature = "tokio"), not(target_arch = "wasm32")))]
+fn spawn(work: impl FnOnce() + Send + 'static) {
+    std::thread::spawn(work);
+}
+
+#[cfg(target_arch = "wasm32")]
+fn spawn(work: impl FnOnce() + Send + 'static) {
+    work();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::FutureExt;
+    use futures::executor::block_on;
+
+    #[test]
+    fn resolves_with_the_result() {
+        assert_eq!(block_on(spawn_blocking(|| 6 * 7)), 42);
+    }
+
+    #[test]
+    fn resumes_a_panic_in_the_awaiting_task() {
+        let result = block_on(AssertUnwindSafe(spawn_blocking(|| panic!("decode"))).catch_unwind());
+        let panic = result.unwrap_err();
+        assert_eq!(panic.downcast_ref::<&str>(), Some(&"decode"));
+    }
+
+    #[test]
+    fn dropped_work_leaves_the_future_pending() {
+        let (sender, receiver) = oneshot::channel::<thread::Result<i32>>();
+        drop(sender);
+        assert_eq!(outcome(receiver).now_or_never(), None);
+    }
+}
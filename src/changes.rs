@@ -0,0 +1,63 @@
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use futures::channel::mpsc::{UnboundedReceiver, unbounded};
+use futures::{Stream, StreamExt};
+
+use crate::Value;
+use crate::node::{ReadableNode, WatchSlot};
+use crate::subscription::Connection;
+
+/// A stream of the values of a store or reader, with one item per change.
+///
+/// Every change is buffered until it is consumed, and consecutive equal values
+/// are skipped. The stream holds its own subscription, so it keeps yielding
+/// after [`unbind`](crate::Read::unbind). Returned by
+/// [`Store::state_stream`](crate::Store::state_stream) and
+/// [`Reader::stream`](crate::Reader::stream).
+pub struct Changes<T: Value> {
+    receiver: UnboundedReceiver<T>,
+    _connection: Connection,
+    /// The node watched, for streams which keep it alive.
+    _node: Option<Arc<dyn ReadableNode<T>>>,
+}
+
+impl<T: Value> Changes<T> {
+    /// Streams the changes of `node`, keeping it alive.
+    pub(crate) fn keep(node: Arc<dyn ReadableNode<T>>) -> Self {
+        Changes {
+            _node: Some(node.clone()),
+            ..Self::new(node.as_ref())
+        }
+    }
+
+    pub(crate) fn new(node: &dyn ReadableNode<T>) -> Self {
+        let (sender, receiver) = unbounded();
+        let (connection, alive) = Connection::new();
+        let last = Mutex::new(node.get());
+        node.add_watcher(WatchSlot {
+            alive,
+            callback: Arc::new(move |value: &T| {
+                let mut last = last.lock().unwrap();
+                if *last != *value {
+                    *last = value.clone();
+                    let _ = sender.unbounded_send(value.clone());
+                }
+            }),
+        });
+        Changes {
+            receiver,
+            _connection: connection,
+            _node: None,
+        }
+    }
+}
+
+impl<T: Value> Stream for Changes<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.receiver.poll_next_unpin(cx)
+    }
+}
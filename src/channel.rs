@@ -0,0 +1,168 @@
+use std::task::{Context, Poll};
+
+use futures::channel::mpsc::{self, SendError, Sender, TrySendError, UnboundedSender};
+use futures::stream::{BoxStream, StreamExt};
+use futures::{Sink, SinkExt};
+
+use crate::activity::Activity;
+use crate::dispatcher::OverflowPolicy;
+
+/// The sending half of a store's queue, either bounded or unbounded.
+pub(crate) struct ChannelSender<T> {
+    kind: SenderKind<T>,
+    /// Records the values sent, if the queue belongs to a store.
+    activity: Option<Activity>,
+    /// The number of queued commands past which `overflow` applies.
+    capacity: Option<usize>,
+    overflow: OverflowPolicy,
+}
+
+enum SenderKind<T> {
+    Bounded(Sender<T>),
+    Unbounded(UnboundedSender<T>),
+}
+
+impl<T> Clone for ChannelSender<T> {
+    fn clone(&self) -> Self {
+        let kind = match &self.kind {
+            SenderKind::Bounded(s) => SenderKind::Bounded(s.clone()),
+            SenderKind::Unbounded(s) => SenderKind::Unbounded(s.clone()),
+        };
+        ChannelSender {
+            kind,
+            activity: self.activity.clone(),
+            capacity: self.capacity,
+            overflow: self.overflow,
+        }
+    }
+}
+
+impl<T> ChannelSender<T> {
+    /// Handles full queues in [`dispatch`](ChannelSender::dispatch) according
+    /// to `overflow`.
+    pub(crate) fn with_overflow(mut self, overflow: OverflowPolicy) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Runs `send`, counting the value as queued if it is accepted. The count
+    /// is raised first so that the receiver never sees it underflow.
+    fn counted<R, E>(
+        &mut self,
+        send: impl FnOnce(&mut SenderKind<T>) -> Result<R, E>,
+    ) -> Result<R, E> {
+        if let Some(activity) = &self.activity {
+            activity.queue();
+        }
+        let result = send(&mut self.kind);
+        self.record(result.is_ok());
+        result
+    }
+
+    /// Records the outcome of a send counted as queued.
+    fn record(&self, accepted: bool) {
+        match &self.activity {
+            Some(activity) if accepted => activity.dispatched(),
+            Some(activity) => activity.unqueue(),
+            None => {}
+        }
+    }
+
+    pub(crate) fn try_send(&mut self, value: T) -> Result<(), TrySendError<T>> {
+        self.counted(|kind| match kind {
+            SenderKind::Bounded(s) => s.try_send(value),
+            SenderKind::Unbounded(s) => s.unbounded_send(value),
+        })
+    }
+
+    /// Sends `value`, applying the overflow policy if the store already holds
+    /// `capacity` queued commands. A closed queue drops the value.
+    pub(crate) fn dispatch(&mut self, value: T) {
+        let full = match (&self.activity, self.capacity) {
+            (Some(activity), Some(capacity)) => activity.queue_depth() >= capacity,
+            _ => false,
+        };
+        if !full || self.overflow.overflowed() {
+            let _ = self.try_send(value);
+        }
+    }
+
+    pub(crate) async fn send(&mut self, value: T) -> Result<(), SendError> {
+        if let Some(activity) = &self.activity {
+            activity.queue();
+        }
+        let result = match &mut self.kind {
+            SenderKind::Bounded(s) => s.send(value).await,
+            SenderKind::Unbounded(s) => s.send(value).await,
+        };
+        self.record(result.is_ok());
+        result
+    }
+
+    pub(crate) fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), SendError>> {
+        match &mut self.kind {
+            SenderKind::Bounded(s) => Sink::poll_ready(std::pin::Pin::new(s), cx),
+            SenderKind::Unbounded(s) => UnboundedSender::poll_ready(s, cx),
+        }
+    }
+
+    pub(crate) fn start_send(&mut self, value: T) -> Result<(), SendError> {
+        self.counted(|kind| match kind {
+            SenderKind::Bounded(s) => s.start_send(value),
+            SenderKind::Unbounded(s) => s.start_send(value),
+        })
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        match &self.kind {
+            SenderKind::Bounded(s) => s.is_closed(),
+            SenderKind::Unbounded(s) => s.is_closed(),
+        }
+    }
+
+    pub(crate) fn close_channel(&mut self) {
+        match &mut self.kind {
+            SenderKind::Bounded(s) => s.close_channel(),
+            SenderKind::Unbounded(s) => s.close_channel(),
+        }
+    }
+}
+
+impl<T> From<Sender<T>> for ChannelSender<T> {
+    fn from(sender: Sender<T>) -> Self {
+        ChannelSender {
+            kind: SenderKind::Bounded(sender),
+            activity: None,
+            capacity: None,
+            overflow: OverflowPolicy::default(),
+        }
+    }
+}
+
+/// Creates a queue holding up to `capacity` values, or any number of values
+/// if `capacity` is `None`.
+///
+/// Every value accepted into the queue is recorded as queued in `activity`;
+/// the receiver is responsible for marking it processed.
+pub(crate) fn channel<T: Send + 'static>(
+    capacity: Option<usize>,
+    activity: Activity,
+) -> (ChannelSender<T>, BoxStream<'static, T>) {
+    let (kind, receiver) = match capacity {
+        Some(capacity) => {
+            let (sender, receiver) = mpsc::channel(capacity);
+            (SenderKind::Bounded(sender), receiver.boxed())
+        }
+        None => {
+            let (sender, receiver) = mpsc::unbounded();
+            (SenderKind::Unbounded(sender), receiver.boxed())
+        }
+    };
+    let sender = ChannelSender {
+        kind,
+        activity: Some(activity),
+        capacity,
+        overflow: OverflowPolicy::default(),
+    };
+    (sender, receiver)
+}
@@ -0,0 +1,98 @@
+/// Composes per-field reducers into one reducer over a parent struct.
+///
+/// Each entry names a field of the state, the action variant routed to it,
+/// and the reducer for that field: `field: Action::Variant => reducer`. The
+/// variant must hold the field's action in a single tuple field. Actions
+/// matching none of the listed variants leave the state unchanged.
+///
+/// ```
+/// use uniflow::{Dispatch, Read, Store, combine_reducers};
+///
+/// uniflow::manual_spawner::init().expect("init");
+///
+/// #[derive(Clone, Default, PartialEq)]
+/// struct App {
+///     count: i32,
+///     log: Vec<String>,
+/// }
+///
+/// enum Action {
+///     Count(i32),
+///     Log(String),
+/// }
+///
+/// fn count(state: i32, by: i32) -> i32 {
+///     state + by
+/// }
+///
+/// fn log(mut state: Vec<String>, line: String) -> Vec<String> {
+///     state.push(line);
+///     state
+/// }
+///
+/// let store = Store::new(
+///     App::default(),
+///     combine_reducers!(App {
+///         count: Action::Count => count,
+///         log: Action::Log => log,
+///     }),
+/// );
+/// store.dispatch(Action::Count(2));
+/// uniflow::manual_spawner::step();
+/// assert_eq!(store.get().count, 2);
+/// ```
+#[macro_export]
+macro_rules! combine_reducers {
+    ($state:ty { $($field:ident : $($variant:ident)::+ => $reducer:expr),+ $(,)? }) => {
+        move |mut state: $state, action| {
+            match action {
+                $($($variant)::+(action) => {
+                    state.$field = ($reducer)(state.$field, action);
+                })+
+                #[allow(unreachable_patterns)]
+                _ => {}
+            }
+            state
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    #[derive(Clone, Debug, Default, PartialEq)]
+    struct App {
+        count: i32,
+        name: String,
+    }
+
+    enum Action {
+        Add(i32),
+        Sub(i32),
+        Rename(String),
+        Ignored,
+    }
+
+    #[test]
+    fn routes_each_variant_to_its_field() {
+        let reducer = combine_reducers!(App {
+            count: Action::Add => |count: i32, n: i32| count + n,
+            count: Action::Sub => |count: i32, n: i32| count - n,
+            name: Action::Rename => |_: String, name: String| name,
+        });
+        let state = [
+            Action::Add(5),
+            Action::Sub(2),
+            Action::Rename("app".into()),
+            Action::Ignored,
+        ]
+        .into_iter()
+        .fold(App::default(), &reducer);
+        assert_eq!(
+            state,
+            App {
+                count: 3,
+                name: "app".into()
+            }
+        );
+    }
+}
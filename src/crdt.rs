@@ -0,0 +1,305 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::Value;
+
+/// Identifies a device or process editing a replicated state. Each replica
+/// must use an id no other replica uses.
+pub type ReplicaId = u64;
+
+/// A conflict-free replicated data type: state which replicas edit
+/// independently, offline if need be, and merge into the same result
+/// whatever order the merges happen in.
+///
+/// `merge` must be commutative, associative and idempotent. Structs of CRDTs
+/// are CRDTs, merging field by field. Merge a replica's state into a store
+/// with [`Store::merge`](crate::Store::merge).
+pub trait Crdt: Value {
+    /// Merges the edits made to `other` into `self`.
+    fn merge(&mut self, other: &Self);
+
+    fn merged(&self, other: &Self) -> Self {
+        let mut merged = self.clone();
+        merged.merge(other);
+        merged
+    }
+}
+
+/// Merges the values of shared keys, and keeps the others.
+impl<K: Ord + Value, V: Crdt> Crdt for BTreeMap<K, V> {
+    fn merge(&mut self, other: &Self) {
+        for (key, theirs) in other {
+            self.entry(key.clone())
+                .and_modify(|ours| ours.merge(theirs))
+                .or_insert_with(|| theirs.clone());
+        }
+    }
+}
+
+// ── Counters ──────────────────────────────────────────────────────────────────
+
+/// A counter which only grows.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GCounter {
+    counts: BTreeMap<ReplicaId, u64>,
+}
+
+impl GCounter {
+    pub fn new() -> Self {
+        GCounter::default()
+    }
+
+    pub fn increment(&mut self, replica: ReplicaId, by: u64) {
+        *self.counts.entry(replica).or_default() += by;
+    }
+
+    pub fn value(&self) -> u64 {
+        self.counts.values().sum()
+    }
+}
+
+impl Crdt for GCounter {
+    fn merge(&mut self, other: &Self) {
+        for (&replica, &count) in &other.counts {
+            let ours = self.counts.entry(replica).or_default();
+            *ours = (*ours).max(count);
+        }
+    }
+}
+
+/// A counter which grows and shrinks.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PnCounter {
+    increments: GCounter,
+    decrements: GCounter,
+}
+
+impl PnCounter {
+    pub fn new() -> Self {
+        PnCounter::default()
+    }
+
+    pub fn increment(&mut self, replica: ReplicaId, by: u64) {
+        self.increments.increment(replica, by);
+    }
+
+    pub fn decrement(&mut self, replica: ReplicaId, by: u64) {
+        self.decrements.increment(replica, by);
+    }
+
+    pub fn value(&self) -> i64 {
+        self.increments.value() as i64 - self.decrements.value() as i64
+    }
+}
+
+impl Crdt for PnCounter {
+    fn merge(&mut self, other: &Self) {
+        self.increments.merge(&other.increments);
+        self.decrements.merge(&other.decrements);
+    }
+}
+
+// ── Registers ─────────────────────────────────────────────────────────────────
+
+/// A value whose last write wins.
+///
+/// Writes are ordered by a logical clock rather than the time of day, so
+/// replicas with skewed clocks still agree: a write wins over every write
+/// its replica had seen, and concurrent writes are ordered by replica id.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LwwRegister<T> {
+    value: T,
+    clock: u64,
+    replica: ReplicaId,
+}
+
+impl<T: Value> LwwRegister<T> {
+    pub fn new(value: T) -> Self {
+        LwwRegister {
+            value,
+            clock: 0,
+            replica: 0,
+        }
+    }
+
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+
+    pub fn set(&mut self, replica: ReplicaId, value: T) {
+        self.value = value;
+        self.clock += 1;
+        self.replica = replica;
+    }
+}
+
+impl<T: Value> Crdt for LwwRegister<T> {
+    fn merge(&mut self, other: &Self) {
+        if (other.clock, other.replica) > (self.clock, self.replica) {
+            *self = other.clone();
+        }
+    }
+}
+
+// ── Sets ──────────────────────────────────────────────────────────────────────
+
+/// Tags one insertion: the replica which made it, and its count of
+/// insertions so far.
+type Dot = (ReplicaId, u64);
+
+/// A set whose elements can be inserted and removed again. An insertion
+/// concurrent with a removal of the same element wins.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrSet<T: Ord> {
+    inserted: BTreeSet<(T, Dot)>,
+    removed: BTreeSet<Dot>,
+    clocks: BTreeMap<ReplicaId, u64>,
+}
+
+impl<T: Ord> Default for OrSet<T> {
+    fn default() -> Self {
+        OrSet {
+            inserted: BTreeSet::new(),
+            removed: BTreeSet::new(),
+            clocks: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T: Ord + Value> OrSet<T> {
+    pub fn new() -> Self {
+        OrSet::default()
+    }
+
+    pub fn insert(&mut self, replica: ReplicaId, value: T) {
+        let clock = self.clocks.entry(replica).or_default();
+        *clock += 1;
+        self.inserted.insert((value, (replica, *clock)));
+    }
+
+    /// Removes the insertions of `value` this replica has seen.
+    pub fn remove(&mut self, value: &T) {
+        let dots = self.dots(value).collect::<Vec<_>>();
+        self.removed.extend(dots);
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.dots(value).any(|dot| !self.removed.contains(&dot))
+    }
+
+    /// The elements, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        let mut last = None;
+        self.inserted
+            .iter()
+            .filter(|(_, dot)| !self.removed.contains(dot))
+            .map(|(value, _)| value)
+            .filter(move |value| last.replace(*value) != Some(*value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    fn dots<'a>(&'a self, value: &'a T) -> impl Iterator<Item = Dot> + 'a {
+        self.inserted
+            .iter()
+            .filter(move |(inserted, _)| inserted == value)
+            .map(|(_, dot)| *dot)
+    }
+}
+
+impl<T: Ord + Value> Crdt for OrSet<T> {
+    fn merge(&mut self, other: &Self) {
+        self.inserted.extend(other.inserted.iter().cloned());
+        self.removed.extend(other.removed.iter().copied());
+        for (&replica, &clock) in &other.clocks {
+            let ours = self.clocks.entry(replica).or_default();
+            *ours = (*ours).max(clock);
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{init as init_executor, tick};
+    use crate::{Dispatch, Read, Store};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[test]
+    fn counters_merge_concurrent_increments() {
+        let mut laptop = PnCounter::new();
+        let mut phone = PnCounter::new();
+        laptop.increment(1, 3);
+        phone.increment(2, 2);
+        phone.decrement(2, 1);
+
+        assert_eq!(laptop.merged(&phone), phone.merged(&laptop));
+        assert_eq!(laptop.merged(&phone).value(), 4);
+        assert_eq!(laptop.merged(&phone).merged(&phone).value(), 4);
+    }
+
+    #[test]
+    fn registers_agree_on_the_last_write() {
+        let mut laptop = LwwRegister::new("draft");
+        let mut phone = laptop.clone();
+        laptop.set(1, "laptop");
+        phone.set(2, "phone");
+        assert_eq!(laptop.merged(&phone).get(), &"phone");
+        assert_eq!(phone.merged(&laptop).get(), &"phone");
+
+        let mut later = phone.merged(&laptop);
+        later.set(1, "later");
+        assert_eq!(phone.merged(&later).get(), &"later");
+    }
+
+    #[test]
+    fn sets_keep_insertions_concurrent_with_removals() {
+        let mut laptop = OrSet::new();
+        laptop.insert(1, "milk");
+        laptop.insert(1, "eggs");
+        let mut phone = laptop.clone();
+        laptop.remove(&"milk");
+        phone.remove(&"eggs");
+        phone.insert(2, "milk");
+
+        let merged = laptop.merged(&phone);
+        assert_eq!(merged, phone.merged(&laptop));
+        assert_eq!(merged.iter().collect::<Vec<_>>(), vec![&"milk"]);
+    }
+
+    #[test]
+    fn stores_merge_states_from_other_replicas() {
+        init_executor();
+        let store = Store::new(GCounter::new(), |mut s: GCounter, n: u64| {
+            s.increment(1, n);
+            s
+        });
+        let mut offline = GCounter::new();
+        offline.increment(2, 5);
+
+        store.dispatch(1);
+        store.merge(offline.clone());
+        tick();
+        assert_eq!(store.get().value(), 6);
+
+        // Merging edits already merged is not a change.
+        let notified = Arc::new(AtomicBool::new(false));
+        let flag = notified.clone();
+        store.watch(move |_| flag.store(true, Ordering::SeqCst));
+        store.merge(offline);
+        tick();
+        assert!(!notified.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,178 @@
+//! Containers for the dependencies handed to reducers and effects, such as
+//! [`DepsMap`] and the swappable [`Swap`] for test doubles.
+
+use std::any::{Any, TypeId, type_name};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Dependencies stored by type.
+///
+/// Use it as a store's deps when a hand-written struct of every service would
+/// grow unwieldy: services are registered one by one, and looked up by type
+/// with [`get`](DepsMap::get).
+///
+/// ```
+/// use uniflow::DepsMap;
+///
+/// struct HttpClient {
+///     base_url: &'static str,
+/// }
+///
+/// let deps = DepsMap::new().with(HttpClient {
+///     base_url: "https://example.com",
+/// });
+/// assert_eq!(deps.require::<HttpClient>().base_url, "https://example.com");
+/// assert!(deps.get::<String>().is_none());
+/// ```
+#[derive(Clone, Default)]
+pub struct DepsMap {
+    deps: Arc<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+}
+
+impl DepsMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `dep`, replacing any dependency of the same type.
+    pub fn with<T: Send + Sync + 'static>(mut self, dep: T) -> Self {
+        self.insert(dep);
+        self
+    }
+
+    /// Registers `dep`, replacing any dependency of the same type.
+    ///
+    /// Clones of the map taken earlier, such as the deps of a store built
+    /// from it, are not affected.
+    pub fn insert<T: Send + Sync + 'static>(&mut self, dep: T) {
+        Arc::make_mut(&mut self.deps).insert(TypeId::of::<T>(), Arc::new(dep));
+    }
+
+    /// Returns the dependency of type `T`, if one is registered.
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.deps.get(&TypeId::of::<T>())?.downcast_ref()
+    }
+
+    /// Returns the dependency of type `T`.
+    ///
+    /// Panics if none is registered.
+    pub fn require<T: Send + Sync + 'static>(&self) -> &T {
+        self.get()
+            .unwrap_or_else(|| panic!("no dependency of type {}", type_name::<T>()))
+    }
+
+    /// Whether a dependency of type `T` is registered.
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.deps.contains_key(&TypeId::of::<T>())
+    }
+}
+
+/// A dependency which can be replaced after the deps have been handed to a
+/// store, such as a clock or an API client which a test fakes.
+///
+/// Clones share one slot: replacing the dependency through any clone replaces
+/// it for every holder, including effects which run later. Holds trait
+/// objects as well as concrete types.
+///
+/// ```
+/// use std::sync::Arc;
+/// use uniflow::deps::Swap;
+///
+/// trait Api: Send + Sync {
+///     fn fetch(&self) -> u32;
+/// }
+///
+/// struct Real;
+/// impl Api for Real {
+///     fn fetch(&self) -> u32 {
+///         1
+///     }
+/// }
+///
+/// struct Fake;
+/// impl Api for Fake {
+///     fn fetch(&self) -> u32 {
+///         2
+///     }
+/// }
+///
+/// let api: Swap<dyn Api> = Swap::from_arc(Arc::new(Real));
+/// let in_store = api.clone();
+/// api.replace(Arc::new(Fake));
+/// assert_eq!(in_store.get().fetch(), 2);
+/// ```
+pub struct Swap<T: ?Sized> {
+    slot: Arc<RwLock<Arc<T>>>,
+}
+
+impl<T> Swap<T> {
+    pub fn new(dep: T) -> Self {
+        Self::from_arc(Arc::new(dep))
+    }
+}
+
+impl<T: ?Sized> Swap<T> {
+    pub fn from_arc(dep: Arc<T>) -> Self {
+        Swap {
+            slot: Arc::new(RwLock::new(dep)),
+        }
+    }
+
+    /// Returns the current dependency.
+    pub fn get(&self) -> Arc<T> {
+        self.slot.read().unwrap().clone()
+    }
+
+    /// Replaces the dependency for every clone of this handle, returning the
+    /// previous one.
+    pub fn replace(&self, dep: Arc<T>) -> Arc<T> {
+        std::mem::replace(&mut *self.slot.write().unwrap(), dep)
+    }
+}
+
+impl<T: ?Sized> Clone for Swap<T> {
+    fn clone(&self) -> Self {
+        Swap {
+            slot: self.slot.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{init as init_executor, tick};
+    use crate::{Context, Dispatch, Effect, Read, Store};
+
+    #[derive(Clone)]
+    struct Deps {
+        greeting: Swap<String>,
+    }
+
+    #[test]
+    fn effects_see_a_swapped_dependency() {
+        init_executor();
+        let deps = Deps {
+            greeting: Swap::new("hello".to_string()),
+        };
+        let store = Store::new_with_deps(
+            String::new(),
+            |state: String, action: Option<String>| -> (String, Effect<Option<String>, Deps>) {
+                match action {
+                    Some(greeting) => (greeting, Effect::none()),
+                    None => {
+                        let effect = Effect::new(|ctx: Context<Option<String>, Deps>| async move {
+                            ctx.dispatch(Some(ctx.deps().greeting.get().to_string()));
+                        });
+                        (state, effect)
+                    }
+                }
+            },
+            deps.clone(),
+        );
+        deps.greeting.replace(Arc::new("fake".to_string()));
+        store.dispatch(None);
+        tick();
+        assert_eq!(store.get(), "fake");
+    }
+}
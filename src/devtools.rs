@@ -0,0 +1,393 @@
+//! [Redux DevTools](https://github.com/reduxjs/redux-devtools) integration.
+//!
+//! Speaks the remote monitoring protocol used by `@redux-devtools/cli`
+//! (SocketCluster over WebSocket): every reduced action is streamed together
+//! with the resulting state, and jump / rollback commands issued from the
+//! DevTools UI replace the store state.
+//!
+//! ```no_run
+//! use uniflow::devtools::{DevTools, WebSocketTransport};
+//!
+//! #[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+//! struct Counter(i32);
+//!
+//! let transport = WebSocketTransport::connect("ws://localhost:8000/socketcluster/")?;
+//! let devtools = DevTools::connect(transport, "counter");
+//! let store = uniflow::Store::builder(Counter(0), |s: Counter, n: i32| Counter(s.0 + n))
+//!     .middleware(devtools.clone())
+//!     .build();
+//! devtools.attach(&store);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::marker::PhantomData;
+use std::sync::mpsc::{Sender, channel};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Value as Json, json};
+
+use crate::{Action, Deps, Middleware, Read, Store, Value};
+
+use crate::transport::run;
+pub use crate::transport::{Transport, WebSocketTransport};
+
+// ── Connection state ──────────────────────────────────────────────────────────
+
+type Jump = Box<dyn Fn(&str) + Send + Sync>;
+type Snapshot = Box<dyn Fn() -> Option<String> + Send + Sync>;
+
+struct Shared {
+    name: String,
+    outgoing: Mutex<Sender<String>>,
+    socket_id: Mutex<Option<String>>,
+    next_cid: Mutex<u64>,
+    pending_action: Mutex<Option<Json>>,
+    jump: Mutex<Option<Jump>>,
+    snapshot: Mutex<Option<Snapshot>>,
+}
+
+impl Shared {
+    fn emit(&self, event: &str, data: Json) {
+        let cid = {
+            let mut next = self.next_cid.lock().unwrap();
+            *next += 1;
+            *next
+        };
+        let message = json!({ "event": event, "data": data, "cid": cid });
+        let _ = self.outgoing.lock().unwrap().send(message.to_string());
+    }
+
+    fn log(&self, kind: &str, fields: Json) {
+        let mut data = json!({
+            "type": kind,
+            "id": *self.socket_id.lock().unwrap(),
+            "instanceId": self.name,
+            "name": self.name,
+        });
+        if let (Json::Object(data), Json::Object(fields)) = (&mut data, fields) {
+            data.extend(fields);
+        }
+        self.emit("log", data);
+    }
+
+    fn send_init(&self) {
+        let snapshot = self.snapshot.lock().unwrap();
+        if let Some(payload) = snapshot.as_ref().and_then(|s| s()) {
+            self.log("INIT", json!({ "payload": payload }));
+        }
+    }
+
+    fn handle(&self, raw: &str) {
+        // SocketCluster heartbeats.
+        match raw {
+            "#1" => return self.reply("#2"),
+            "" => return self.reply(""),
+            _ => {}
+        }
+        let Ok(message) = serde_json::from_str::<Json>(raw) else {
+            return;
+        };
+        match (
+            message.get("rid").and_then(Json::as_u64),
+            message.get("event"),
+        ) {
+            // Handshake response carries our socket id.
+            (Some(1), _) => {
+                *self.socket_id.lock().unwrap() = message["data"]["id"].as_str().map(str::to_owned);
+            }
+            // Login response names the channel the monitor publishes on.
+            (Some(2), _) => {
+                if let Some(channel) = message["data"].as_str() {
+                    self.emit("#subscribe", json!({ "channel": channel }));
+                }
+            }
+            (_, Some(Json::String(event))) if event == "#publish" => {
+                self.handle_monitor(&message["data"]["data"]);
+            }
+            (_, Some(Json::String(_))) => self.handle_monitor(&message["data"]),
+            _ => {}
+        }
+    }
+
+    fn handle_monitor(&self, message: &Json) {
+        match message["type"].as_str() {
+            Some("START") => self.send_init(),
+            Some("DISPATCH") => {
+                let command = message["action"]["type"].as_str();
+                if matches!(
+                    command,
+                    Some("JUMP_TO_STATE" | "JUMP_TO_ACTION" | "ROLLBACK")
+                ) && let Some(state) = message["state"].as_str()
+                    && let Some(jump) = self.jump.lock().unwrap().as_ref()
+                {
+                    jump(state);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn reply(&self, raw: &str) {
+        let _ = self.outgoing.lock().unwrap().send(raw.to_owned());
+    }
+}
+
+/// Describes an action the way Redux DevTools expects: an object with a
+/// `type`. Externally tagged enum variants use their variant name as the type.
+fn describe_action(action: Json) -> Json {
+    match action {
+        Json::String(kind) => json!({ "type": kind }),
+        Json::Object(map) if map.len() == 1 => {
+            let (kind, payload) = map.into_iter().next().expect("one entry");
+            json!({ "type": kind, "payload": payload })
+        }
+        other => json!({ "type": "ACTION", "payload": other }),
+    }
+}
+
+fn timestamp() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default()
+}
+
+// ── DevTools ──────────────────────────────────────────────────────────────────
+
+/// A connection to a Redux DevTools server for one store.
+///
+/// Attach it to a store as a [`Middleware`] so every reduced action is
+/// reported, then call [`attach`](DevTools::attach) on the built store to send
+/// the initial state and accept jump commands from the DevTools UI.
+pub struct DevTools<S, A> {
+    shared: Arc<Shared>,
+    _types: PhantomData<fn(S, A)>,
+}
+
+impl<S, A> DevTools<S, A>
+where
+    S: Value + Serialize + DeserializeOwned,
+    A: Action + Serialize,
+{
+    /// Starts a background thread serving `transport` and logs in as a
+    /// monitored instance called `name`.
+    pub fn connect<T: Transport>(transport: T, name: impl Into<String>) -> Self {
+        let (sender, receiver) = channel();
+        let shared = Arc::new(Shared {
+            name: name.into(),
+            outgoing: Mutex::new(sender),
+            socket_id: Mutex::new(None),
+            next_cid: Mutex::new(0),
+            pending_action: Mutex::new(None),
+            jump: Mutex::new(None),
+            snapshot: Mutex::new(None),
+        });
+        shared.emit("#handshake", json!({ "authToken": null }));
+        shared.emit("login", json!("master"));
+        let weak = Arc::downgrade(&shared);
+        std::thread::spawn(move || run(transport, receiver, weak, Shared::handle));
+        DevTools {
+            shared,
+            _types: PhantomData,
+        }
+    }
+
+    /// Sends the current state of `store` as the initial DevTools state and
+    /// lets jump commands from the UI replace it.
+    pub fn attach<D: Deps>(&self, store: &Store<S, A, D>) {
+        let set_state = store.state_setter();
+        *self.shared.jump.lock().unwrap() = Some(Box::new(move |json: &str| {
+            if let Ok(state) = serde_json::from_str::<S>(json) {
+                set_state(state);
+            }
+        }));
+        let reader = store.reader();
+        *self.shared.snapshot.lock().unwrap() =
+            Some(Box::new(move || serde_json::to_string(&reader.get()).ok()));
+        self.shared.send_init();
+    }
+}
+
+impl<S, A> Clone for DevTools<S, A> {
+    fn clone(&self) -> Self {
+        DevTools {
+            shared: self.shared.clone(),
+            _types: PhantomData,
+        }
+    }
+}
+
+impl<S, A> Middleware<S, A> for DevTools<S, A>
+where
+    S: Value + Serialize,
+    A: Action + Serialize,
+{
+    fn before(&self, _: &S, action: A) -> Option<A> {
+        let described = serde_json::to_value(&action).map(describe_action).ok();
+        *self.shared.pending_action.lock().unwrap() = described;
+        Some(action)
+    }
+
+    fn after(&self, _: &S, next: &S) {
+        let Some(action) = self.shared.pending_action.lock().unwrap().take() else {
+            return;
+        };
+        let Ok(payload) = serde_json::to_string(next) else {
+            return;
+        };
+        let action = json!({ "action": action, "timestamp": timestamp() }).to_string();
+        self.shared
+            .log("ACTION", json!({ "action": action, "payload": payload }));
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Dispatch;
+    use crate::executor::{init as init_executor, tick};
+    use crate::transport::ChannelTransport;
+    use std::sync::mpsc::Receiver;
+    use std::time::{Duration, Instant};
+
+    #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    enum Op {
+        Add(i32),
+        Reset,
+    }
+
+    struct Server {
+        sent: Receiver<String>,
+        incoming: Sender<String>,
+    }
+
+    impl Server {
+        fn next(&self) -> Json {
+            let raw = self.sent.recv_timeout(Duration::from_secs(1)).unwrap();
+            serde_json::from_str(&raw).unwrap()
+        }
+    }
+
+    fn connect() -> (DevTools<i32, Op>, Server) {
+        let (sent_tx, sent_rx) = channel();
+        let (in_tx, in_rx) = channel();
+        let devtools = DevTools::connect(
+            ChannelTransport {
+                sent: sent_tx,
+                incoming: in_rx,
+            },
+            "test",
+        );
+        (
+            devtools,
+            Server {
+                sent: sent_rx,
+                incoming: in_tx,
+            },
+        )
+    }
+
+    fn reducer(state: i32, op: Op) -> i32 {
+        match op {
+            Op::Add(n) => state + n,
+            Op::Reset => 0,
+        }
+    }
+
+    #[test]
+    fn describes_actions_by_variant() {
+        assert_eq!(describe_action(json!("Reset")), json!({ "type": "Reset" }));
+        assert_eq!(
+            describe_action(json!({ "Add": 2 })),
+            json!({ "type": "Add", "payload": 2 })
+        );
+    }
+
+    #[test]
+    fn logs_in_and_subscribes_to_the_monitor_channel() {
+        let (_devtools, server) = connect();
+        assert_eq!(server.next()["event"], "#handshake");
+        assert_eq!(server.next()["event"], "login");
+
+        server
+            .incoming
+            .send(json!({ "rid": 1, "data": { "id": "socket-1" } }).to_string())
+            .unwrap();
+        server
+            .incoming
+            .send(json!({ "rid": 2, "data": "respond" }).to_string())
+            .unwrap();
+        let subscribe = server.next();
+        assert_eq!(subscribe["event"], "#subscribe");
+        assert_eq!(subscribe["data"]["channel"], "respond");
+    }
+
+    #[test]
+    fn streams_actions_with_resulting_state() {
+        init_executor();
+        let (devtools, server) = connect();
+        let store = Store::builder(0, reducer)
+            .middleware(devtools.clone())
+            .build();
+        devtools.attach(&store);
+        server.next(); // handshake
+        server.next(); // login
+        let init = server.next();
+        assert_eq!(init["data"]["type"], "INIT");
+        assert_eq!(init["data"]["payload"], "0");
+
+        store.dispatch(Op::Add(3));
+        tick();
+
+        let logged = server.next();
+        assert_eq!(logged["data"]["type"], "ACTION");
+        assert_eq!(logged["data"]["payload"], "3");
+        let action: Json =
+            serde_json::from_str(logged["data"]["action"].as_str().unwrap()).unwrap();
+        assert_eq!(action["action"], json!({ "type": "Add", "payload": 3 }));
+    }
+
+    #[test]
+    fn jump_commands_replace_the_state() {
+        init_executor();
+        let (devtools, server) = connect();
+        let store = Store::builder(0, reducer)
+            .middleware(devtools.clone())
+            .build();
+        devtools.attach(&store);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let s = seen.clone();
+        store.watch(move |v| s.lock().unwrap().push(*v));
+
+        server
+            .incoming
+            .send(
+                json!({
+                    "event": "#publish",
+                    "data": {
+                        "channel": "respond",
+                        "data": {
+                            "type": "DISPATCH",
+                            "action": { "type": "JUMP_TO_STATE", "index": 1 },
+                            "state": "42",
+                        },
+                    },
+                })
+                .to_string(),
+            )
+            .unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while store.get() != 42 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+            tick();
+        }
+        assert_eq!(store.get(), 42);
+        assert_eq!(*seen.lock().unwrap(), vec![42]);
+    }
+}
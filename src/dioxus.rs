@@ -0,0 +1,91 @@
+//! Hooks subscribing [Dioxus](https://dioxuslabs.com) components to stores
+//! (feature `dioxus`).
+
+use std::rc::Rc;
+
+use dioxus_core::{schedule_update, use_hook};
+
+use crate::{Action, Deps, Read, Reader, Store, Value};
+
+/// Returns the state of `store`, re-rendering the component whenever it
+/// changes.
+pub fn use_store<S: Value, A: Action, D: Deps>(store: &Store<S, A, D>) -> S {
+    use_reader(|| store.reader())
+}
+
+/// Returns the part of the state of `store` selected by `f`, re-rendering the
+/// component only when that part changes.
+///
+/// Like every hook, `f` is only taken on the first render: later renders keep
+/// the selector the component started with.
+pub fn use_selector<S, A, D, T, F>(store: &Store<S, A, D>, f: F) -> T
+where
+    S: Value,
+    A: Action,
+    D: Deps,
+    T: Value,
+    F: Fn(&S) -> T + Send + Sync + 'static,
+{
+    use_reader(|| store.derived(f))
+}
+
+/// Returns the value of the reader `make` returns on the first render,
+/// re-rendering the component whenever it changes.
+///
+/// The component is unsubscribed when it unmounts.
+pub fn use_reader<T: Value>(make: impl FnOnce() -> Reader<T>) -> T {
+    let reader = use_hook(|| {
+        let reader = make();
+        let update = schedule_update();
+        reader.watch(move |_| update());
+        Rc::new(reader)
+    });
+    reader.get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Dispatch;
+    use crate::executor::{init as init_executor, tick};
+    use dioxus_core::{Element, VNode, VirtualDom};
+    use std::sync::OnceLock;
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    static STORE: OnceLock<Store<(i32, i32), (i32, i32)>> = OnceLock::new();
+    static RENDERED: AtomicI32 = AtomicI32::new(-1);
+    static RENDERS: AtomicI32 = AtomicI32::new(0);
+
+    fn app() -> Element {
+        let first = use_selector(STORE.get().unwrap(), |s: &(i32, i32)| s.0);
+        RENDERED.store(first, Ordering::SeqCst);
+        RENDERS.fetch_add(1, Ordering::SeqCst);
+        VNode::empty()
+    }
+
+    #[test]
+    fn selector_rerenders_only_when_its_part_changes() {
+        init_executor();
+        let store = STORE.get_or_init(|| {
+            Store::new((0, 0), |s: (i32, i32), (a, b): (i32, i32)| {
+                (s.0 + a, s.1 + b)
+            })
+        });
+        let mut dom = VirtualDom::new(app);
+        dom.rebuild_in_place();
+        assert_eq!(RENDERS.load(Ordering::SeqCst), 1);
+
+        store.dispatch((0, 1));
+        tick();
+        dom.process_events();
+        dom.render_immediate_to_vec();
+        assert_eq!(RENDERS.load(Ordering::SeqCst), 1);
+
+        store.dispatch((2, 0));
+        tick();
+        dom.process_events();
+        dom.render_immediate_to_vec();
+        assert_eq!(RENDERS.load(Ordering::SeqCst), 2);
+        assert_eq!(RENDERED.load(Ordering::SeqCst), 2);
+    }
+}
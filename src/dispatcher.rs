@@ -0,0 +1,571 @@
+use std::fmt;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context as TaskContext, Poll};
+use std::time::Duration;
+
+use futures::Sink;
+use futures::future::BoxFuture;
+use futures::task::noop_waker_ref;
+
+use crate::channel::ChannelSender;
+use crate::schedule::{ScheduleHandle, spawn_cancellable};
+use crate::time::Clock;
+use crate::{Action, Dispatch};
+
+/// Why an action could not be dispatched. Carries the action back to the
+/// caller.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DispatchError<A> {
+    /// The store's queue is full.
+    Full(A),
+    /// The store has shut down.
+    Closed(A),
+}
+
+impl<A> DispatchError<A> {
+    pub fn is_full(&self) -> bool {
+        matches!(self, DispatchError::Full(_))
+    }
+
+    pub fn is_closed(&self) -> bool {
+        matches!(self, DispatchError::Closed(_))
+    }
+
+    /// Returns the action which was not dispatched.
+    pub fn into_inner(self) -> A {
+        match self {
+            DispatchError::Full(action) | DispatchError::Closed(action) => action,
+        }
+    }
+
+    fn with<B>(self, action: B) -> DispatchError<B> {
+        match self {
+            DispatchError::Full(_) => DispatchError::Full(action),
+            DispatchError::Closed(_) => DispatchError::Closed(action),
+        }
+    }
+}
+
+impl<A> fmt::Display for DispatchError<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DispatchError::Full(_) => f.write_str("store queue is full"),
+            DispatchError::Closed(_) => f.write_str("store is shut down"),
+        }
+    }
+}
+
+impl<A: fmt::Debug> std::error::Error for DispatchError<A> {}
+
+/// What [`dispatch`](Dispatch::dispatch) does with an action when the store
+/// already holds as many queued actions as its capacity. Set with
+/// [`StoreBuilder::overflow_policy`](crate::StoreBuilder::overflow_policy).
+///
+/// Actions dispatched after the store has shut down are always dropped.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Queues the action anyway, beyond the capacity.
+    #[default]
+    Grow,
+    /// Drops the action.
+    Drop,
+    /// Panics, to surface undersized queues or runaway producers.
+    Panic,
+}
+
+impl OverflowPolicy {
+    /// Whether an action dispatched into a full queue should still be sent.
+    pub(crate) fn overflowed(self) -> bool {
+        match self {
+            OverflowPolicy::Grow => true,
+            OverflowPolicy::Drop => false,
+            OverflowPolicy::Panic => panic!("store queue is full"),
+        }
+    }
+}
+
+/// Where a [`Dispatcher`] delivers its actions.
+pub(crate) trait ActionSink<A>: Send + Sync {
+    fn dispatch(&self, action: A);
+
+    /// Sends `action` on the high-priority lane, if the store has one.
+    fn dispatch_priority(&self, action: A);
+
+    fn dispatch_async(&self, action: A) -> BoxFuture<'static, ()>;
+
+    /// Whether the store behind this sink has shut down.
+    fn is_closed(&self) -> bool;
+
+    /// Sends the action returned by `make` if there is room in the queue.
+    /// `make` is only called once the action is certain to be sent.
+    fn try_dispatch_with(&self, make: &mut dyn FnMut() -> A) -> Result<(), DispatchError<()>>;
+}
+
+/// Sends actions, wrapped by `wrap`, into a channel.
+///
+/// Every send through [`dispatch`](ActionSink::dispatch) uses a fresh sender
+/// and is always accepted by an open channel. `try_dispatch_with` goes through
+/// one long-lived sender instead, which is parked once the channel holds more
+/// than its buffer size, so it reports a full queue.
+pub(crate) struct ChannelSink<T, A> {
+    sender: Mutex<ChannelSender<T>>,
+    priority: Option<ChannelSender<T>>,
+    wrap: fn(A) -> T,
+}
+
+impl<T, A> ChannelSink<T, A> {
+    pub(crate) fn new(sender: impl Into<ChannelSender<T>>, wrap: fn(A) -> T) -> Self {
+        ChannelSink {
+            sender: Mutex::new(sender.into()),
+            priority: None,
+            wrap,
+        }
+    }
+
+    /// Sends priority actions through `priority` instead of the normal queue.
+    pub(crate) fn with_priority(mut self, priority: ChannelSender<T>) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    fn sender(&self) -> ChannelSender<T> {
+        self.sender.lock().unwrap().clone()
+    }
+}
+
+impl<T: Send + 'static, A: Action> ActionSink<A> for ChannelSink<T, A> {
+    fn dispatch(&self, action: A) {
+        self.sender().dispatch((self.wrap)(action));
+    }
+
+    fn dispatch_priority(&self, action: A) {
+        let mut sender = match &self.priority {
+            Some(priority) => priority.clone(),
+            None => self.sender(),
+        };
+        sender.dispatch((self.wrap)(action));
+    }
+
+    fn dispatch_async(&self, action: A) -> BoxFuture<'static, ()> {
+        let mut sender = self.sender();
+        let message = (self.wrap)(action);
+        Box::pin(async move {
+            // A closed channel means the store is gone: drop the action, as
+            // `dispatch` does.
+            let _ = sender.send(message).await;
+        })
+    }
+
+    fn is_closed(&self) -> bool {
+        self.sender.lock().unwrap().is_closed()
+    }
+
+    fn try_dispatch_with(&self, make: &mut dyn FnMut() -> A) -> Result<(), DispatchError<()>> {
+        let mut sender = self.sender.lock().unwrap();
+        let mut cx = TaskContext::from_waker(noop_waker_ref());
+        match sender.poll_ready(&mut cx) {
+            Poll::Ready(Ok(())) => sender
+                .start_send((self.wrap)(make()))
+                .map_err(|_| DispatchError::Closed(())),
+            Poll::Ready(Err(_)) => Err(DispatchError::Closed(())),
+            Poll::Pending => Err(DispatchError::Full(())),
+        }
+    }
+}
+
+struct Mapped<A, F> {
+    parent: Arc<dyn ActionSink<A>>,
+    f: F,
+}
+
+impl<A, B, F> ActionSink<B> for Mapped<A, F>
+where
+    A: Action,
+    F: Fn(B) -> A + Send + Sync,
+{
+    fn dispatch(&self, action: B) {
+        self.parent.dispatch((self.f)(action));
+    }
+
+    fn dispatch_priority(&self, action: B) {
+        self.parent.dispatch_priority((self.f)(action));
+    }
+
+    fn dispatch_async(&self, action: B) -> BoxFuture<'static, ()> {
+        self.parent.dispatch_async((self.f)(action))
+    }
+
+    fn is_closed(&self) -> bool {
+        self.parent.is_closed()
+    }
+
+    fn try_dispatch_with(&self, make: &mut dyn FnMut() -> B) -> Result<(), DispatchError<()>> {
+        self.parent.try_dispatch_with(&mut || (self.f)(make()))
+    }
+}
+
+/// A cheap, cloneable handle which dispatches into a store.
+///
+/// Obtained from [`Store::dispatcher`](crate::Store::dispatcher) or
+/// [`Context::dispatcher`](crate::Context::dispatcher). It can be moved into UI
+/// callbacks, background tasks and other threads without sharing the store
+/// itself; it does not keep the store's state alive. Once the store has been
+/// dropped, actions dispatched through the handle are dropped too.
+///
+/// A dispatcher is also a [`Sink`] of actions, so a stream of actions can be
+/// [`forward`](futures::StreamExt::forward)ed into the store. The sink reports
+/// an error once the store has shut down.
+pub struct Dispatcher<A: Action> {
+    sink: Arc<dyn ActionSink<A>>,
+    clock: Arc<dyn Clock>,
+    /// The send started by the last `start_send`, if it has not completed.
+    sending: Mutex<Option<BoxFuture<'static, ()>>>,
+}
+
+impl<A: Action> Dispatcher<A> {
+    pub(crate) fn new(sink: Arc<dyn ActionSink<A>>, clock: Arc<dyn Clock>) -> Self {
+        Dispatcher {
+            sink,
+            clock,
+            sending: Mutex::new(None),
+        }
+    }
+
+    /// Queues `action` without waiting. A full queue is handled by the store's
+    /// [`OverflowPolicy`], which grows it by default; the action is dropped
+    /// only once the store has shut down.
+    pub fn dispatch(&self, action: A) {
+        self.sink.dispatch(action);
+    }
+
+    /// Queues `action` on the store's high-priority lane, which the reducer
+    /// drains before the normal queue.
+    pub fn dispatch_priority(&self, action: A) {
+        self.sink.dispatch_priority(action);
+    }
+
+    /// Queues `action` if the store's queue has room, or hands it back.
+    pub fn try_dispatch(&self, action: A) -> Result<(), DispatchError<A>> {
+        let mut slot = Some(action);
+        self.sink
+            .try_dispatch_with(&mut || slot.take().expect("called at most once"))
+            .map_err(|e| e.with(slot.take().expect("not sent on error")))
+    }
+
+    /// Queues `action`, waiting for room in the store's queue if it is full.
+    pub async fn dispatch_async(&self, action: A) {
+        self.sink.dispatch_async(action).await;
+    }
+
+    /// Queues every action of `actions` in order, waiting for room in the
+    /// store's queue whenever it is full, so that none is dropped.
+    ///
+    /// Unlike a loop of [`try_dispatch`](Dispatcher::try_dispatch), a full
+    /// queue never cuts the sequence short. Stops early only if the store
+    /// shuts down, which drops the remaining actions.
+    pub async fn dispatch_all(&self, actions: impl IntoIterator<Item = A>) {
+        for action in actions {
+            if self.sink.is_closed() {
+                return;
+            }
+            self.sink.dispatch_async(action).await;
+        }
+    }
+
+    /// Dispatches `action` once `delay` has elapsed on the store's clock.
+    ///
+    /// The returned handle cancels the dispatch if it has not happened yet.
+    pub fn dispatch_after(&self, delay: Duration, action: A) -> ScheduleHandle {
+        let sleep = self.clock.sleep(delay);
+        let sink = self.sink.clone();
+        spawn_cancellable(async move {
+            sleep.await;
+            sink.dispatch(action);
+        })
+    }
+
+    /// Dispatches the action returned by `f` every `interval` on the store's
+    /// clock, until cancelled through the returned handle or until the store
+    /// shuts down.
+    pub fn schedule_every<F>(&self, interval: Duration, f: F) -> ScheduleHandle
+    where
+        F: Fn() -> A + Send + 'static,
+    {
+        let sink = self.sink.clone();
+        let clock = self.clock.clone();
+        spawn_cancellable(async move {
+            // Deadlines are measured from the start so that slow ticks do not
+            // accumulate drift.
+            let mut next = clock.now() + interval;
+            loop {
+                clock
+                    .sleep(next.saturating_duration_since(clock.now()))
+                    .await;
+                if sink.is_closed() {
+                    break;
+                }
+                sink.dispatch(f());
+                next += interval;
+            }
+        })
+    }
+
+    /// Returns a `Dispatcher<B>` that maps actions `B -> A` before
+    /// dispatching through this handle.
+    pub fn map<B, F>(&self, f: F) -> Dispatcher<B>
+    where
+        B: Action,
+        F: Fn(B) -> A + Send + Sync + 'static,
+    {
+        let sink = Arc::new(Mapped {
+            parent: self.sink.clone(),
+            f,
+        });
+        Dispatcher::new(sink, self.clock.clone())
+    }
+}
+
+impl<A: Action> Clone for Dispatcher<A> {
+    fn clone(&self) -> Self {
+        Dispatcher::new(self.sink.clone(), self.clock.clone())
+    }
+}
+
+impl<A: Action> Sink<A> for Dispatcher<A> {
+    type Error = DispatchError<()>;
+
+    fn poll_ready(
+        mut self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        if self.as_mut().poll_flush(cx)?.is_pending() {
+            return Poll::Pending;
+        }
+        if self.sink.is_closed() {
+            return Poll::Ready(Err(DispatchError::Closed(())));
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, action: A) -> Result<(), Self::Error> {
+        if self.sink.is_closed() {
+            return Err(DispatchError::Closed(()));
+        }
+        *self.sending.get_mut().unwrap() = Some(self.sink.dispatch_async(action));
+        Ok(())
+    }
+
+    fn poll_flush(
+        mut self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        let sending = self.sending.get_mut().unwrap();
+        if let Some(send) = sending.as_mut() {
+            if send.as_mut().poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+            *sending = None;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    /// Flushes the pending action. Closing the sink does not shut the store
+    /// down.
+    fn poll_close(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_flush(cx)
+    }
+}
+
+impl<A: Action> Dispatch<A> for Dispatcher<A> {
+    fn dispatch(&self, action: A) {
+        Dispatcher::dispatch(self, action);
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::DispatchError;
+    use crate::executor::{init as init_executor, tick};
+    use crate::test::TestClock;
+    use crate::{Read, Store};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[test]
+    fn dispatches_from_another_thread() {
+        init_executor();
+        let store = Store::new(0, |s: i32, n: i32| s + n);
+        let dispatcher = store.dispatcher();
+        std::thread::spawn(move || dispatcher.dispatch(5))
+            .join()
+            .unwrap();
+        tick();
+        assert_eq!(store.get(), 5);
+    }
+
+    #[test]
+    fn map_converts_actions() {
+        init_executor();
+        let store = Store::new(0, |s: i32, n: i32| s + n);
+        let dispatcher = store.dispatcher().map(|s: &str| s.len() as i32);
+        dispatcher.clone().dispatch("abc");
+        dispatcher.dispatch("de");
+        tick();
+        assert_eq!(store.get(), 5);
+    }
+
+    #[test]
+    fn dropping_the_store_stops_its_task() {
+        init_executor();
+        let deps = Arc::new(());
+        let store = Store::new_with_deps(
+            0,
+            |s: i32, n: i32| -> (i32, crate::Effect<i32, Arc<()>>) {
+                (s + n, crate::Effect::none())
+            },
+            deps.clone(),
+        );
+        let dispatcher = store.dispatcher();
+        dispatcher.dispatch(1);
+        drop(store);
+        tick();
+        assert_eq!(Arc::strong_count(&deps), 1);
+        assert_eq!(dispatcher.try_dispatch(2), Err(DispatchError::Closed(2)));
+    }
+
+    #[test]
+    fn streams_forward_into_the_dispatcher() {
+        use futures::{FutureExt, StreamExt, stream};
+
+        init_executor();
+        let store = Store::new(0, |s: i32, n: i32| s + n);
+        let forwarded = stream::iter([1, 2, 3].map(Ok)).forward(store.dispatcher());
+        assert_eq!(forwarded.now_or_never(), Some(Ok(())));
+        tick();
+        assert_eq!(store.get(), 6);
+
+        store.shutdown();
+        let forwarded = stream::iter([4].map(Ok)).forward(store.dispatcher());
+        assert_eq!(
+            forwarded.now_or_never(),
+            Some(Err(DispatchError::Closed(())))
+        );
+    }
+
+    #[test]
+    fn try_dispatch_returns_action_when_full_or_closed() {
+        init_executor();
+        let store = Store::new_with_capacity(0, |s: i32, n: i32| s + n, 0);
+        let dispatcher = store.dispatcher().map(|n: u8| n as i32);
+        assert_eq!(dispatcher.try_dispatch(1), Ok(()));
+        assert_eq!(dispatcher.try_dispatch(2), Err(DispatchError::Full(2)));
+        tick();
+        assert_eq!(store.get(), 1);
+
+        store.shutdown();
+        assert_eq!(dispatcher.try_dispatch(3), Err(DispatchError::Closed(3)));
+    }
+
+    #[test]
+    fn dispatch_after_waits_for_the_store_clock() {
+        init_executor();
+        let clock = TestClock::new();
+        let store = Store::builder(0, |s: i32, n: i32| s + n)
+            .with_clock(clock.clone())
+            .build();
+        let handle = store.dispatcher().dispatch_after(Duration::from_secs(1), 5);
+        tick();
+        clock.advance(Duration::from_millis(999));
+        assert_eq!(store.get(), 0);
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(store.get(), 5);
+        assert!(handle.is_finished());
+    }
+
+    #[test]
+    fn cancelled_dispatch_after_never_fires() {
+        init_executor();
+        let clock = TestClock::new();
+        let store = Store::builder(0, |s: i32, n: i32| s + n)
+            .with_clock(clock.clone())
+            .build();
+        let handle = store.context().dispatch_after(Duration::from_secs(1), 5);
+        tick();
+        handle.cancel();
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(store.get(), 0);
+        assert!(handle.is_cancelled() && !handle.is_finished());
+    }
+
+    #[test]
+    fn schedule_every_repeats_until_cancelled() {
+        init_executor();
+        let clock = TestClock::new();
+        let store = Store::builder(0, |s: i32, n: i32| s + n)
+            .with_clock(clock.clone())
+            .build();
+        let handle = store.schedule_every(Duration::from_secs(1), || 1);
+        tick();
+        clock.advance(Duration::from_millis(3500));
+        assert_eq!(store.get(), 3);
+        handle.cancel();
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(store.get(), 3);
+    }
+
+    #[test]
+    fn schedule_every_stops_with_the_store() {
+        init_executor();
+        let clock = TestClock::new();
+        let store = Store::builder(0, |s: i32, n: i32| s + n)
+            .with_clock(clock.clone())
+            .build();
+        let handle = store.schedule_every(Duration::from_secs(1), || 1);
+        tick();
+        clock.advance(Duration::from_secs(1));
+        store.shutdown();
+        clock.advance(Duration::from_secs(1));
+        assert!(handle.is_finished());
+    }
+
+    #[test]
+    fn dispatch_async_waits_for_capacity() {
+        init_executor();
+        let store = Store::new_with_capacity(0, |s: i32, n: i32| s + n, 1);
+        let dispatcher = store.dispatcher();
+        let sent = Arc::new(AtomicUsize::new(0));
+        let s = sent.clone();
+        any_spawner::Executor::spawn(async move {
+            for n in 1..=5 {
+                dispatcher.dispatch_async(n).await;
+                s.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        tick();
+        assert_eq!(sent.load(Ordering::SeqCst), 5);
+        assert_eq!(store.get(), 15);
+    }
+
+    #[test]
+    fn dispatch_all_keeps_every_action_in_order() {
+        init_executor();
+        let store = Store::new_with_capacity(
+            Vec::new(),
+            |mut s: Vec<i32>, n: i32| {
+                s.push(n);
+                s
+            },
+            1,
+        );
+        let dispatcher = store.dispatcher();
+        any_spawner::Executor::spawn(async move {
+            dispatcher.dispatch_all(1..=5).await;
+        });
+        tick();
+        assert_eq!(store.get(), vec![1, 2, 3, 4, 5]);
+    }
+}
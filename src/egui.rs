@@ -0,0 +1,66 @@
+//! A store wrapper for [egui](https://www.egui.rs) apps (feature `egui`).
+
+use std::sync::Arc;
+
+use crate::{Action, Deps, Dispatch, Read, Store, Value};
+
+/// A store which asks egui to repaint whenever its state changes.
+///
+/// Immediate-mode UIs only redraw when something asks them to, so changes
+/// made by effects or other threads would otherwise wait for the next input
+/// event. Read the state once per frame with [`state`](EguiStore::state) and
+/// dispatch from widget handlers with [`dispatch`](EguiStore::dispatch);
+/// neither blocks nor awaits.
+pub struct EguiStore<S: Value, A: Action, D: Deps = ()> {
+    store: Store<S, A, D>,
+}
+
+impl<S: Value, A: Action, D: Deps> EguiStore<S, A, D> {
+    pub fn new(store: Store<S, A, D>, ctx: &egui::Context) -> Self {
+        let ctx = ctx.clone();
+        store.watch(move |_| ctx.request_repaint());
+        EguiStore { store }
+    }
+
+    /// The current state, shared rather than cloned.
+    pub fn state(&self) -> Arc<S> {
+        self.store.get_arc()
+    }
+
+    /// A copy of the current state.
+    pub fn get(&self) -> S {
+        self.store.get()
+    }
+
+    pub fn dispatch(&self, action: A) {
+        self.store.dispatch(action);
+    }
+
+    pub fn store(&self) -> &Store<S, A, D> {
+        &self.store
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{init as init_executor, tick};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn state_changes_request_a_repaint() {
+        init_executor();
+        let ctx = egui::Context::default();
+        let repaints = Arc::new(AtomicUsize::new(0));
+        let counted = repaints.clone();
+        ctx.set_request_repaint_callback(move |_| {
+            counted.fetch_add(1, Ordering::SeqCst);
+        });
+        let store = EguiStore::new(Store::new(0, |s: i32, n: i32| s + n), &ctx);
+
+        store.dispatch(1);
+        tick();
+        assert!(repaints.load(Ordering::SeqCst) > 0);
+        assert_eq!(*store.state(), 1);
+    }
+}
@@ -0,0 +1,282 @@
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::{Action, Deps, EffectReducer, Middleware, Value};
+
+/// An append-only log of actions, periodically compacted into a snapshot.
+pub trait EventLog<S, A>: Send + Sync + 'static {
+    /// Appends `action` to the log.
+    fn append(&self, action: &A) -> io::Result<()>;
+
+    /// Returns the latest snapshot, if any, and every action appended after it.
+    fn load(&self) -> io::Result<(Option<S>, Vec<A>)>;
+
+    /// Stores `snapshot` as the state after every appended action, and drops
+    /// those actions.
+    fn compact(&self, snapshot: &S) -> io::Result<()>;
+}
+
+struct MemoryEventLogInner<S, A> {
+    snapshot: Option<S>,
+    actions: Vec<A>,
+}
+
+/// An [`EventLog`] kept in memory. Clones share the same log.
+pub struct MemoryEventLog<S, A> {
+    inner: Arc<Mutex<MemoryEventLogInner<S, A>>>,
+}
+
+impl<S, A> MemoryEventLog<S, A> {
+    pub fn new() -> Self {
+        MemoryEventLog {
+            inner: Arc::new(Mutex::new(MemoryEventLogInner {
+                snapshot: None,
+                actions: Vec::new(),
+            })),
+        }
+    }
+
+    /// Number of actions appended since the last compaction.
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().actions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<S, A> Default for MemoryEventLog<S, A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, A> Clone for MemoryEventLog<S, A> {
+    fn clone(&self) -> Self {
+        MemoryEventLog {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<S, A> EventLog<S, A> for MemoryEventLog<S, A>
+where
+    S: Clone + Send + 'static,
+    A: Clone + Send + 'static,
+{
+    fn append(&self, action: &A) -> io::Result<()> {
+        self.inner.lock().unwrap().actions.push(action.clone());
+        Ok(())
+    }
+
+    fn load(&self) -> io::Result<(Option<S>, Vec<A>)> {
+        let guard = self.inner.lock().unwrap();
+        Ok((guard.snapshot.clone(), guard.actions.clone()))
+    }
+
+    fn compact(&self, snapshot: &S) -> io::Result<()> {
+        let mut guard = self.inner.lock().unwrap();
+        guard.snapshot = Some(snapshot.clone());
+        guard.actions.clear();
+        Ok(())
+    }
+}
+
+type ErrorHandler = Arc<dyn Fn(io::Error) + Send + Sync>;
+
+/// Event-sourcing configuration for a store, attached with
+/// [`StoreBuilder::event_sourced`](crate::StoreBuilder::event_sourced).
+///
+/// At construction the store state is rebuilt by folding the logged actions
+/// over the latest snapshot, or over the initial state if there is none.
+/// Effects are not run while rebuilding. Every action is then appended to the
+/// log before it is reduced; an action which cannot be appended is dropped,
+/// so the state never gets ahead of the log.
+///
+/// ```
+/// use uniflow::{Dispatch, EventSourcing, MemoryEventLog, Read, Store};
+///
+/// uniflow::manual_spawner::init().expect("init");
+///
+/// let log = MemoryEventLog::new();
+/// let store = Store::builder(0, |s: i32, n: i32| s + n)
+///     .event_sourced(EventSourcing::new(log.clone()))
+///     .build();
+/// store.dispatch(1);
+/// store.dispatch(2);
+/// uniflow::manual_spawner::step();
+///
+/// let rebuilt = Store::builder(0, |s: i32, n: i32| s + n)
+///     .event_sourced(EventSourcing::new(log))
+///     .build();
+/// assert_eq!(rebuilt.get(), 3);
+/// ```
+pub struct EventSourcing<L> {
+    log: L,
+    snapshot_every: Option<usize>,
+    since_snapshot: AtomicUsize,
+    on_error: Option<ErrorHandler>,
+}
+
+impl<L> EventSourcing<L> {
+    pub fn new(log: L) -> Self {
+        EventSourcing {
+            log,
+            snapshot_every: None,
+            since_snapshot: AtomicUsize::new(0),
+            on_error: None,
+        }
+    }
+
+    /// Compacts the log into a snapshot after every `actions` reduced actions.
+    pub fn snapshot_every(mut self, actions: usize) -> Self {
+        self.snapshot_every = Some(actions.max(1));
+        self
+    }
+
+    /// Calls `f` whenever reading, appending to or compacting the log fails.
+    pub fn on_error(mut self, f: impl Fn(io::Error) + Send + Sync + 'static) -> Self {
+        self.on_error = Some(Arc::new(f));
+        self
+    }
+
+    fn report<T>(&self, result: io::Result<T>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                if let Some(on_error) = &self.on_error {
+                    on_error(e);
+                }
+                None
+            }
+        }
+    }
+
+    /// Folds the logged actions with `reducer`, starting from the latest
+    /// snapshot or `initial`.
+    pub(crate) fn rebuild<S, A, D, R>(&self, initial: S, reducer: &R) -> S
+    where
+        S: Value,
+        A: Action,
+        D: Deps,
+        R: EffectReducer<S, A, D>,
+        L: EventLog<S, A>,
+    {
+        let Some((snapshot, actions)) = self.report(self.log.load()) else {
+            return initial;
+        };
+        self.since_snapshot.store(actions.len(), Ordering::Relaxed);
+        actions
+            .into_iter()
+            .fold(snapshot.unwrap_or(initial), |state, action| {
+                reducer(state, action).0
+            })
+    }
+}
+
+impl<S: Value, A: Action, L: EventLog<S, A>> Middleware<S, A> for EventSourcing<L> {
+    fn before(&self, _: &S, action: A) -> Option<A> {
+        self.report(self.log.append(&action)).map(|()| action)
+    }
+
+    fn after(&self, _: &S, next: &S) {
+        let Some(every) = self.snapshot_every else {
+            return;
+        };
+        if self.since_snapshot.fetch_add(1, Ordering::Relaxed) + 1 >= every
+            && self.report(self.log.compact(next)).is_some()
+        {
+            self.since_snapshot.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{init as init_executor, tick};
+    use crate::{Dispatch, Read, Store};
+
+    fn add(s: i32, n: i32) -> i32 {
+        s + n
+    }
+
+    fn sourced_store(log: MemoryEventLog<i32, i32>, every: usize) -> Store<i32, i32> {
+        Store::builder(0, add)
+            .event_sourced(EventSourcing::new(log).snapshot_every(every))
+            .build()
+    }
+
+    struct FailingLog;
+
+    impl EventLog<i32, i32> for FailingLog {
+        fn append(&self, _: &i32) -> io::Result<()> {
+            Err(io::Error::other("disk full"))
+        }
+
+        fn load(&self) -> io::Result<(Option<i32>, Vec<i32>)> {
+            Ok((None, Vec::new()))
+        }
+
+        fn compact(&self, _: &i32) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn actions_are_appended_before_reduction() {
+        init_executor();
+        let log = MemoryEventLog::new();
+        let store = sourced_store(log.clone(), 100);
+        store.dispatch(1);
+        store.dispatch(2);
+        tick();
+        assert_eq!(log.load().unwrap(), (None, vec![1, 2]));
+    }
+
+    #[test]
+    fn compaction_snapshots_state_and_truncates_log() {
+        init_executor();
+        let log = MemoryEventLog::new();
+        let store = sourced_store(log.clone(), 2);
+        for n in [1, 2, 3] {
+            store.dispatch(n);
+        }
+        tick();
+        assert_eq!(log.load().unwrap(), (Some(3), vec![3]));
+
+        let rebuilt = sourced_store(log, 2);
+        assert_eq!(rebuilt.get(), 6);
+    }
+
+    #[test]
+    fn rebuild_counts_pending_actions_towards_next_snapshot() {
+        init_executor();
+        let log = MemoryEventLog::new();
+        log.append(&5).unwrap();
+        let store = sourced_store(log.clone(), 2);
+        store.dispatch(1);
+        tick();
+        assert_eq!(log.load().unwrap(), (Some(6), vec![]));
+    }
+
+    #[test]
+    fn failed_append_drops_the_action() {
+        init_executor();
+        let errors = Arc::new(Mutex::new(0));
+        let e = errors.clone();
+        let store = Store::builder(0, add)
+            .event_sourced(
+                EventSourcing::new(FailingLog).on_error(move |_| *e.lock().unwrap() += 1),
+            )
+            .build();
+        store.dispatch(1);
+        tick();
+        assert_eq!(store.get(), 0);
+        assert_eq!(*errors.lock().unwrap(), 1);
+    }
+}
@@ -1,3 +1,7 @@
+use std::sync::OnceLock;
+
+static EXECUTOR: OnceLock<()> = OnceLock::new();
+
 pub fn tick() {
     crate::manual_spawner::step();
 }
@@ -5,3 +9,8 @@ pub fn tick() {
 pub fn init_test_executer() -> Result<(), any_spawner::ExecutorError> {
     crate::manual_spawner::init()
 }
+
+/// Initialise the manual spawner once for the whole test binary.
+pub fn init() {
+    EXECUTOR.get_or_init(|| init_test_executer().expect("Initialize global sync executor"));
+}
@@ -0,0 +1,287 @@
+//! An `extern "C"` API for embedding a store in C and C++ hosts (feature
+//! `ffi`).
+//!
+//! The app's own `staticlib` or `cdylib` crate builds its store and hands it
+//! to the host as an opaque [`UniflowStore`] handle; the functions below,
+//! exported unmangled, do the rest. Actions go in and state comes out as JSON
+//! strings:
+//!
+//! ```ignore
+//! #[unsafe(no_mangle)]
+//! pub extern "C" fn app_store_new() -> *mut UniflowStore {
+//!     uniflow::ffi::into_raw(Store::new(State::default(), reducer))
+//! }
+//! ```
+//!
+//! ```c
+//! UniflowStore *store = app_store_new();
+//! UniflowSubscription *sub = uniflow_store_watch(store, on_change, ctx);
+//! uniflow_store_dispatch(store, "{\"Add\":1}");
+//! char *state = uniflow_store_state(store);
+//! uniflow_string_free(state);
+//! uniflow_subscription_free(sub);
+//! uniflow_store_free(store);
+//! ```
+//!
+//! The state callback runs on whichever thread the store's executor reduces
+//! on, and the string it is passed only lives for the duration of the call.
+//!
+//! No function unwinds into the host: null handles and panics are reported
+//! as the function's failure value, as are states which cannot be
+//! serialized as JSON, such as maps with non-string keys.
+
+use std::ffi::{CStr, CString, c_char, c_int, c_void};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::ptr;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::json_store::JsonStore;
+use crate::subscription::Subscription;
+use crate::{Action, Deps, Store, Value};
+
+/// An opaque handle to a store.
+pub struct UniflowStore(JsonStore);
+
+/// An opaque handle to a state callback registered with
+/// [`uniflow_store_watch`].
+pub struct UniflowSubscription(Subscription);
+
+/// Called with the new state, as a NUL-terminated JSON string, and the
+/// `user_data` it was registered with. The state is null if it failed to
+/// serialize.
+pub type UniflowStateCallback = extern "C" fn(state: *const c_char, user_data: *mut c_void);
+
+/// Moves `store` behind a handle for the host, to be released with
+/// [`uniflow_store_free`].
+pub fn into_raw<S, A, D>(store: Store<S, A, D>) -> *mut UniflowStore
+where
+    S: Value + Serialize,
+    A: Action + DeserializeOwned,
+    D: Deps,
+{
+    Box::into_raw(Box::new(UniflowStore(JsonStore::new(store))))
+}
+
+/// Dispatches the action serialized as JSON in `action`. Returns 0, or -1 if
+/// either pointer is null, or `action` is not valid UTF-8 or not valid JSON
+/// for the store's action type.
+///
+/// # Safety
+///
+/// `store` must be null or a live handle, and `action` null or a
+/// NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn uniflow_store_dispatch(
+    store: *const UniflowStore,
+    action: *const c_char,
+) -> c_int {
+    if store.is_null() || action.is_null() {
+        return -1;
+    }
+    guard(-1, || {
+        let (store, action) = unsafe { (&*store, CStr::from_ptr(action)) };
+        match action.to_str().map(|action| store.0.dispatch(action)) {
+            Ok(Ok(())) => 0,
+            _ => -1,
+        }
+    })
+}
+
+/// The current state as a JSON string, to be released with
+/// [`uniflow_string_free`]. Null if `store` is null or the state fails to
+/// serialize.
+///
+/// # Safety
+///
+/// `store` must be null or a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn uniflow_store_state(store: *const UniflowStore) -> *mut c_char {
+    if store.is_null() {
+        return ptr::null_mut();
+    }
+    guard(ptr::null_mut(), || {
+        let store = unsafe { &*store };
+        match store.0.state().ok().and_then(to_c_string) {
+            Some(state) => state.into_raw(),
+            None => ptr::null_mut(),
+        }
+    })
+}
+
+/// Calls `callback` with the new state after every change, until the returned
+/// handle is released with [`uniflow_subscription_free`]. Null if `store` is
+/// null.
+///
+/// # Safety
+///
+/// `store` must be null or a live handle, and `user_data` must be safe to
+/// use from the threads the store reduces on until the subscription is
+/// released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn uniflow_store_watch(
+    store: *const UniflowStore,
+    callback: UniflowStateCallback,
+    user_data: *mut c_void,
+) -> *mut UniflowSubscription {
+    if store.is_null() {
+        return ptr::null_mut();
+    }
+    guard(ptr::null_mut(), || {
+        let store = unsafe { &*store };
+        let user_data = UserData(user_data);
+        let subscription = store.0.watch(move |state| {
+            let state = state.ok().and_then(to_c_string);
+            let state = state.as_deref().map_or(ptr::null(), CStr::as_ptr);
+            callback(state, user_data.get());
+        });
+        Box::into_raw(Box::new(UniflowSubscription(subscription)))
+    })
+}
+
+/// Unregisters the callback and releases the handle. Null is ignored.
+///
+/// # Safety
+///
+/// `subscription` must be null or a handle from [`uniflow_store_watch`] not
+/// yet released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn uniflow_subscription_free(subscription: *mut UniflowSubscription) {
+    if !subscription.is_null() {
+        guard((), || {
+            unsafe { Box::from_raw(subscription) }.0.unsubscribe()
+        });
+    }
+}
+
+/// Releases the handle. The store stops once its last handle and context are
+/// gone. Null is ignored.
+///
+/// # Safety
+///
+/// `store` must be null or a handle from [`into_raw`] not yet released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn uniflow_store_free(store: *mut UniflowStore) {
+    if !store.is_null() {
+        guard((), || drop(unsafe { Box::from_raw(store) }));
+    }
+}
+
+/// Releases a string returned by [`uniflow_store_state`]. Null is ignored.
+///
+/// # Safety
+///
+/// `string` must be null or a string from [`uniflow_store_state`] not yet
+/// released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn uniflow_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        guard((), || drop(unsafe { CString::from_raw(string) }));
+    }
+}
+
+struct UserData(*mut c_void);
+
+impl UserData {
+    fn get(&self) -> *mut c_void {
+        self.0
+    }
+}
+
+// SAFETY: the host promises, in `uniflow_store_watch`, that `user_data` may
+// be used from the store's threads.
+unsafe impl Send for UserData {}
+unsafe impl Sync for UserData {}
+
+/// Runs `f`, returning `failed` instead if it panics: unwinding out of an
+/// `extern "C"` function aborts the host.
+fn guard<T>(failed: T, f: impl FnOnce() -> T) -> T {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(failed)
+}
+
+fn to_c_string(json: String) -> Option<CString> {
+    // JSON escapes control characters, so it never contains a NUL.
+    CString::new(json).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{init as init_executor, tick};
+    use std::sync::Mutex;
+
+    extern "C" fn record(state: *const c_char, user_data: *mut c_void) {
+        let seen = unsafe { &*(user_data as *const Mutex<Vec<String>>) };
+        let state = unsafe { CStr::from_ptr(state) };
+        seen.lock()
+            .unwrap()
+            .push(state.to_str().unwrap().to_string());
+    }
+
+    #[test]
+    fn dispatches_and_watches_through_c_strings() {
+        init_executor();
+        let store = into_raw(Store::new(vec![1], |mut s: Vec<i32>, n: i32| {
+            s.push(n);
+            s
+        }));
+        let seen = Mutex::new(Vec::<String>::new());
+        let user_data = &seen as *const _ as *mut c_void;
+
+        unsafe {
+            let subscription = uniflow_store_watch(store, record, user_data);
+            assert_eq!(uniflow_store_dispatch(store, c"2".as_ptr()), 0);
+            assert_eq!(uniflow_store_dispatch(store, c"\"three\"".as_ptr()), -1);
+            tick();
+            uniflow_subscription_free(subscription);
+            assert_eq!(uniflow_store_dispatch(store, c"4".as_ptr()), 0);
+            tick();
+
+            let state = uniflow_store_state(store);
+            assert_eq!(CStr::from_ptr(state).to_str(), Ok("[1,2,4]"));
+            uniflow_string_free(state);
+            uniflow_store_free(store);
+        }
+        assert_eq!(*seen.lock().unwrap(), vec!["[1,2]".to_string()]);
+    }
+
+    extern "C" fn record_null(state: *const c_char, user_data: *mut c_void) {
+        let nulls = unsafe { &*(user_data as *const Mutex<usize>) };
+        if state.is_null() {
+            *nulls.lock().unwrap() += 1;
+        }
+    }
+
+    #[test]
+    fn failures_are_reported_rather_than_unwound() {
+        use std::collections::BTreeMap;
+
+        init_executor();
+        // JSON objects only have string keys.
+        let store = into_raw(Store::new(
+            BTreeMap::new(),
+            |mut s: BTreeMap<(i32, i32), i32>, n: i32| {
+                s.insert((n, n), n);
+                s
+            },
+        ));
+        let nulls = Mutex::new(0);
+        let user_data = &nulls as *const _ as *mut c_void;
+
+        unsafe {
+            assert_eq!(uniflow_store_dispatch(ptr::null(), c"1".as_ptr()), -1);
+            assert_eq!(uniflow_store_dispatch(store, ptr::null()), -1);
+            assert!(uniflow_store_state(ptr::null()).is_null());
+            assert!(uniflow_store_watch(ptr::null(), record_null, user_data).is_null());
+
+            let subscription = uniflow_store_watch(store, record_null, user_data);
+            assert_eq!(uniflow_store_dispatch(store, c"1".as_ptr()), 0);
+            tick();
+            assert!(uniflow_store_state(store).is_null());
+            uniflow_subscription_free(subscription);
+            uniflow_store_free(store);
+        }
+        assert_eq!(*nulls.lock().unwrap(), 1);
+    }
+}
@@ -0,0 +1,259 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{Action, Deps, Middleware, Store, Value};
+
+type Setter<S> = Box<dyn Fn(S) + Send + Sync>;
+
+struct HistoryInner<S, A> {
+    entries: Vec<(Option<A>, S)>,
+    cursor: usize,
+    limit: Option<usize>,
+    pending: Option<A>,
+    set_state: Option<Setter<S>>,
+}
+
+/// Records every state transition of a store for time-travel debugging.
+///
+/// Attach it as a [`Middleware`] and then [`attach`](History::attach) it to the
+/// built store:
+///
+/// ```
+/// use uniflow::{Dispatch, History, Read, Store};
+///
+/// uniflow::manual_spawner::init().expect("init");
+///
+/// let history = History::new();
+/// let store = Store::builder(0, |s: i32, n: i32| s + n)
+///     .middleware(history.clone())
+///     .build();
+/// history.attach(&store);
+///
+/// store.dispatch(1);
+/// store.dispatch(2);
+/// uniflow::manual_spawner::step();
+/// assert_eq!(store.get(), 3);
+///
+/// history.step_back();
+/// uniflow::manual_spawner::step();
+/// assert_eq!(store.get(), 1);
+/// ```
+///
+/// Entry `0` is the state before the first recorded action; entry `n` is the
+/// action `n` together with the state it produced. Jumping replaces the store
+/// state, so watchers and readers fire as for any other change. Dispatching a
+/// new action while rewound discards the entries after the current one.
+pub struct History<S, A> {
+    inner: Arc<Mutex<HistoryInner<S, A>>>,
+}
+
+impl<S: Value, A: Action + Clone> History<S, A> {
+    pub fn new() -> Self {
+        History {
+            inner: Arc::new(Mutex::new(HistoryInner {
+                entries: Vec::new(),
+                cursor: 0,
+                limit: None,
+                pending: None,
+                set_state: None,
+            })),
+        }
+    }
+
+    /// Keeps at most `limit` entries, forgetting the oldest ones first.
+    pub fn with_limit(limit: usize) -> Self {
+        let history = Self::new();
+        history.inner.lock().unwrap().limit = Some(limit.max(1));
+        history
+    }
+
+    /// Lets the history replace the state of `store` when jumping.
+    pub fn attach<D: Deps>(&self, store: &Store<S, A, D>) {
+        self.inner.lock().unwrap().set_state = Some(Box::new(store.state_setter()));
+    }
+
+    /// All recorded `(action, state)` pairs, oldest first.
+    pub fn entries(&self) -> Vec<(Option<A>, S)> {
+        self.inner.lock().unwrap().entries.clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Index of the entry the store currently reflects.
+    pub fn cursor(&self) -> usize {
+        self.inner.lock().unwrap().cursor
+    }
+
+    /// Replaces the store state with the state of entry `index`.
+    ///
+    /// Returns `false` if there is no such entry.
+    pub fn jump_to(&self, index: usize) -> bool {
+        let mut guard = self.inner.lock().unwrap();
+        let Some((_, state)) = guard.entries.get(index) else {
+            return false;
+        };
+        let state = state.clone();
+        guard.cursor = index;
+        if let Some(set_state) = &guard.set_state {
+            set_state(state);
+        }
+        true
+    }
+
+    pub fn step_back(&self) -> bool {
+        match self.cursor().checked_sub(1) {
+            Some(index) => self.jump_to(index),
+            None => false,
+        }
+    }
+
+    pub fn step_forward(&self) -> bool {
+        self.jump_to(self.cursor() + 1)
+    }
+}
+
+impl<S: Value, A: Action + Clone> Default for History<S, A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, A> Clone for History<S, A> {
+    fn clone(&self) -> Self {
+        History {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<S: Value, A: Action + Clone> Middleware<S, A> for History<S, A> {
+    fn before(&self, state: &S, action: A) -> Option<A> {
+        let mut guard = self.inner.lock().unwrap();
+        if guard.entries.is_empty() {
+            guard.entries.push((None, state.clone()));
+        }
+        guard.pending = Some(action.clone());
+        Some(action)
+    }
+
+    fn after(&self, _: &S, next: &S) {
+        let mut guard = self.inner.lock().unwrap();
+        let action = guard.pending.take();
+        let keep = guard.cursor + 1;
+        guard.entries.truncate(keep);
+        guard.entries.push((action, next.clone()));
+        if let Some(limit) = guard.limit
+            && guard.entries.len() > limit
+        {
+            let excess = guard.entries.len() - limit;
+            guard.entries.drain(..excess);
+        }
+        guard.cursor = guard.entries.len() - 1;
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{init as init_executor, tick};
+    use crate::{Dispatch, Read};
+
+    fn recorded_store() -> (Store<i32, i32>, History<i32, i32>) {
+        let history = History::new();
+        let store = Store::builder(0, |s: i32, n: i32| s + n)
+            .middleware(history.clone())
+            .build();
+        history.attach(&store);
+        (store, history)
+    }
+
+    #[test]
+    fn records_action_state_pairs() {
+        init_executor();
+        let (store, history) = recorded_store();
+        store.dispatch(1);
+        store.dispatch(2);
+        tick();
+        assert_eq!(
+            history.entries(),
+            vec![(None, 0), (Some(1), 1), (Some(2), 3)]
+        );
+        assert_eq!(history.cursor(), 2);
+    }
+
+    #[test]
+    fn jump_to_replaces_state_and_notifies_watchers() {
+        init_executor();
+        let (store, history) = recorded_store();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let s = seen.clone();
+        let doubled = store.derived(|v| v * 2);
+        doubled.watch(move |v| s.lock().unwrap().push(*v));
+        store.dispatch(1);
+        store.dispatch(2);
+        tick();
+
+        assert!(history.jump_to(0));
+        tick();
+        assert_eq!(store.get(), 0);
+        assert_eq!(*seen.lock().unwrap(), vec![2, 6, 0]);
+        assert!(!history.jump_to(7));
+    }
+
+    #[test]
+    fn step_back_and_forward_move_the_cursor() {
+        init_executor();
+        let (store, history) = recorded_store();
+        store.dispatch(1);
+        store.dispatch(2);
+        tick();
+
+        assert!(history.step_back());
+        tick();
+        assert_eq!(store.get(), 1);
+        assert!(history.step_forward());
+        tick();
+        assert_eq!(store.get(), 3);
+        assert!(!history.step_forward());
+    }
+
+    #[test]
+    fn new_action_while_rewound_discards_the_future() {
+        init_executor();
+        let (store, history) = recorded_store();
+        store.dispatch(1);
+        store.dispatch(2);
+        tick();
+        history.step_back();
+        tick();
+
+        store.dispatch(10);
+        tick();
+        assert_eq!(store.get(), 11);
+        assert_eq!(
+            history.entries(),
+            vec![(None, 0), (Some(1), 1), (Some(10), 11)]
+        );
+    }
+
+    #[test]
+    fn limit_forgets_oldest_entries() {
+        init_executor();
+        let history = History::with_limit(2);
+        let store = Store::builder(0, |s: i32, n: i32| s + n)
+            .middleware(history.clone())
+            .build();
+        store.dispatch(1);
+        store.dispatch(2);
+        tick();
+        assert_eq!(history.entries(), vec![(Some(1), 1), (Some(2), 3)]);
+        assert_eq!(history.cursor(), 1);
+    }
+}
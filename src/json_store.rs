@@ -0,0 +1,60 @@
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::subscription::Subscription;
+use crate::{Action, Deps, Dispatch, Read, Store, Value};
+
+type DispatchJson = Box<dyn Fn(&str) -> serde_json::Result<()> + Send + Sync>;
+type StateJson = Box<dyn Fn() -> serde_json::Result<String> + Send + Sync>;
+type OnChange = Box<dyn Fn(serde_json::Result<String>) + Send + Sync>;
+type WatchJson = Box<dyn Fn(OnChange) -> Subscription + Send + Sync>;
+
+/// A store with its types erased, whose actions go in and state comes out as
+/// JSON: the shape in which stores cross FFI boundaries.
+pub(crate) struct JsonStore {
+    dispatch: DispatchJson,
+    state: StateJson,
+    watch: WatchJson,
+}
+
+impl JsonStore {
+    pub(crate) fn new<S, A, D>(store: Store<S, A, D>) -> Self
+    where
+        S: Value + Serialize,
+        A: Action + DeserializeOwned,
+        D: Deps,
+    {
+        let store = std::sync::Arc::new(store);
+        let (for_state, for_watch) = (store.clone(), store.clone());
+        JsonStore {
+            dispatch: Box::new(move |json| {
+                store.dispatch(serde_json::from_str(json)?);
+                Ok(())
+            }),
+            state: Box::new(move || serde_json::to_string(&for_state.get())),
+            watch: Box::new(move |on_change| {
+                for_watch.watch(move |state| on_change(serde_json::to_string(state)))
+            }),
+        }
+    }
+
+    /// Dispatches the action serialized in `json`.
+    pub(crate) fn dispatch(&self, json: &str) -> serde_json::Result<()> {
+        (self.dispatch)(json)
+    }
+
+    /// The current state as JSON. Fails for states serde_json cannot
+    /// express, such as maps with non-string keys.
+    pub(crate) fn state(&self) -> serde_json::Result<String> {
+        (self.state)()
+    }
+
+    /// Calls `on_change` with the new state after every change, or with the
+    /// error if it fails to serialize.
+    pub(crate) fn watch(
+        &self,
+        on_change: impl Fn(serde_json::Result<String>) + Send + Sync + 'static,
+    ) -> Subscription {
+        (self.watch)(Box::new(on_change))
+    }
+}
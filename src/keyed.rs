@@ -0,0 +1,185 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::Value;
+use crate::node::{ReadableNode, RegionNode, SourceNode};
+use crate::reader::Reader;
+use crate::region::Changed;
+
+type ItemNode<I> = Arc<dyn ReadableNode<Option<I>>>;
+type WeakItemNode<I> = Weak<dyn ReadableNode<Option<I>>>;
+type MakeItem<K, I> = Box<dyn Fn(K) -> ItemNode<I> + Send + Sync>;
+
+/// Per-item readers over a keyed collection in the store state.
+///
+/// Returned by [`Store::reader_each`](crate::Store::reader_each). The reader
+/// for one key only notifies when that item changes, is added or is removed,
+/// so editing one element of a long list does not re-run the watchers of every
+/// other element.
+pub struct KeyedReaders<K: Value, I: Value> {
+    keys: Reader<Vec<K>>,
+    make: MakeItem<K, I>,
+    items: Mutex<HashMap<K, WeakItemNode<I>>>,
+}
+
+impl<K, I> KeyedReaders<K, I>
+where
+    K: Value + Hash + Eq,
+    I: Value,
+{
+    pub(crate) fn new<S, C, F>(source: Arc<SourceNode<S>>, collection: C, key: F) -> Self
+    where
+        S: Value,
+        C: Fn(&S) -> &Vec<I> + Send + Sync + 'static,
+        F: Fn(&I) -> K + Send + Sync + 'static,
+    {
+        let collection = Arc::new(collection);
+        let key = Arc::new(key);
+        let keys = {
+            let (collection, key) = (collection.clone(), key.clone());
+            Reader::new(RegionNode::new(
+                source.clone(),
+                Changed::ALL,
+                move |s: &S| collection(s).iter().map(|item| key(item)).collect(),
+            ))
+        };
+        let make = Box::new(move |wanted: K| -> ItemNode<I> {
+            let (collection, key) = (collection.clone(), key.clone());
+            // Where the item was last found; checked first, since items
+            // rarely move.
+            let hint = AtomicUsize::new(0);
+            RegionNode::new(source.clone(), Changed::ALL, move |s: &S| {
+                let items = collection(s);
+                let at = hint.load(Ordering::Relaxed);
+                if let Some(item) = items.get(at).filter(|item| key(item) == wanted) {
+                    return Some(item.clone());
+                }
+                let at = items.iter().position(|item| key(item) == wanted)?;
+                hint.store(at, Ordering::Relaxed);
+                Some(items[at].clone())
+            })
+        });
+        KeyedReaders {
+            keys,
+            make,
+            items: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// A reader over the keys of the collection, in order. It only notifies
+    /// when items are added, removed or reordered.
+    pub fn keys(&self) -> Reader<Vec<K>> {
+        self.keys.clone()
+    }
+
+    /// A reader over the item with `key`, or `None` while there is no such
+    /// item. Readers for the same key share one selection.
+    pub fn get(&self, key: K) -> Reader<Option<I>> {
+        let mut items = self.items.lock().unwrap();
+        if let Some(node) = items.get(&key).and_then(Weak::upgrade) {
+            return Reader::new(node);
+        }
+        items.retain(|_, node| node.strong_count() > 0);
+        let node = (self.make)(key.clone());
+        items.insert(key, Arc::downgrade(&node));
+        Reader::new(node)
+    }
+}
+
+type OnInsert<I> = Box<dyn Fn(usize, &I) + Send + Sync>;
+type OnUpdate<I> = Box<dyn Fn(usize, &I, &I) + Send + Sync>;
+type OnRemove<I> = Box<dyn Fn(&I) + Send + Sync>;
+
+/// Callbacks for [`Store::watch_diff`](crate::Store::watch_diff), invoked with
+/// a keyed diff between successive versions of a collection.
+///
+/// For each change, removed items are reported first, then inserted and
+/// updated items in their order in the new collection, with their new index.
+/// Items which only moved are not reported.
+pub struct CollectionDiff<I> {
+    on_insert: OnInsert<I>,
+    on_update: OnUpdate<I>,
+    on_remove: OnRemove<I>,
+}
+
+impl<I> CollectionDiff<I> {
+    pub fn new() -> Self {
+        CollectionDiff {
+            on_insert: Box::new(|_, _| {}),
+            on_update: Box::new(|_, _, _| {}),
+            on_remove: Box::new(|_| {}),
+        }
+    }
+
+    /// Called with the index and item of every new key.
+    pub fn on_insert<F: Fn(usize, &I) + Send + Sync + 'static>(mut self, f: F) -> Self {
+        self.on_insert = Box::new(f);
+        self
+    }
+
+    /// Called with the index, the previous item and the new item of every
+    /// key whose item changed.
+    pub fn on_update<F: Fn(usize, &I, &I) + Send + Sync + 'static>(mut self, f: F) -> Self {
+        self.on_update = Box::new(f);
+        self
+    }
+
+    /// Called with the last item of every key which is gone.
+    pub fn on_remove<F: Fn(&I) + Send + Sync + 'static>(mut self, f: F) -> Self {
+        self.on_remove = Box::new(f);
+        self
+    }
+}
+
+impl<I> Default for CollectionDiff<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Diffs each version of a collection against the previous one.
+pub(crate) struct Differ<K, I> {
+    previous: HashMap<K, I>,
+    callbacks: CollectionDiff<I>,
+}
+
+impl<K: Hash + Eq, I: Value> Differ<K, I> {
+    pub(crate) fn new(items: &[I], key: impl Fn(&I) -> K, callbacks: CollectionDiff<I>) -> Self {
+        let previous = items.iter().map(|item| (key(item), item.clone())).collect();
+        Differ {
+            previous,
+            callbacks,
+        }
+    }
+
+    pub(crate) fn update(&mut self, items: &[I], key: impl Fn(&I) -> K) {
+        let keys: Vec<K> = items.iter().map(key).collect();
+        let present: HashSet<&K> = keys.iter().collect();
+        let on_remove = &self.callbacks.on_remove;
+        self.previous.retain(|key, item| {
+            let kept = present.contains(key);
+            if !kept {
+                on_remove(item);
+            }
+            kept
+        });
+        let mut next = HashMap::with_capacity(items.len());
+        for (index, (item, key)) in items.iter().zip(keys).enumerate() {
+            let item = match self.previous.remove(&key) {
+                None => {
+                    (self.callbacks.on_insert)(index, item);
+                    item.clone()
+                }
+                Some(old) if old != *item => {
+                    (self.callbacks.on_update)(index, &old, item);
+                    item.clone()
+                }
+                Some(old) => old,
+            };
+            next.insert(key, item);
+        }
+        self.previous = next;
+    }
+}
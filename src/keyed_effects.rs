@@ -0,0 +1,234 @@
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::channel::oneshot;
+use futures::future::AbortHandle;
+use web_time::Instant;
+
+/// A key of any hashable type, compared by type and value.
+trait DynKey: Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+    fn eq_key(&self, other: &dyn Any) -> bool;
+    fn hash_key(&self, state: &mut dyn Hasher);
+}
+
+impl<K: Hash + Eq + Send + Sync + 'static> DynKey for K {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_key(&self, other: &dyn Any) -> bool {
+        other.downcast_ref::<K>() == Some(self)
+    }
+
+    fn hash_key(&self, mut state: &mut dyn Hasher) {
+        TypeId::of::<K>().hash(&mut state);
+        self.hash(&mut state);
+    }
+}
+
+/// The ID a cancellable effect was started with.
+#[derive(Clone)]
+pub(crate) struct EffectKey(Arc<dyn DynKey>);
+
+impl EffectKey {
+    pub(crate) fn new<K: Hash + Eq + Send + Sync + 'static>(key: K) -> Self {
+        EffectKey(Arc::new(key))
+    }
+}
+
+impl PartialEq for EffectKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.eq_key(other.0.as_any())
+    }
+}
+
+impl Eq for EffectKey {}
+
+impl Hash for EffectKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash_key(state);
+    }
+}
+
+/// How many effects made [`cancellable`](crate::Effect::cancellable) under
+/// one key may run at once. Configured per key with
+/// [`StoreBuilder::concurrency`](crate::StoreBuilder::concurrency).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Concurrency {
+    /// Runs up to this many effects at once. Later effects wait for a running
+    /// one to finish, and start in the order they were spawned.
+    Queue(usize),
+    /// Runs up to this many effects at once. Effects spawned while the limit
+    /// is reached are dropped.
+    Drop(usize),
+    /// Runs one effect at a time: a new effect cancels the running one.
+    Latest,
+}
+
+/// The effects running under a key with a [`Concurrency`] limit.
+#[derive(Default)]
+struct Slots {
+    started: usize,
+    waiting: VecDeque<oneshot::Sender<Permit>>,
+}
+
+/// The state a store keeps for effects started with a key: the cancellable
+/// effects still running, when throttled effects last ran, and the
+/// concurrency limits.
+#[derive(Default)]
+pub(crate) struct KeyedEffects {
+    next: AtomicU64,
+    live: Mutex<HashMap<EffectKey, Vec<(u64, AbortHandle)>>>,
+    throttled: Mutex<HashMap<EffectKey, Instant>>,
+    policies: HashMap<EffectKey, Concurrency>,
+    slots: Mutex<HashMap<EffectKey, Slots>>,
+}
+
+/// Lets an effect run under a [`Concurrency`] limit; hands its slot on when
+/// dropped.
+pub(crate) struct Permit(Option<(Arc<KeyedEffects>, EffectKey)>);
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        if let Some((keyed, key)) = self.0.take() {
+            keyed.release(&key);
+        }
+    }
+}
+
+impl KeyedEffects {
+    pub(crate) fn new(policies: HashMap<EffectKey, Concurrency>) -> Self {
+        KeyedEffects {
+            policies,
+            ..Self::default()
+        }
+    }
+
+    /// Records a running effect, returning the ticket to pass to
+    /// [`finished`](KeyedEffects::finished).
+    pub(crate) fn register(&self, key: EffectKey, abort: AbortHandle) -> u64 {
+        let ticket = self.next.fetch_add(1, Ordering::Relaxed);
+        let mut live = self.live.lock().unwrap();
+        live.entry(key).or_default().push((ticket, abort));
+        ticket
+    }
+
+    pub(crate) fn finished(&self, key: &EffectKey, ticket: u64) {
+        let mut live = self.live.lock().unwrap();
+        if let Some(running) = live.get_mut(key) {
+            running.retain(|(t, _)| *t != ticket);
+            if running.is_empty() {
+                live.remove(key);
+            }
+        }
+    }
+
+    /// Waits until the effect registered under `key` with `ticket` may run
+    /// under the key's concurrency limit, or returns `None` if it is dropped.
+    pub(crate) async fn admit(self: &Arc<Self>, key: &EffectKey, ticket: u64) -> Option<Permit> {
+        let limit = match self.policies.get(key) {
+            None => return Some(Permit(None)),
+            Some(Concurrency::Latest) => {
+                self.cancel_except(key, ticket);
+                return Some(Permit(None));
+            }
+            Some(Concurrency::Queue(limit) | Concurrency::Drop(limit)) => *limit,
+        };
+        let waiting = {
+            let mut slots = self.slots.lock().unwrap();
+            let slots = slots.entry(key.clone()).or_default();
+            if slots.started < limit {
+                slots.started += 1;
+                return Some(Permit(Some((self.clone(), key.clone()))));
+            }
+            if let Some(Concurrency::Drop(_)) = self.policies.get(key) {
+                return None;
+            }
+            let (sender, receiver) = oneshot::channel();
+            slots.waiting.push_back(sender);
+            receiver
+        };
+        waiting.await.ok()
+    }
+
+    /// Hands a finished effect's slot to the next waiting effect, if any.
+    fn release(self: &Arc<Self>, key: &EffectKey) {
+        let mut slots = self.slots.lock().unwrap();
+        let Some(slots) = slots.get_mut(key) else {
+            return;
+        };
+        while let Some(waiter) = slots.waiting.pop_front() {
+            match waiter.send(Permit(Some((self.clone(), key.clone())))) {
+                Ok(()) => return,
+                // The waiting effect was cancelled: the permit must not
+                // release the slot it never took.
+                Err(mut permit) => drop(permit.0.take()),
+            }
+        }
+        slots.started -= 1;
+    }
+
+    /// Whether an effect throttled under `key` may run at `now`, which it may
+    /// if none ran in the `window` before. Records the run if so.
+    pub(crate) fn throttle(&self, key: EffectKey, now: Instant, window: Duration) -> bool {
+        let mut throttled = self.throttled.lock().unwrap();
+        match throttled.get(&key) {
+            Some(last) if now.saturating_duration_since(*last) < window => false,
+            _ => {
+                throttled.insert(key, now);
+                true
+            }
+        }
+    }
+
+    /// Aborts every running effect started with `key`.
+    pub(crate) fn cancel(&self, key: &EffectKey) {
+        let running = self.live.lock().unwrap().remove(key);
+        for (_, abort) in running.into_iter().flatten() {
+            abort.abort();
+        }
+    }
+
+    /// Aborts every running effect started with `key`, except `ticket`.
+    fn cancel_except(&self, key: &EffectKey, ticket: u64) {
+        let mut live = self.live.lock().unwrap();
+        if let Some(running) = live.get_mut(key) {
+            running.retain(|(t, abort)| {
+                if *t != ticket {
+                    abort.abort();
+                }
+                *t == ticket
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_compare_by_type_and_value() {
+        assert!(EffectKey::new("search") == EffectKey::new("search"));
+        assert!(EffectKey::new("search") != EffectKey::new("load"));
+        assert!(EffectKey::new(1u32) != EffectKey::new(1u64));
+    }
+
+    #[test]
+    fn throttle_admits_one_run_per_window() {
+        let keyed = KeyedEffects::default();
+        let start = Instant::now();
+        let window = Duration::from_secs(1);
+        let at = |ms| start + Duration::from_millis(ms);
+        let runs: Vec<_> = [0, 500, 1000, 1200, 2100]
+            .into_iter()
+            .map(|ms| keyed.throttle(EffectKey::new("scroll"), at(ms), window))
+            .collect();
+        assert_eq!(runs, vec![true, false, true, false, true]);
+    }
+}
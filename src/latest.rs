@@ -0,0 +1,160 @@
+use std::future::poll_fn;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Poll, Waker};
+
+use crate::Value;
+use crate::node::{ReadableNode, WatchSlot};
+use crate::subscription::Connection;
+
+struct SlotInner<T> {
+    value: T,
+    generation: u64,
+    wakers: Vec<Waker>,
+}
+
+struct Slot<T> {
+    inner: Mutex<SlotInner<T>>,
+    _connection: Connection,
+}
+
+/// A conflated mailbox holding only the most recent value of a node.
+///
+/// Every change overwrites the single stored value and bumps a generation
+/// counter, so a slow consumer never blocks the store nor accumulates a
+/// backlog. Consumers compare generations to find out how many updates they
+/// skipped.
+///
+/// The mailbox owns its own subscription: unbinding the store or readers does
+/// not disconnect it. It stays connected until the last clone is dropped.
+pub struct Latest<T: Value> {
+    slot: Arc<Slot<T>>,
+    seen: AtomicU64,
+}
+
+impl<T: Value> Latest<T> {
+    pub(crate) fn new(node: &dyn ReadableNode<T>) -> Self {
+        let (connection, alive) = Connection::new();
+        let slot = Arc::new(Slot {
+            inner: Mutex::new(SlotInner {
+                value: node.get(),
+                generation: 0,
+                wakers: Vec::new(),
+            }),
+            _connection: connection,
+        });
+        let weak_slot = Arc::downgrade(&slot);
+        node.add_watcher(WatchSlot {
+            alive,
+            callback: Arc::new(move |value: &T| {
+                let Some(slot) = weak_slot.upgrade() else {
+                    return;
+                };
+                let wakers = {
+                    let mut guard = slot.inner.lock().unwrap();
+                    guard.value = value.clone();
+                    guard.generation += 1;
+                    std::mem::take(&mut guard.wakers)
+                };
+                for waker in wakers {
+                    waker.wake();
+                }
+            }),
+        });
+        Latest {
+            slot,
+            seen: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the most recent value and marks it as observed.
+    pub fn get(&self) -> T {
+        let guard = self.slot.inner.lock().unwrap();
+        self.seen.store(guard.generation, Ordering::Relaxed);
+        guard.value.clone()
+    }
+
+    /// The number of updates the mailbox has received since it was created.
+    pub fn generation(&self) -> u64 {
+        self.slot.inner.lock().unwrap().generation
+    }
+
+    /// Resolves once a value newer than the last observed one is available.
+    ///
+    /// Returns how many updates happened since the last observation; anything
+    /// above one means intermediate values were skipped. The new value is
+    /// marked as observed, retrieve it with [`get`](Latest::get).
+    pub async fn changed(&self) -> u64 {
+        let seen = self.seen.load(Ordering::Relaxed);
+        let generation = poll_fn(|cx| {
+            let mut guard = self.slot.inner.lock().unwrap();
+            if guard.generation > seen {
+                return Poll::Ready(guard.generation);
+            }
+            if !guard.wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                guard.wakers.push(cx.waker().clone());
+            }
+            Poll::Pending
+        })
+        .await;
+        self.seen.store(generation, Ordering::Relaxed);
+        generation - seen
+    }
+}
+
+impl<T: Value> Clone for Latest<T> {
+    fn clone(&self) -> Self {
+        Latest {
+            slot: self.slot.clone(),
+            seen: AtomicU64::new(self.seen.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::SourceNode;
+    use futures::FutureExt;
+
+    #[test]
+    fn get_returns_initial_value() {
+        let source = SourceNode::new(7i32);
+        let latest = Latest::new(source.as_ref());
+        assert_eq!(latest.get(), 7);
+        assert_eq!(latest.generation(), 0);
+    }
+
+    #[test]
+    fn changed_is_pending_until_a_new_value_arrives() {
+        let source = SourceNode::new(0i32);
+        let latest = Latest::new(source.as_ref());
+        assert!(latest.changed().now_or_never().is_none());
+        source.set(1);
+        assert_eq!(latest.changed().now_or_never(), Some(1));
+        assert_eq!(latest.get(), 1);
+    }
+
+    #[test]
+    fn get_marks_value_observed() {
+        let source = SourceNode::new(0i32);
+        let latest = Latest::new(source.as_ref());
+        source.set(1);
+        assert_eq!(latest.get(), 1);
+        assert!(latest.changed().now_or_never().is_none());
+    }
+
+    #[test]
+    fn clones_observe_independently() {
+        let source = SourceNode::new(0i32);
+        let a = Latest::new(source.as_ref());
+        source.set(1);
+        let b = a.clone();
+        assert_eq!(a.changed().now_or_never(), Some(1));
+        source.set(2);
+        assert_eq!(a.changed().now_or_never(), Some(1));
+        assert_eq!(b.changed().now_or_never(), Some(2));
+    }
+}
@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+type Get<S, T> = Arc<dyn Fn(&S) -> &T + Send + Sync>;
+type GetMut<S, T> = Arc<dyn Fn(&mut S) -> &mut T + Send + Sync>;
+
+/// A typed path from a state `S` to a part of it `T`, which can both read and
+/// update that part.
+///
+/// Lenses compose with [`then`](Lens::then). Rather than writing them by hand,
+/// derive them with `#[derive(Lenses)]` (feature `derive`), which chains as
+/// `State::lenses().settings().theme()`. Read through a lens with
+/// [`Store::reader_at`](crate::Store::reader_at):
+///
+/// ```
+/// use uniflow::{Lens, Read, Store};
+///
+/// uniflow::manual_spawner::init().expect("init");
+///
+/// #[derive(Clone, PartialEq)]
+/// struct State {
+///     theme: &'static str,
+/// }
+///
+/// let theme = Lens::new(|s: &State| &s.theme, |s: &mut State| &mut s.theme);
+/// let store = Store::new(State { theme: "light" }, |s: State, a: &'static str| State { theme: a });
+/// assert_eq!(store.reader_at(theme).get(), "light");
+/// ```
+pub struct Lens<S, T> {
+    get: Get<S, T>,
+    get_mut: GetMut<S, T>,
+}
+
+impl<S: 'static, T: 'static> Lens<S, T> {
+    pub fn new<G, M>(get: G, get_mut: M) -> Self
+    where
+        G: Fn(&S) -> &T + Send + Sync + 'static,
+        M: Fn(&mut S) -> &mut T + Send + Sync + 'static,
+    {
+        Lens {
+            get: Arc::new(get),
+            get_mut: Arc::new(get_mut),
+        }
+    }
+
+    pub fn get<'s>(&self, state: &'s S) -> &'s T {
+        (self.get)(state)
+    }
+
+    pub fn get_mut<'s>(&self, state: &'s mut S) -> &'s mut T {
+        (self.get_mut)(state)
+    }
+
+    /// Replaces the focused part of `state` with `value`.
+    pub fn set(&self, state: &mut S, value: T) {
+        *self.get_mut(state) = value;
+    }
+
+    /// Returns a lens onto the part of this lens's target focused by `next`.
+    pub fn then<U: 'static>(&self, next: impl Into<Lens<T, U>>) -> Lens<S, U> {
+        let next = next.into();
+        let (get, inner_get) = (self.get.clone(), next.get);
+        let (get_mut, inner_get_mut) = (self.get_mut.clone(), next.get_mut);
+        Lens {
+            get: Arc::new(move |s| inner_get(get(s))),
+            get_mut: Arc::new(move |s| inner_get_mut(get_mut(s))),
+        }
+    }
+}
+
+impl<S: 'static> Lens<S, S> {
+    /// The lens onto the whole state.
+    pub fn identity() -> Self {
+        Lens::new(|s| s, |s| s)
+    }
+}
+
+impl<S, T> Clone for Lens<S, T> {
+    fn clone(&self) -> Self {
+        Lens {
+            get: self.get.clone(),
+            get_mut: self.get_mut.clone(),
+        }
+    }
+}
+
+/// Implemented by `#[derive(Lenses)]`: names the generated type holding the
+/// lenses onto each field, so that lenses into nested structs can be chained.
+pub trait Lenses: Sized + 'static {
+    type Fields<Root: 'static>: From<Lens<Root, Self>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Settings {
+        theme: &'static str,
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct App {
+        settings: Settings,
+        count: u32,
+    }
+
+    #[test]
+    fn composed_lens_reads_and_writes_the_nested_field() {
+        let settings = Lens::new(|s: &App| &s.settings, |s: &mut App| &mut s.settings);
+        let theme = settings.then(Lens::new(
+            |s: &Settings| &s.theme,
+            |s: &mut Settings| &mut s.theme,
+        ));
+        let mut app = App {
+            settings: Settings { theme: "light" },
+            count: 0,
+        };
+        assert_eq!(*theme.get(&app), "light");
+        theme.set(&mut app, "dark");
+        assert_eq!(app.settings.theme, "dark");
+        assert_eq!(app.count, 0);
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn derived_lenses_chain_through_nested_structs() {
+        use crate::Lenses;
+
+        #[derive(Clone, PartialEq, Lenses)]
+        struct Inner {
+            theme: String,
+        }
+
+        #[derive(Clone, PartialEq, Lenses)]
+        struct Outer {
+            #[lens(nested)]
+            inner: Inner,
+            count: u32,
+        }
+
+        let mut outer = Outer {
+            inner: Inner {
+                theme: "light".into(),
+            },
+            count: 1,
+        };
+        let theme = outer_theme();
+        theme.set(&mut outer, "dark".into());
+        assert_eq!(outer.inner.theme, "dark");
+        *Outer::lenses().count().get_mut(&mut outer) += 1;
+        assert_eq!(outer.count, 2);
+
+        fn outer_theme() -> Lens<Outer, String> {
+            Outer::lenses().inner().theme()
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Interop with [Leptos](https://leptos.dev) through `reactive_graph` signals
+//! (feature `leptos`).
+
+use reactive_graph::owner::{Owner, on_cleanup};
+use reactive_graph::signal::{ReadSignal, RwSignal};
+use reactive_graph::traits::Set;
+
+use crate::{Action, Deps, Read, Reader, Store, Value};
+
+impl<T: Value> Reader<T> {
+    /// Returns a signal holding the reader's value, updated whenever the
+    /// reader changes, for use in Leptos views.
+    ///
+    /// The signal follows the reader until the current reactive owner, such as
+    /// the component creating it, is cleaned up. Created outside any owner, it
+    /// follows the reader for good.
+    pub fn into_signal(self) -> ReadSignal<T> {
+        let signal = RwSignal::new(self.get());
+        self.watch(move |value| signal.set(value.clone()));
+        if Owner::current().is_some() {
+            on_cleanup(move || drop(self));
+        } else {
+            std::mem::forget(self);
+        }
+        signal.read_only()
+    }
+}
+
+impl<S: Value, A: Action, D: Deps> Store<S, A, D> {
+    /// Returns a signal holding the store's state. See [`Reader::into_signal`].
+    pub fn signal(&self) -> ReadSignal<S> {
+        self.reader().into_signal()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Dispatch;
+    use crate::executor::{init as init_executor, tick};
+    use reactive_graph::traits::{GetUntracked, WithUntracked};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn signal_follows_the_store_until_its_owner_is_cleaned_up() {
+        init_executor();
+        let store = Store::new(0, |s: i32, n: i32| s + n);
+        let owner = Owner::new();
+        let evaluations = Arc::new(AtomicUsize::new(0));
+        let e = evaluations.clone();
+        let signal = owner.with(|| {
+            let reader = store.reader().map(move |s| {
+                e.fetch_add(1, Ordering::Relaxed);
+                s * 10
+            });
+            reader.into_signal()
+        });
+        store.dispatch(1);
+        tick();
+        assert_eq!(signal.get_untracked(), 10);
+
+        owner.cleanup();
+        let before = evaluations.load(Ordering::Relaxed);
+        store.dispatch(1);
+        tick();
+        assert_eq!(store.get(), 2);
+        assert_eq!(evaluations.load(Ordering::Relaxed), before);
+        assert_eq!(signal.try_with_untracked(|v| *v), None);
+    }
+}
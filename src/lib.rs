@@ -1,24 +1,139 @@
 #![doc = include_str!("../README.md")]
 
-use futures::channel::mpsc::TrySendError;
-use futures::future::BoxFuture;
+use futures::future::{AbortHandle, Abortable, BoxFuture};
+use futures::{FutureExt, StreamExt};
+use std::any::{Any, type_name};
+use std::future::Future;
+use std::hash::Hash;
+use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
-
+use std::time::Duration;
+
+use keyed_effects::{EffectKey, KeyedEffects};
+use node::{RegionNode, SourceNode};
+
+mod activity;
+mod arc_state;
+mod blocking;
+mod changes;
+mod channel;
+mod compose;
+mod crdt;
+mod dispatcher;
+mod event_log;
+mod history;
+#[cfg(any(feature = "uniffi", feature = "ffi"))]
+mod json_store;
+mod keyed;
+mod keyed_effects;
+mod latest;
+mod lens;
+#[cfg(feature = "leptos")]
+mod leptos;
+mod local;
+mod logger;
+mod metrics;
+mod middleware;
 mod node;
+mod panic;
+mod prism;
+mod projection;
 mod reader;
+mod recorder;
+mod region;
+mod retry;
+mod schedule;
+mod scope;
+mod selector_cache;
+mod slices;
+#[cfg(feature = "serde")]
+mod snapshot;
 mod state;
 mod store;
 mod subscription;
-
+mod supervisor;
+mod tap;
+mod thunk;
+mod trace;
+#[cfg(any(feature = "devtools", feature = "sync"))]
+mod transport;
+mod undo;
+
+#[cfg(feature = "bevy")]
+pub mod bevy;
+pub mod deps;
+#[cfg(feature = "devtools")]
+pub mod devtools;
+#[cfg(feature = "dioxus")]
+pub mod dioxus;
+#[cfg(feature = "egui")]
+pub mod egui;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod manual_spawner;
+#[cfg(feature = "persist")]
+pub mod persist;
+pub mod query;
+#[cfg(feature = "remote")]
+pub mod remote;
+#[cfg(feature = "sync")]
+pub mod sync;
+#[cfg(feature = "tauri")]
+pub mod tauri;
+pub mod test;
+pub mod time;
+#[cfg(feature = "uniffi")]
+pub mod uniffi;
+#[cfg(feature = "yew")]
+pub mod yew;
 
 #[cfg(test)]
 mod executor;
 
+// Lets the code generated by the derive macros resolve `::uniflow` in tests.
+#[cfg(all(test, feature = "derive"))]
+extern crate self as uniflow;
+
+pub use activity::Shutdown;
 pub use any_spawner;
+pub use arc_state::ArcState;
+pub use changes::Changes;
+pub use crdt::{Crdt, GCounter, LwwRegister, OrSet, PnCounter, ReplicaId};
+pub use deps::DepsMap;
+pub use dispatcher::{DispatchError, Dispatcher, OverflowPolicy};
+pub use event_log::{EventLog, EventSourcing, MemoryEventLog};
+pub use history::History;
+pub use keyed::{CollectionDiff, KeyedReaders};
+pub use keyed_effects::Concurrency;
+pub use latest::Latest;
+pub use lens::{Lens, Lenses};
+pub use local::{LocalContext, LocalDispatcher, LocalEffect, LocalStore};
+pub use logger::{LogLevel, LoggerMiddleware};
+pub use metrics::{LatencyHistogram, StoreMetrics};
+pub use middleware::Middleware;
+pub use panic::{Panic, PanicOrigin};
+pub use prism::Prism;
+pub use projection::Projection;
 pub use reader::{Merge, Reader, with};
+pub use recorder::{ActionLog, ActionRecorder, RecordedAction, replay};
+pub use region::{Changed, Region};
+pub use retry::RetryPolicy;
+pub use schedule::ScheduleHandle;
+pub use scope::ScopedStore;
+#[cfg(feature = "serde")]
+pub use snapshot::SerializedState;
 pub use state::State;
-pub use store::{Store, StoreBuilder};
+pub use store::{Fallible, InPlace, Store, StoreBuilder};
+pub use subscription::{Subscription, WatchGuard};
+pub use supervisor::Supervisor;
+pub use thunk::AsyncAction;
+pub use time::{Clock, SystemClock};
+pub use undo::{UndoOptions, Undoable, UndoableAction, undoable, undoable_with};
+#[cfg(feature = "derive")]
+pub use uniflow_derive::Lenses;
+
+#[cfg(feature = "uniffi")]
+::uniffi::setup_scaffolding!();
 
 pub mod prelude {
     pub use crate::{Dispatch, Read, ReadWrite, Write};
@@ -47,12 +162,80 @@ impl<S: Value, A: Action, D: Deps, R: Fn(S, A) -> (S, Effect<A, D>) + Send + 'st
 {
 }
 
+/// A reducer which can read the store's deps, such as configuration or
+/// feature flags, while reducing. See [`Store::new_with_deps_reducer`].
+pub trait DepsReducer<S: Value, A: Action, D: Deps>:
+    Fn(S, A, &D) -> (S, Effect<A, D>) + Send + 'static
+{
+}
+impl<S: Value, A: Action, D: Deps, R: Fn(S, A, &D) -> (S, Effect<A, D>) + Send + 'static>
+    DepsReducer<S, A, D> for R
+{
+}
+
 // ── Read trait ────────────────────────────────────────────────────────────────
 
 pub trait Read<T: Value>: Send + Sync {
     fn get(&self) -> T;
-    fn watch<F: Fn(&T) + Send + Sync + 'static>(&self, f: F) -> &Self;
-    fn bind<F: Fn(&T) + Send + Sync + 'static>(&self, f: F) -> &Self;
+    /// Calls `f` whenever the value changes, until the returned
+    /// [`Subscription`] is unsubscribed or the reader is unbound.
+    fn watch<F: Fn(&T) + Send + Sync + 'static>(&self, f: F) -> Subscription;
+    /// Like [`watch`](Read::watch), but also calls `f` with the current value
+    /// straight away, so a UI binding can render its first frame without a
+    /// separate [`get`](Read::get).
+    #[doc(alias = "watch_immediate")]
+    fn bind<F: Fn(&T) + Send + Sync + 'static>(&self, f: F) -> Subscription;
+    /// Like [`watch`](Read::watch), but the callback is disconnected when the
+    /// returned guard is dropped.
+    fn watch_guarded<F: Fn(&T) + Send + Sync + 'static>(&self, f: F) -> WatchGuard {
+        WatchGuard::new(self.watch(f))
+    }
+
+    /// Like [`watch`](Read::watch), but skips values which `eq` considers
+    /// equal to the last value `f` was called with.
+    fn watch_with<E, F>(&self, eq: E, f: F) -> Subscription
+    where
+        E: Fn(&T, &T) -> bool + Send + Sync + 'static,
+        F: Fn(&T) + Send + Sync + 'static,
+    {
+        let last = std::sync::Mutex::new(self.get());
+        self.watch(move |value| {
+            {
+                let mut last = last.lock().unwrap();
+                if eq(&last, value) {
+                    return;
+                }
+                *last = value.clone();
+            }
+            f(value);
+        })
+    }
+
+    /// Calls `f` on the next change only, then disconnects it.
+    fn watch_once<F: FnOnce(&T) + Send + 'static>(&self, f: F) -> Subscription {
+        let pending = Arc::new(std::sync::Mutex::new((Some(f), None::<Subscription>)));
+        let slot = pending.clone();
+        let subscription = self.watch(move |value| {
+            let (f, subscription) = {
+                let mut slot = slot.lock().unwrap();
+                (slot.0.take(), slot.1.take())
+            };
+            if let Some(f) = f {
+                f(value);
+            }
+            if let Some(subscription) = subscription {
+                subscription.unsubscribe();
+            }
+        });
+        // The change may already have been notified on another thread.
+        let mut pending = pending.lock().unwrap();
+        if pending.0.is_some() {
+            pending.1 = Some(subscription.clone());
+        } else {
+            subscription.clone().unsubscribe();
+        }
+        subscription
+    }
     fn unbind(&self);
 }
 
@@ -81,8 +264,12 @@ pub trait Dispatch<A: Action> {
 // ── Context ───────────────────────────────────────────────────────────────────
 
 pub struct Context<A: Action, D: Deps = ()> {
-    pub(crate) dispatcher: Arc<dyn Fn(A) + Send + Sync>,
+    pub(crate) dispatcher: Dispatcher<A>,
     pub(crate) deps: D,
+    pub(crate) clock: Arc<dyn Clock>,
+    pub(crate) keyed_effects: Arc<KeyedEffects>,
+    /// The store's `SourceNode<S>`: contexts are not generic over the state.
+    pub(crate) state: Arc<dyn Any + Send + Sync>,
 }
 
 impl<A: Action, D: Deps> Clone for Context<A, D> {
@@ -90,19 +277,137 @@ impl<A: Action, D: Deps> Clone for Context<A, D> {
         Self {
             dispatcher: self.dispatcher.clone(),
             deps: self.deps.clone(),
+            clock: self.clock.clone(),
+            keyed_effects: self.keyed_effects.clone(),
+            state: self.state.clone(),
         }
     }
 }
 
 impl<A: Action, D: Deps> Context<A, D> {
     pub fn dispatch(&self, action: A) {
-        (self.dispatcher)(action);
+        self.dispatcher.dispatch(action);
+    }
+
+    /// Returns a cloneable handle which dispatches through this context.
+    pub fn dispatcher(&self) -> Dispatcher<A> {
+        self.dispatcher.clone()
+    }
+
+    /// Dispatches `action` once `delay` has elapsed on the store's clock.
+    /// See [`Dispatcher::dispatch_after`].
+    pub fn dispatch_after(&self, delay: Duration, action: A) -> ScheduleHandle {
+        self.dispatcher.dispatch_after(delay, action)
+    }
+
+    /// Dispatches `action` on the store's high-priority lane.
+    pub fn dispatch_priority(&self, action: A) {
+        self.dispatcher.dispatch_priority(action);
+    }
+
+    /// Dispatches `action` if the store's queue has room, or hands it back.
+    pub fn try_dispatch(&self, action: A) -> Result<(), DispatchError<A>> {
+        self.dispatcher.try_dispatch(action)
+    }
+
+    /// Dispatches `action`, waiting for room in the store's queue if it is
+    /// full.
+    pub async fn dispatch_async(&self, action: A) {
+        self.dispatcher.dispatch_async(action).await;
+    }
+
+    /// Dispatches every action of `actions` in order, waiting for room in the
+    /// store's queue rather than dropping any. See
+    /// [`Dispatcher::dispatch_all`].
+    pub async fn dispatch_all(&self, actions: impl IntoIterator<Item = A>) {
+        self.dispatcher.dispatch_all(actions).await;
+    }
+
+    /// Dispatches every action `actions` yields, with backpressure, until the
+    /// stream ends or the store shuts down.
+    ///
+    /// The building block for long-lived subscriptions: run it in an effect,
+    /// made [`cancellable`](Effect::cancellable) to stop it early.
+    pub async fn forward<St: futures::Stream<Item = A>>(&self, actions: St) {
+        // Fails only once the store has shut down.
+        let _ = actions.map(Ok).forward(self.dispatcher()).await;
+    }
+
+    /// Runs the CPU-bound or blocking `f`, such as image decoding or file IO,
+    /// off the executor, and resolves with its result.
+    ///
+    /// Within a tokio runtime and with feature `tokio`, `f` runs on tokio's
+    /// blocking pool; otherwise on a thread of its own, except on wasm32, where
+    /// it runs straight away. A panic in `f` is resumed in the effect, and if
+    /// the runtime shuts down before `f` runs, the effect never resumes.
+    pub async fn spawn_blocking<T, F>(&self, f: F) -> T
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        blocking::spawn_blocking(f).await
     }
 
     pub fn deps(&self) -> &D {
         &self.deps
     }
 
+    /// The clock of the store this context dispatches into. Effects should
+    /// create their timers through it so that tests can control time.
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /// The store's current state, which includes every action reduced since
+    /// the effect was started.
+    ///
+    /// Panics if `S` is not the store's state type.
+    pub fn state<S: Value>(&self) -> S {
+        self.with_state(S::clone)
+    }
+
+    /// Calls `f` with the store's current state, without cloning it.
+    ///
+    /// Panics if `S` is not the store's state type.
+    pub fn with_state<S: Value, R>(&self, f: impl FnOnce(&S) -> R) -> R {
+        self.source::<S>().with(f)
+    }
+
+    /// Resolves with the first state of the store, starting from the current
+    /// one, which satisfies `predicate`. See [`Store::wait_for`].
+    ///
+    /// Lets a multi-step effect wait for reductions running concurrently,
+    /// such as another effect's results arriving: `ctx.wait_for(|s:
+    /// &State| s.loaded).await`. Panics if `S` is not the store's state type.
+    pub fn wait_for<S, F>(&self, predicate: F) -> impl Future<Output = S> + Send + 'static
+    where
+        S: Value,
+        F: Fn(&S) -> bool + Send + Sync + 'static,
+    {
+        store::wait_for(&self.source(), predicate)
+    }
+
+    /// Returns a stream of the values `f` selects from the store's state,
+    /// with one item per change of the selected value. The current value is
+    /// not yielded.
+    ///
+    /// Panics if `S` is not the store's state type.
+    pub fn select<S, T, F>(&self, f: F) -> Changes<T>
+    where
+        S: Value,
+        T: Value,
+        F: Fn(&S) -> T + Send + Sync + 'static,
+    {
+        Changes::keep(RegionNode::new(self.source(), Changed::ALL, f))
+    }
+
+    fn source<S: Value>(&self) -> Arc<SourceNode<S>> {
+        self.state
+            .clone()
+            .downcast()
+            .unwrap_or_else(|_| panic!("the store's state is not a {}", type_name::<S>()))
+    }
+
     /// Returns a new `Context<B, D>` that maps actions `B -> A` before dispatching
     /// to this context. Useful for passing a narrowed context to subsystems that
     /// only know about a subset of the store's action type.
@@ -111,10 +416,12 @@ impl<A: Action, D: Deps> Context<A, D> {
         B: Action,
         F: Fn(B) -> A + Send + Sync + 'static,
     {
-        let parent = self.dispatcher.clone();
         Context {
-            dispatcher: Arc::new(move |b| parent(f(b))),
+            dispatcher: self.dispatcher.map(f),
             deps: self.deps.clone(),
+            clock: self.clock.clone(),
+            keyed_effects: self.keyed_effects.clone(),
+            state: self.state.clone(),
         }
     }
 }
@@ -127,9 +434,10 @@ impl<A: Action, D: Deps> Dispatch<A> for Context<A, D> {
 
 // ── Effect ────────────────────────────────────────────────────────────────────
 
+type EffectFn<A, D> = Box<dyn FnOnce(Context<A, D>) -> BoxFuture<'static, ()> + Send>;
+
 pub struct Effect<A: Action, D: Deps = ()> {
-    #[allow(clippy::type_complexity)]
-    inner: Option<Box<dyn FnOnce(Context<A, D>) -> BoxFuture<'static, ()> + Send>>,
+    inner: Option<EffectFn<A, D>>,
 }
 
 impl<A: Action, D: Deps> Effect<A, D> {
@@ -147,36 +455,343 @@ impl<A: Action, D: Deps> Effect<A, D> {
         Self { inner: None }
     }
 
-    pub(crate) fn run(self, ctx: Context<A, D>) {
+    /// An effect which runs `future` and dispatches nothing, such as
+    /// telemetry or cleanup work.
+    pub fn fire_and_forget<Fut>(future: Fut) -> Self
+    where
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        Self::new(move |_| future)
+    }
+
+    /// Like [`new`](Effect::new), for effects whose future is not `Send`,
+    /// such as those holding thread-bound GUI or web handles.
+    ///
+    /// The future is spawned with
+    /// [`Executor::spawn_local`](any_spawner::Executor::spawn_local), so it
+    /// stays on the thread which runs the store's effects; the executor must
+    /// support local tasks, as wasm-bindgen does, or tokio within a
+    /// `LocalSet`. The effect can be cancelled and awaited like any other.
+    pub fn new_local<F, Fut>(f: F) -> Self
+    where
+        F: FnOnce(Context<A, D>) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = ()> + 'static,
+    {
+        Self::new(move |ctx| {
+            // Dropping the handle, as cancelling the effect does, drops the
+            // local future too; its panics are resumed through the handle.
+            let (local, handle) = async move { f(ctx).await }.remote_handle();
+            any_spawner::Executor::spawn_local(local);
+            handle
+        })
+    }
+
+    /// An effect which dispatches every action yielded by the stream `f`
+    /// returns, until the stream ends or the store shuts down.
+    ///
+    /// Suits subscriptions such as websocket feeds or file watchers. Actions
+    /// are dispatched with backpressure, as by
+    /// [`dispatch_async`](Context::dispatch_async).
+    pub fn from_stream<F, St>(f: F) -> Self
+    where
+        F: FnOnce(Context<A, D>) -> St + Send + 'static,
+        St: futures::Stream<Item = A> + Send + 'static,
+    {
+        Self::new(move |ctx| async move {
+            let actions = f(ctx.clone());
+            ctx.forward(actions).await;
+        })
+    }
+
+    /// An effect which dispatches `action` as soon as it runs.
+    pub fn send(action: A) -> Self {
+        Self::new(move |ctx| async move { ctx.dispatch(action) })
+    }
+
+    /// An effect which dispatches each of `actions`, in order, as soon as it
+    /// runs.
+    pub fn send_all(actions: impl IntoIterator<Item = A>) -> Self {
+        let actions: Vec<A> = actions.into_iter().collect();
+        if actions.is_empty() {
+            return Self::none();
+        }
+        Self::new(move |ctx| async move { actions.into_iter().for_each(|a| ctx.dispatch(a)) })
+    }
+
+    /// An effect which runs all of `effects` concurrently. Also available
+    /// through [`FromIterator`], as `effects.into_iter().collect()`.
+    pub fn merge(effects: impl IntoIterator<Item = Effect<A, D>>) -> Self {
+        let mut effects: Vec<_> = effects.into_iter().filter_map(|e| e.inner).collect();
+        match effects.len() {
+            0 => Self::none(),
+            1 => Self {
+                inner: effects.pop(),
+            },
+            _ => Self::new(move |ctx| {
+                futures::future::join_all(effects.into_iter().map(|f| f(ctx.clone()))).map(|_| ())
+            }),
+        }
+    }
+
+    /// Lifts the effect into another action type: actions it dispatches are
+    /// passed through `f` first. Use it to compose the effects of a feature's
+    /// reducer into the app's reducer.
+    pub fn map<B, F>(self, f: F) -> Effect<B, D>
+    where
+        B: Action,
+        F: Fn(A) -> B + Send + Sync + 'static,
+    {
+        Effect {
+            inner: self.inner.map(|inner| {
+                Box::new(move |ctx: Context<B, D>| inner(ctx.map(f))) as EffectFn<B, D>
+            }),
+        }
+    }
+
+    /// Aborts the effect if it has not completed after `duration` on the
+    /// store's clock, then dispatches `on_timeout`, if any.
+    pub fn timeout(self, duration: Duration, on_timeout: impl Into<Option<A>>) -> Self {
+        let on_timeout = on_timeout.into();
+        Effect {
+            inner: self.inner.map(|inner| {
+                Box::new(move |ctx: Context<A, D>| {
+                    let deadline = ctx.clock().sleep(duration);
+                    let dispatcher = ctx.dispatcher();
+                    let effect = inner(ctx);
+                    Box::pin(async move {
+                        let timed_out = matches!(
+                            futures::future::select(effect, deadline).await,
+                            futures::future::Either::Right(_)
+                        );
+                        if let Some(action) = on_timeout.filter(|_| timed_out) {
+                            dispatcher.dispatch(action);
+                        }
+                    }) as BoxFuture<'static, ()>
+                }) as EffectFn<A, D>
+            }),
+        }
+    }
+
+    /// An effect which runs `work` and dispatches the action it succeeds
+    /// with, retrying failures as `policy` allows. Once attempts are
+    /// exhausted, dispatches `on_failure` of the last error.
+    ///
+    /// Delays between attempts are measured on the store's clock.
+    pub fn retrying<W, Fut, E, F>(policy: RetryPolicy, mut work: W, on_failure: F) -> Self
+    where
+        W: FnMut(Context<A, D>) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<A, E>> + Send,
+        E: Send,
+        F: FnOnce(E) -> A + Send + 'static,
+    {
+        Self::new(move |ctx| async move {
+            let mut attempt = 1;
+            loop {
+                let error = match work(ctx.clone()).await {
+                    Ok(action) => return ctx.dispatch(action),
+                    Err(error) => error,
+                };
+                match policy.delay(attempt) {
+                    Some(delay) => ctx.clock().sleep(delay).await,
+                    None => return ctx.dispatch(on_failure(error)),
+                }
+                attempt += 1;
+            }
+        })
+    }
+
+    /// An effect which calls `work` with the store's deps, dispatching
+    /// `embed(Started)` first, then `embed` of how the call ended. Spares
+    /// every network call its hand-written pending, success and failure
+    /// actions; see [`AsyncAction`].
+    ///
+    /// ```
+    /// use uniflow::{AsyncAction, Effect};
+    ///
+    /// #[derive(Clone)]
+    /// struct Api;
+    ///
+    /// impl Api {
+    ///     async fn user(&self, id: u32) -> Result<String, String> {
+    ///         Ok(format!("user {id}"))
+    ///     }
+    /// }
+    ///
+    /// enum Action {
+    ///     Load(u32),
+    ///     User(AsyncAction<String, String>),
+    /// }
+    ///
+    /// fn reduce(s: Option<String>, action: Action) -> (Option<String>, Effect<Action, Api>) {
+    ///     match action {
+    ///         Action::Load(id) => (s, Effect::thunk(Action::User, move |api: Api| async move {
+    ///             api.user(id).await
+    ///         })),
+    ///         Action::User(AsyncAction::Succeeded(user)) => (Some(user), Effect::none()),
+    ///         Action::User(_) => (s, Effect::none()),
+    ///     }
+    /// }
+    /// ```
+    pub fn thunk<T, E, F, W, Fut>(embed: F, work: W) -> Self
+    where
+        F: Fn(AsyncAction<T, E>) -> A + Send + 'static,
+        W: FnOnce(D) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<T, E>> + Send + 'static,
+    {
+        Self::new(move |ctx| async move {
+            ctx.dispatch(embed(AsyncAction::Started));
+            let result = work(ctx.deps().clone()).await;
+            ctx.dispatch(embed(result.into()));
+        })
+    }
+
+    /// Makes the effect cancellable with [`Effect::cancel`] under `id`, which
+    /// may be a value of any hashable type.
+    ///
+    /// To have a new request replace the one still in flight, cancel before
+    /// starting: `Effect::merge([Effect::cancel(id), effect.cancellable(id)])`,
+    /// or configure the store with [`Concurrency::Latest`] for `id`. The
+    /// effect runs under the [`Concurrency`] limit configured for `id` with
+    /// [`StoreBuilder::concurrency`], and while it waits for its turn it can
+    /// be cancelled too.
+    pub fn cancellable<K: Hash + Eq + Send + Sync + 'static>(self, id: K) -> Self {
+        let key = EffectKey::new(id);
+        Effect {
+            inner: self.inner.map(|inner| {
+                Box::new(move |ctx: Context<A, D>| {
+                    let keyed_effects = ctx.keyed_effects.clone();
+                    let (abort, registration) = AbortHandle::new_pair();
+                    let ticket = keyed_effects.register(key.clone(), abort);
+                    let admitted = {
+                        let (keyed_effects, key) = (keyed_effects.clone(), key.clone());
+                        async move {
+                            if let Some(_permit) = keyed_effects.admit(&key, ticket).await {
+                                inner(ctx).await;
+                            }
+                        }
+                    };
+                    let effect = Abortable::new(admitted, registration);
+                    Box::pin(async move {
+                        let _ = effect.await;
+                        keyed_effects.finished(&key, ticket);
+                    }) as BoxFuture<'static, ()>
+                }) as EffectFn<A, D>
+            }),
+        }
+    }
+
+    /// Delays the effect by `duration`, and cancels the effect started with
+    /// the same `key` before it, whether it is still waiting or running.
+    ///
+    /// Only the last of a burst of effects runs, once the burst has paused
+    /// for `duration`: the usual way to search as the user types. Debounced
+    /// effects share their keys with [`cancellable`](Effect::cancellable)
+    /// ones, so [`Effect::cancel`] also cancels a pending debounced effect.
+    pub fn debounced<K>(self, key: K, duration: Duration) -> Self
+    where
+        K: Hash + Eq + Clone + Send + Sync + 'static,
+    {
+        let inner = self.inner;
+        let delayed = Self::new(move |ctx| async move {
+            ctx.clock().sleep(duration).await;
+            if let Some(inner) = inner {
+                inner(ctx).await;
+            }
+        });
+        Self::merge([Self::cancel(key.clone()), delayed.cancellable(key)])
+    }
+
+    /// Runs the effect only if no effect throttled under the same `key` has
+    /// run in the last `duration` on the store's clock, and drops it
+    /// otherwise.
+    ///
+    /// Rate-limits expensive work triggered by high-frequency actions such as
+    /// scrolling: the first effect of a window runs straight away, and the
+    /// rest of the window's effects are dropped.
+    pub fn throttled<K>(self, key: K, duration: Duration) -> Self
+    where
+        K: Hash + Eq + Send + Sync + 'static,
+    {
+        let key = EffectKey::new(key);
+        Effect {
+            inner: self.inner.map(|inner| {
+                Box::new(move |ctx: Context<A, D>| {
+                    let now = ctx.clock().now();
+                    if ctx.keyed_effects.throttle(key, now, duration) {
+                        inner(ctx)
+                    } else {
+                        Box::pin(futures::future::ready(()))
+                    }
+                }) as EffectFn<A, D>
+            }),
+        }
+    }
+
+    /// An effect which cancels every running effect of the store made
+    /// [`cancellable`](Effect::cancellable) under `id`.
+    pub fn cancel<K: Hash + Eq + Send + Sync + 'static>(id: K) -> Self {
+        let key = EffectKey::new(id);
+        Self::new(move |ctx| {
+            ctx.keyed_effects.cancel(&key);
+            futures::future::ready(())
+        })
+    }
+
+    /// Spawns the effect. A panic inside it is caught and reported to
+    /// `on_panic` instead of unwinding through the executor.
+    pub(crate) fn run(
+        self,
+        ctx: Context<A, D>,
+        on_panic: Option<panic::PanicHook>,
+        mut guard: activity::EffectGuard,
+    ) {
         if let Some(f) = self.inner {
-            any_spawner::Executor::spawn(async move { f(ctx).await });
+            let effect = trace::effect(guard.store(), async move { f(ctx).await });
+            let effect = guard.abortable(effect);
+            any_spawner::Executor::spawn(async move {
+                let _guard = guard;
+                let result = AssertUnwindSafe(effect).catch_unwind().await;
+                if let Err(payload) = result {
+                    panic::report(on_panic.as_ref(), Panic::new(PanicOrigin::Effect, payload));
+                }
+            });
         }
     }
 }
 
-// ── Internal helpers ──────────────────────────────────────────────────────────
-
-pub(crate) fn handle_dispatch_result<A>(result: Result<(), TrySendError<A>>) {
-    match result {
-        Ok(()) => {}
-        Err(e) => {
-            debug_assert!(!e.is_full())
+impl<A: Action> Effect<A, ()> {
+    /// Lifts an effect which needs no deps into a store which has some.
+    pub(crate) fn with_deps<D: Deps>(self) -> Effect<A, D> {
+        Effect {
+            inner: self.inner.map(|inner| {
+                Box::new(move |ctx: Context<A, D>| {
+                    inner(Context {
+                        dispatcher: ctx.dispatcher,
+                        deps: (),
+                        clock: ctx.clock,
+                        keyed_effects: ctx.keyed_effects,
+                        state: ctx.state,
+                    })
+                }) as EffectFn<A, D>
+            }),
         }
     }
 }
 
+impl<A: Action, D: Deps> FromIterator<Effect<A, D>> for Effect<A, D> {
+    fn from_iter<I: IntoIterator<Item = Effect<A, D>>>(effects: I) -> Self {
+        Self::merge(effects)
+    }
+}
+
 // ── Tests ─────────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    static EXECUTOR: std::sync::OnceLock<()> = std::sync::OnceLock::new();
-
     fn init_executor() {
-        EXECUTOR.get_or_init(|| {
-            executor::init_test_executer().expect("Initialize global sync executor")
-        });
+        executor::init();
     }
 
     #[derive(Clone, Default, Debug, PartialEq)]
@@ -232,6 +847,24 @@ mod tests {
         assert!(store.get().items[0].done);
     }
 
+    #[test]
+    fn shared_store_dispatches_without_a_mutex() {
+        init_executor();
+        let store = Arc::new(Store::new(0, |s: i32, n: i32| s + n));
+        let handles: Vec<_> = (1..=4)
+            .map(|n| {
+                let store = store.clone();
+                std::thread::spawn(move || store.dispatch(n))
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        executor::tick();
+        assert_eq!(store.get(), 10);
+        store.shutdown();
+    }
+
     #[test]
     fn watch_store_calls_callback_on_state_change() {
         use std::sync::{Arc, RwLock};
@@ -379,6 +1012,302 @@ mod tests {
         assert_eq!(*call_count.read().unwrap(), 1); // still 1
     }
 
+    #[test]
+    fn unsubscribe_removes_only_that_watcher() {
+        use std::sync::Mutex;
+
+        init_executor();
+        let store = Store::new(0, |s: i32, n: i32| s + n);
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let (a, b) = (calls.clone(), calls.clone());
+        let first = store.watch(move |s| a.lock().unwrap().push(("first", *s)));
+        let second = store.watch(move |s| b.lock().unwrap().push(("second", *s)));
+
+        first.unsubscribe();
+        store.dispatch(1);
+        executor::tick();
+        assert_eq!(*calls.lock().unwrap(), vec![("second", 1)]);
+        assert!(second.is_active());
+
+        store.unbind();
+        assert!(!second.is_active());
+    }
+
+    #[test]
+    fn watch_once_fires_for_the_next_change_only() {
+        use std::sync::Mutex;
+
+        init_executor();
+        let store = Store::new(0, |s: i32, n: i32| s + n);
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let (a, b) = (calls.clone(), calls.clone());
+        let subscription = store.watch_once(move |s| a.lock().unwrap().push(*s));
+        let tens = store.derived(|s| s * 10);
+        tens.watch_once(move |s| b.lock().unwrap().push(*s));
+
+        store.dispatch(1);
+        store.dispatch(2);
+        executor::tick();
+        calls.lock().unwrap().sort();
+        assert_eq!(*calls.lock().unwrap(), vec![1, 10]);
+        assert!(!subscription.is_active());
+    }
+
+    #[test]
+    fn custom_equality_suppresses_notifications() {
+        use std::sync::Mutex;
+
+        init_executor();
+        let store = Store::new(0.0, |s: f64, n: f64| s + n);
+        let close = |a: &f64, b: &f64| (a - b).abs() < 0.5;
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let (a, b) = (calls.clone(), calls.clone());
+        store.watch_with(close, move |s| a.lock().unwrap().push(("watch", *s)));
+        let reader = store.reader_with_eq(|s| *s, close);
+        reader.watch(move |s| b.lock().unwrap().push(("reader", *s)));
+
+        for n in [0.25, 0.125, 0.375] {
+            store.dispatch(n);
+            executor::tick();
+        }
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![("watch", 0.75), ("reader", 0.75)]
+        );
+        assert_eq!(reader.get(), 0.75);
+    }
+
+    #[test]
+    fn derived_selectors_may_read_the_store() {
+        init_executor();
+        let store = Store::new(1, |s: i32, n: i32| s + n);
+        let reader = store.reader();
+        let sum = store.derived(move |s| s + reader.get());
+        assert_eq!(sum.get(), 2);
+
+        store.dispatch(2);
+        executor::tick();
+        assert_eq!(sum.get(), 6);
+    }
+
+    #[test]
+    fn reader_with_reuses_the_selection_for_equal_arguments() {
+        init_executor();
+        let store = Store::new(vec![1, 2, 3], |mut s: Vec<i32>, (i, n): (usize, i32)| {
+            s[i] = n;
+            s
+        });
+        let item = |i: usize| store.reader_with(i, |s: &Vec<i32>, i: &usize| s[*i]);
+        let first = item(0);
+        let again = item(0);
+        let second = item(1);
+        assert!(Arc::ptr_eq(&first.node, &again.node));
+        assert!(!Arc::ptr_eq(&first.node, &second.node));
+
+        store.dispatch((1, 20));
+        executor::tick();
+        assert_eq!((first.get(), second.get()), (1, 20));
+    }
+
+    #[test]
+    fn reader_with_keeps_the_selections_of_different_functions_apart() {
+        fn doubled(s: &[i32; 3], i: &usize) -> i32 {
+            s[*i] * 2
+        }
+        fn negated(s: &[i32; 3], i: &usize) -> i32 {
+            -s[*i]
+        }
+
+        init_executor();
+        let store = Store::new([1, 2, 3], |s: [i32; 3], ()| s);
+        let (doubled, negated) = (store.reader_with(1, doubled), store.reader_with(1, negated));
+        assert!(!Arc::ptr_eq(&doubled.node, &negated.node));
+        assert_eq!((doubled.get(), negated.get()), (4, -2));
+    }
+
+    #[test]
+    fn reader_each_only_notifies_the_changed_item() {
+        use std::sync::Mutex;
+
+        init_executor();
+        let todos = ToDo {
+            items: vec![
+                Item {
+                    what: "Washing up".into(),
+                    done: false,
+                },
+                Item {
+                    what: "Hoovering".into(),
+                    done: false,
+                },
+            ],
+        };
+        let store = Store::new(todos, reducer);
+        let items = store.reader_each(|s: &ToDo| &s.items, |item: &Item| item.what.clone());
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let readers: Vec<_> = items
+            .keys()
+            .get()
+            .into_iter()
+            .map(|what| {
+                let reader = items.get(what.clone());
+                let calls = calls.clone();
+                reader.watch(move |item| calls.lock().unwrap().push((what.clone(), item.clone())));
+                reader
+            })
+            .collect();
+
+        store.dispatch(Action::Done(1));
+        executor::tick();
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![(
+                "Hoovering".to_owned(),
+                Some(Item {
+                    what: "Hoovering".into(),
+                    done: true,
+                })
+            )]
+        );
+        assert_eq!(readers[0].get().map(|item| item.done), Some(false));
+
+        store.dispatch(Action::Add("Dusting".into()));
+        executor::tick();
+        assert_eq!(items.keys().get().len(), 3);
+        assert_eq!(calls.lock().unwrap().len(), 1);
+        assert_eq!(items.get("Nothing".into()).get(), None);
+    }
+
+    #[test]
+    fn watch_diff_reports_inserted_updated_and_removed_items() {
+        use std::sync::Mutex;
+
+        init_executor();
+        let store = Store::new(vec![(1, 'a'), (2, 'b')], |_: Vec<(u32, char)>, next| next);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let (inserted, updated, removed) = (events.clone(), events.clone(), events.clone());
+        store.watch_diff(
+            |s: &Vec<(u32, char)>| s,
+            |item: &(u32, char)| item.0,
+            CollectionDiff::new()
+                .on_insert(move |i, item| inserted.lock().unwrap().push(format!("+{i} {item:?}")))
+                .on_update(move |i, old, new| {
+                    updated
+                        .lock()
+                        .unwrap()
+                        .push(format!("~{i} {old:?} {new:?}"))
+                })
+                .on_remove(move |item| removed.lock().unwrap().push(format!("-{item:?}"))),
+        );
+
+        store.dispatch(vec![(3, 'c'), (2, 'B')]);
+        executor::tick();
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec!["-(1, 'a')", "+0 (3, 'c')", "~1 (2, 'b') (2, 'B')"]
+        );
+    }
+
+    #[test]
+    fn scope_reads_its_slice_and_wraps_its_actions() {
+        #[derive(Debug)]
+        enum AppAction {
+            ToDo(Action),
+            Rename(String),
+        }
+
+        #[derive(Clone, Debug, PartialEq)]
+        struct App {
+            name: String,
+            todo: ToDo,
+        }
+
+        init_executor();
+        let store = Store::new(
+            App {
+                name: "app".into(),
+                todo: ToDo::default(),
+            },
+            |mut app: App, action| {
+                match action {
+                    AppAction::ToDo(action) => app.todo = reducer(app.todo, action),
+                    AppAction::Rename(name) => app.name = name,
+                }
+                app
+            },
+        );
+        let todo = store.scope(
+            Lens::new(|app: &App| &app.todo, |app: &mut App| &mut app.todo),
+            prism!(AppAction::ToDo),
+        );
+        let notified = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = notified.clone();
+        todo.watch(move |_| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        });
+
+        todo.dispatch(Action::Add("Washing up".into()));
+        store.dispatch(AppAction::Rename("renamed".into()));
+        executor::tick();
+        assert_eq!(todo.get().items[0].what, "Washing up");
+        assert_eq!(notified.load(std::sync::atomic::Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn injected_slice_is_reduced_with_later_actions() {
+        init_executor();
+        let store = Store::new(ToDo::default(), reducer);
+        store.dispatch(Action::Add("Before".into()));
+        executor::tick();
+
+        let added = store.inject_slice("added", 0usize, |n, action: &Action| match action {
+            Action::Add(_) => n + 1,
+            Action::Done(_) => n,
+        });
+        let again = store.inject_slice("added", 10usize, |n, _: &Action| n);
+        store.dispatch(Action::Add("After".into()));
+        store.dispatch(Action::Done(0));
+        executor::tick();
+        assert_eq!(added.get(), 1);
+        assert_eq!(again.get(), 1);
+        assert_eq!(store.slice::<usize>("added").map(|r| r.get()), Some(1));
+        assert!(store.slice::<String>("added").is_none());
+    }
+
+    #[test]
+    fn panicking_slice_reducer_leaves_the_store_working() {
+        init_executor();
+        let panics = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let p = panics.clone();
+        let store = Store::builder(ToDo::default(), reducer)
+            .on_panic(move |panic| p.lock().unwrap().push(panic.to_string()))
+            .build();
+        let done = store.inject_slice("done", 0usize, |n, action: &Action| match action {
+            Action::Add(_) => n,
+            Action::Done(_) => panic!("slice failed"),
+        });
+        let added = store.inject_slice("added", 0usize, |n, action: &Action| match action {
+            Action::Add(_) => n + 1,
+            Action::Done(_) => n,
+        });
+
+        store.dispatch(Action::Add("Washing up".into()));
+        store.dispatch(Action::Done(0));
+        store.dispatch(Action::Add("Ironing".into()));
+        executor::tick();
+        assert_eq!(store.get().items.len(), 2);
+        assert!(store.get().items[0].done);
+        assert_eq!(added.get(), 2);
+        assert_eq!(done.get(), 0);
+        assert_eq!(*panics.lock().unwrap(), vec!["tap panicked: slice failed"]);
+
+        let count = store.inject_slice("count", 0usize, |n, _: &Action| n + 1);
+        store.dispatch(Action::Add("Cooking".into()));
+        executor::tick();
+        assert_eq!(store.get().items.len(), 3);
+        assert_eq!(count.get(), 1);
+    }
+
     #[test]
     fn unbind_reader_stops_watch_callbacks() {
         use std::sync::{Arc, RwLock};
@@ -538,13 +1467,399 @@ mod tests {
     }
 
     #[test]
-    fn effect_none_is_inert() {
-        init_executor();
+    fn reducer_reads_injected_deps() {
+        #[derive(Clone)]
+        struct Limits {
+            max: i32,
+        }
 
-        let store = Store::new_with_deps(
+        init_executor();
+        let store = Store::new_with_deps_reducer(
             0i32,
-            |state: i32, action: i32| -> (i32, Effect<i32>) { (state + action, Effect::none()) },
-            (),
+            |state: i32, action: i32, limits: &Limits| -> (i32, Effect<i32, Limits>) {
+                ((state + action).min(limits.max), Effect::none())
+            },
+            Limits { max: 10 },
+        );
+        store.dispatch(7);
+        store.dispatch(7);
+        executor::tick();
+        assert_eq!(store.get(), 10);
+    }
+
+    #[test]
+    fn effect_looks_up_deps_by_type() {
+        struct Multiplier(i32);
+
+        init_executor();
+        let store = Store::new_with_deps(
+            1i32,
+            |state: i32, action: i32| -> (i32, Effect<i32, DepsMap>) {
+                if action == 0 {
+                    let effect = Effect::new(|ctx: Context<i32, DepsMap>| async move {
+                        ctx.dispatch(ctx.deps().require::<Multiplier>().0);
+                    });
+                    (state, effect)
+                } else {
+                    (state * action, Effect::none())
+                }
+            },
+            DepsMap::new().with(Multiplier(3)),
+        );
+        store.dispatch(0);
+        executor::tick();
+        assert_eq!(store.get(), 3);
+    }
+
+    #[test]
+    fn send_and_send_all_dispatch_follow_up_actions() {
+        init_executor();
+        let store = Store::new_with_deps(
+            Vec::new(),
+            |mut log: Vec<i32>, action: i32| -> (Vec<i32>, Effect<i32>) {
+                log.push(action);
+                let effect = match action {
+                    0 => Effect::send(1),
+                    1 => Effect::send_all([2, 3]),
+                    _ => Effect::none(),
+                };
+                (log, effect)
+            },
+            (),
+        );
+        store.dispatch(0);
+        executor::tick();
+        assert_eq!(store.get(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn merged_effects_all_run() {
+        init_executor();
+        let store = Store::new_with_deps(
+            0,
+            |state: i32, action: i32| -> (i32, Effect<i32>) {
+                let effect = match action {
+                    0 => Effect::merge([Effect::send(1), Effect::none(), Effect::send(10)]),
+                    1 => [Effect::send(100), Effect::send(1000)]
+                        .into_iter()
+                        .collect(),
+                    _ => Effect::none(),
+                };
+                (state + action, effect)
+            },
+            (),
+        );
+        store.dispatch(0);
+        executor::tick();
+        assert_eq!(store.get(), 1111);
+    }
+
+    #[test]
+    fn mapped_effect_dispatches_parent_actions() {
+        #[derive(Debug)]
+        enum Parent {
+            Child(u8),
+            Done,
+        }
+
+        fn child(state: u8, action: u8) -> (u8, Effect<u8>) {
+            (state + action, Effect::send(action + 1))
+        }
+
+        init_executor();
+        let store = Store::new_with_deps(
+            (0u8, false),
+            |(count, done): (u8, bool), action: Parent| -> ((u8, bool), Effect<Parent>) {
+                match action {
+                    Parent::Child(0) => {
+                        let (count, effect) = child(count, 0);
+                        ((count, done), effect.map(Parent::Child))
+                    }
+                    Parent::Child(n) => ((count + n, done), Effect::send(Parent::Done)),
+                    Parent::Done => ((count, true), Effect::none()),
+                }
+            },
+            (),
+        );
+        store.dispatch(Parent::Child(0));
+        executor::tick();
+        assert_eq!(store.get(), (1, true));
+    }
+
+    #[test]
+    fn fire_and_forget_runs_the_future() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        init_executor();
+        let ran = Arc::new(AtomicBool::new(false));
+        let flag = ran.clone();
+        let store = Store::new_with_deps(
+            0,
+            move |state: i32, action: i32| -> (i32, Effect<i32>) {
+                let flag = flag.clone();
+                let effect = Effect::fire_and_forget(async move {
+                    flag.store(true, Ordering::Relaxed);
+                });
+                (state + action, effect)
+            },
+            (),
+        );
+        store.dispatch(1);
+        executor::tick();
+        assert!(ran.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn stream_effect_dispatches_every_item() {
+        init_executor();
+        let store = Store::new_with_deps(
+            Vec::new(),
+            |mut log: Vec<i32>, action: i32| -> (Vec<i32>, Effect<i32>) {
+                log.push(action);
+                let effect = if action == 0 {
+                    Effect::from_stream(|_| futures::stream::iter([1, 2, 3]))
+                } else {
+                    Effect::none()
+                };
+                (log, effect)
+            },
+            (),
+        );
+        store.dispatch(0);
+        executor::tick();
+        assert_eq!(store.get(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn cancel_aborts_the_running_effect_with_that_id() {
+        use futures::channel::oneshot;
+
+        #[derive(Debug)]
+        enum Search {
+            Start(oneshot::Receiver<i32>),
+            Cancel,
+            Found(i32),
+        }
+
+        init_executor();
+        let store = Store::new_with_deps(
+            0,
+            |state: i32, action: Search| -> (i32, Effect<Search>) {
+                match action {
+                    Search::Start(reply) => {
+                        let effect = Effect::new(|ctx: Context<Search>| async move {
+                            if let Ok(found) = reply.await {
+                                ctx.dispatch(Search::Found(found));
+                            }
+                        });
+                        (state, effect.cancellable("search"))
+                    }
+                    Search::Cancel => (state, Effect::cancel("search")),
+                    Search::Found(found) => (found, Effect::none()),
+                }
+            },
+            (),
+        );
+        let (first, reply) = oneshot::channel();
+        store.dispatch(Search::Start(reply));
+        let (second, reply) = oneshot::channel();
+        store.dispatch(Search::Start(reply));
+        executor::tick();
+
+        store.dispatch(Search::Cancel);
+        executor::tick();
+        for reply in [first, second] {
+            assert_eq!(reply.send(1), Err(1));
+        }
+        executor::tick();
+        assert_eq!(store.get(), 0);
+    }
+
+    #[test]
+    fn queued_effects_run_one_at_a_time_in_order() {
+        use futures::channel::oneshot;
+
+        enum Fetch {
+            Start(oneshot::Receiver<i32>),
+            Fetched(i32),
+        }
+
+        init_executor();
+        let store = Store::builder_with_deps(
+            Vec::new(),
+            |mut state: Vec<i32>, action: Fetch| -> (Vec<i32>, Effect<Fetch>) {
+                match action {
+                    Fetch::Start(reply) => {
+                        let effect = Effect::new(|ctx: Context<Fetch>| async move {
+                            if let Ok(fetched) = reply.await {
+                                ctx.dispatch(Fetch::Fetched(fetched));
+                            }
+                        });
+                        (state, effect.cancellable("fetch"))
+                    }
+                    Fetch::Fetched(fetched) => {
+                        state.push(fetched);
+                        (state, Effect::none())
+                    }
+                }
+            },
+            (),
+        )
+        .concurrency("fetch", Concurrency::Queue(1))
+        .build();
+        let (first, reply) = oneshot::channel();
+        store.dispatch(Fetch::Start(reply));
+        let (second, reply) = oneshot::channel();
+        store.dispatch(Fetch::Start(reply));
+        executor::tick();
+
+        second.send(2).unwrap();
+        executor::tick();
+        assert_eq!(store.get(), Vec::<i32>::new());
+        first.send(1).unwrap();
+        executor::tick();
+        assert_eq!(store.get(), vec![1, 2]);
+    }
+
+    #[test]
+    fn context_state_sees_actions_reduced_after_the_effect_started() {
+        use futures::channel::oneshot;
+        use std::sync::Mutex;
+
+        enum Counter {
+            Add(i32),
+            Report(oneshot::Receiver<()>),
+        }
+
+        init_executor();
+        let seen = Arc::new(Mutex::new(None));
+        let report = seen.clone();
+        let store = Store::new_with_deps(
+            0,
+            move |state: i32, action: Counter| -> (i32, Effect<Counter>) {
+                match action {
+                    Counter::Add(n) => (state + n, Effect::none()),
+                    Counter::Report(go) => {
+                        let report = report.clone();
+                        let effect = Effect::new(|ctx: Context<Counter>| async move {
+                            let _ = go.await;
+                            *report.lock().unwrap() = Some(ctx.state::<i32>());
+                        });
+                        (state, effect)
+                    }
+                }
+            },
+            (),
+        );
+        let (go, wait) = oneshot::channel();
+        store.dispatch(Counter::Report(wait));
+        store.dispatch(Counter::Add(2));
+        executor::tick();
+        go.send(()).unwrap();
+        executor::tick();
+        assert_eq!(*seen.lock().unwrap(), Some(2));
+    }
+
+    #[test]
+    fn context_wait_for_resumes_after_concurrent_reductions() {
+        #[derive(Clone, Debug, Default, PartialEq)]
+        struct Upload {
+            chunks: u32,
+            done: bool,
+        }
+
+        enum Step {
+            Start,
+            Chunk,
+            Finish,
+        }
+
+        init_executor();
+        let store = Store::new_with_deps(
+            Upload::default(),
+            |mut state: Upload, action: Step| -> (Upload, Effect<Step>) {
+                match action {
+                    Step::Start => {
+                        let effect = Effect::new(|ctx: Context<Step>| async move {
+                            ctx.wait_for(|s: &Upload| s.chunks == 2).await;
+                            ctx.dispatch(Step::Finish);
+                        });
+                        return (state, effect);
+                    }
+                    Step::Chunk => state.chunks += 1,
+                    Step::Finish => state.done = true,
+                }
+                (state, Effect::none())
+            },
+            (),
+        );
+        store.dispatch(Step::Start);
+        store.dispatch(Step::Chunk);
+        executor::tick();
+        assert!(!store.get().done);
+        store.dispatch(Step::Chunk);
+        executor::tick();
+        assert!(store.get().done);
+    }
+
+    #[test]
+    fn context_forward_resumes_once_the_stream_ends() {
+        init_executor();
+        let store = Store::new_with_deps(
+            Vec::new(),
+            |mut state: Vec<i32>, action: Option<i32>| -> (Vec<i32>, Effect<Option<i32>>) {
+                let Some(n) = action else {
+                    let effect = Effect::new(|ctx: Context<Option<i32>>| async move {
+                        ctx.forward(futures::stream::iter([Some(1), Some(2)])).await;
+                        ctx.dispatch(Some(0));
+                    });
+                    return (state, effect);
+                };
+                state.push(n);
+                (state, Effect::none())
+            },
+            (),
+        );
+        store.dispatch(None);
+        executor::tick();
+        assert_eq!(store.get(), vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn local_effects_may_hold_non_send_values() {
+        use std::rc::Rc;
+
+        init_executor();
+        let store = Store::new_with_deps(
+            0,
+            |state: i32, action: Option<i32>| -> (i32, Effect<Option<i32>>) {
+                match action {
+                    Some(n) => (n, Effect::none()),
+                    None => {
+                        let effect = Effect::new_local(|ctx: Context<Option<i32>>| async move {
+                            let shared = Rc::new(7);
+                            futures::future::ready(()).await;
+                            ctx.dispatch(Some(*shared));
+                        });
+                        (state, effect)
+                    }
+                }
+            },
+            (),
+        );
+        store.dispatch(None);
+        executor::tick();
+        assert_eq!(store.get(), 7);
+    }
+
+    #[test]
+    fn effect_none_is_inert() {
+        init_executor();
+
+        let store = Store::new_with_deps(
+            0i32,
+            |state: i32, action: i32| -> (i32, Effect<i32>) { (state + action, Effect::none()) },
+            (),
         );
 
         store.dispatch(5);
@@ -583,67 +1898,685 @@ mod tests {
             reducer,
         );
 
-        let count_a: Arc<RwLock<usize>> = Arc::new(RwLock::new(0));
-        let count_b: Arc<RwLock<usize>> = Arc::new(RwLock::new(0));
-
-        let reader_a = store.reader();
-        let reader_b = reader_a.clone();
-
-        let ca = count_a.clone();
-        let cb = count_b.clone();
-        reader_a.watch(move |_| *ca.write().unwrap() += 1);
-        reader_b.watch(move |_| *cb.write().unwrap() += 1);
+        let count_a: Arc<RwLock<usize>> = Arc::new(RwLock::new(0));
+        let count_b: Arc<RwLock<usize>> = Arc::new(RwLock::new(0));
+
+        let reader_a = store.reader();
+        let reader_b = reader_a.clone();
+
+        let ca = count_a.clone();
+        let cb = count_b.clone();
+        reader_a.watch(move |_| *ca.write().unwrap() += 1);
+        reader_b.watch(move |_| *cb.write().unwrap() += 1);
+
+        store.dispatch(Action::Done(0));
+        executor::tick();
+        assert_eq!(*count_a.read().unwrap(), 1);
+        assert_eq!(*count_b.read().unwrap(), 1);
+
+        drop(reader_a); // disconnect a
+
+        store.dispatch(Action::Add("New".into()));
+        executor::tick();
+        assert_eq!(*count_a.read().unwrap(), 1); // still 1
+        assert_eq!(*count_b.read().unwrap(), 2); // still active
+    }
+
+    #[test]
+    fn with_on_two_derived_readers() {
+        use std::sync::{Arc, RwLock};
+
+        init_executor();
+
+        let store = Store::new(
+            ToDo {
+                items: vec![
+                    Item {
+                        what: "A".into(),
+                        done: false,
+                    },
+                    Item {
+                        what: "B".into(),
+                        done: false,
+                    },
+                ],
+            },
+            reducer,
+        );
+
+        let r0 = store.derived(|s| s.items[0].done);
+        let r1 = store.derived(|s| s.items[1].done);
+        let combined = with((r0, r1));
+
+        let received: Arc<RwLock<Option<(bool, bool)>>> = Arc::new(RwLock::new(None));
+        let rc = received.clone();
+        combined.watch(move |v| *rc.write().unwrap() = Some(*v));
+
+        store.dispatch(Action::Done(0));
+        executor::tick();
+        assert_eq!(*received.read().unwrap(), Some((true, false)));
+
+        store.dispatch(Action::Done(1));
+        executor::tick();
+        assert_eq!(*received.read().unwrap(), Some((true, true)));
+    }
+
+    #[test]
+    fn priority_actions_overtake_queued_actions() {
+        init_executor();
+        let store = Store::new(Vec::new(), |mut log: Vec<&'static str>, a| {
+            log.push(a);
+            log
+        });
+        store.dispatch("bulk 1");
+        store.dispatch("bulk 2");
+        store.dispatch_priority("click");
+        store.dispatcher().dispatch_priority("key");
+        executor::tick();
+        assert_eq!(store.get(), vec!["click", "key", "bulk 1", "bulk 2"]);
+    }
+
+    #[test]
+    fn dispatch_batch_notifies_once_with_final_state() {
+        init_executor();
+        let store = Store::new(0, |s: i32, n: i32| s + n);
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let s = seen.clone();
+        store.watch(move |v| s.lock().unwrap().push(*v));
+        let doubled = store.derived(|v| v * 2);
+        let d = seen.clone();
+        doubled.watch(move |v| d.lock().unwrap().push(*v));
+
+        store.dispatch_batch([1, 2, 3]);
+        executor::tick();
+        assert_eq!(store.get(), 6);
+        assert_eq!(*seen.lock().unwrap(), vec![6, 12]);
+    }
+
+    #[test]
+    fn coalesced_store_notifies_once_per_drained_queue() {
+        init_executor();
+        let store = Store::builder(0, |s: i32, n: i32| s + n)
+            .coalesce_notifications()
+            .build();
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let s = seen.clone();
+        store.watch(move |v| s.lock().unwrap().push(*v));
+
+        for n in 1..=4 {
+            store.dispatch(n);
+        }
+        executor::tick();
+        store.dispatch(10);
+        executor::tick();
+        assert_eq!(*seen.lock().unwrap(), vec![10, 20]);
+    }
+
+    #[test]
+    fn no_op_action_does_not_notify_watchers() {
+        init_executor();
+        let store = Store::new(0, |s: i32, n: i32| s.max(n));
+        let calls = Arc::new(std::sync::Mutex::new(0));
+        let c = calls.clone();
+        store.watch(move |_| *c.lock().unwrap() += 1);
+
+        store.dispatch(5);
+        store.dispatch(3);
+        store.dispatch(5);
+        executor::tick();
+        assert_eq!(*calls.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn mut_reducer_mutates_state_in_place() {
+        init_executor();
+        let mut reduced = 0;
+        let store = Store::new_mut(ToDo::default(), move |todo: &mut ToDo, action| {
+            reduced += 1;
+            match action {
+                Action::Add(what) => todo.items.push(Item {
+                    what: format!("{reduced}: {what}"),
+                    done: false,
+                }),
+                Action::Done(index) => todo.items[index].done = true,
+            }
+        });
+
+        store.dispatch(Action::Add("a".into()));
+        store.dispatch(Action::Add("b".into()));
+        store.dispatch(Action::Done(1));
+        executor::tick();
+
+        let items = store.get().items;
+        assert_eq!(items[0].what, "1: a");
+        assert_eq!(items[1].what, "2: b");
+        assert!(items[1].done);
+    }
+
+    #[test]
+    fn fallible_reducer_reports_rejected_actions() {
+        init_executor();
+        let errors = Arc::new(std::sync::Mutex::new(Vec::<usize>::new()));
+        let e = errors.clone();
+        let store = Store::new_fallible(
+            ToDo::default(),
+            |mut todo: ToDo, action| {
+                match action {
+                    Action::Add(what) => todo.items.push(Item { what, done: false }),
+                    Action::Done(index) => {
+                        todo.items.get_mut(index).ok_or(index)?.done = true;
+                    }
+                }
+                Ok(todo)
+            },
+            move |index| e.lock().unwrap().push(index),
+        );
+
+        store.dispatch(Action::Add("a".into()));
+        store.dispatch(Action::Done(3));
+        store.dispatch(Action::Done(0));
+        executor::tick();
+
+        assert!(store.get().items[0].done);
+        assert_eq!(*errors.lock().unwrap(), vec![3]);
+    }
+
+    #[test]
+    fn shutdown_drains_the_queue_and_waits_for_effects() {
+        use crate::test::TestClock;
+        use futures::FutureExt;
+
+        init_executor();
+        let clock = TestClock::new();
+        let store = Store::builder_with_deps(
+            0,
+            |s: i32, n: i32| -> (i32, Effect<i32>) {
+                let effect = Effect::new(|ctx: Context<i32>| async move {
+                    ctx.clock().sleep(Duration::from_secs(1)).await;
+                    ctx.dispatch(100);
+                });
+                (s + n, effect)
+            },
+            (),
+        )
+        .with_clock(clock.clone())
+        .build();
+
+        store.dispatch(1);
+        store.dispatch(2);
+        let mut shutdown = store.shutdown();
+        store.dispatch(3);
+        executor::tick();
+        assert_eq!(store.get(), 3);
+        assert!((&mut shutdown).now_or_never().is_none());
+
+        clock.advance(Duration::from_secs(1));
+        assert!(shutdown.now_or_never().is_some());
+        assert_eq!(store.get(), 3);
+    }
+
+    #[test]
+    fn shutdown_now_aborts_running_and_queued_effects() {
+        use crate::test::TestClock;
+        use futures::FutureExt;
+
+        init_executor();
+        let clock = TestClock::new();
+        let store = Store::builder_with_deps(
+            0,
+            |s: i32, n: i32| -> (i32, Effect<i32>) {
+                let effect = Effect::new(|ctx: Context<i32>| async move {
+                    ctx.clock().sleep(Duration::from_secs(1)).await;
+                    ctx.dispatch(100);
+                });
+                (s + n, effect)
+            },
+            (),
+        )
+        .with_clock(clock.clone())
+        .build();
+
+        store.dispatch(1);
+        executor::tick();
+        store.dispatch(2);
+        let shutdown = store.shutdown_now();
+        executor::tick();
+        assert!(shutdown.now_or_never().is_some());
+        clock.advance(Duration::from_secs(1));
+        executor::tick();
+        assert_eq!(store.get(), 3);
+    }
+
+    #[test]
+    fn effects_idle_waits_for_running_effects() {
+        use crate::test::TestClock;
+        use futures::FutureExt;
+        use std::pin::pin;
+
+        init_executor();
+        let clock = TestClock::new();
+        let store = Store::builder_with_deps(
+            0,
+            |s: i32, n: i32| -> (i32, Effect<i32>) {
+                let effect = Effect::new(|ctx: Context<i32>| async move {
+                    ctx.clock().sleep(Duration::from_secs(1)).await;
+                });
+                (s + n, effect)
+            },
+            (),
+        )
+        .with_clock(clock.clone())
+        .build();
+
+        assert!(store.effects_idle().now_or_never().is_some());
+        store.dispatch(1);
+        executor::tick();
+        let mut idle = pin!(store.effects_idle());
+        assert!(idle.as_mut().now_or_never().is_none());
+        clock.advance(Duration::from_secs(1));
+        executor::tick();
+        assert!(idle.now_or_never().is_some());
+    }
+
+    #[test]
+    fn timed_out_effect_dispatches_the_timeout_action() {
+        use crate::test::TestClock;
+
+        init_executor();
+        let clock = TestClock::new();
+        let store = Store::builder_with_deps(
+            0,
+            |s: i32, n: i32| -> (i32, Effect<i32>) {
+                let effect = Effect::new(move |ctx: Context<i32>| async move {
+                    ctx.clock().sleep(Duration::from_secs(n as u64)).await;
+                    ctx.dispatch(100);
+                });
+                match n {
+                    1 | 5 => (s + n, effect.timeout(Duration::from_secs(2), -1)),
+                    _ => (s + n, Effect::none()),
+                }
+            },
+            (),
+        )
+        .with_clock(clock.clone())
+        .build();
+
+        store.dispatch(1);
+        store.dispatch(5);
+        executor::tick();
+        clock.advance(Duration::from_secs(2));
+        executor::tick();
+        clock.advance(Duration::from_secs(5));
+        executor::tick();
+        assert_eq!(store.get(), 1 + 5 + 100 - 1);
+    }
+
+    #[test]
+    fn retrying_effect_dispatches_failure_once_attempts_run_out() {
+        use crate::test::TestClock;
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        init_executor();
+        let clock = TestClock::new();
+        let attempts = Arc::new(AtomicU32::new(0));
+        let counted = attempts.clone();
+        let store = Store::builder_with_deps(
+            0,
+            move |s: i32, n: i32| -> (i32, Effect<i32>) {
+                if n != 0 {
+                    return (n, Effect::none());
+                }
+                let counted = counted.clone();
+                let policy = RetryPolicy::exponential(Duration::from_secs(1)).max_attempts(3);
+                let effect = Effect::retrying(
+                    policy,
+                    move |_| {
+                        counted.fetch_add(1, Ordering::Relaxed);
+                        async { Err::<i32, _>(-1) }
+                    },
+                    |error| error,
+                );
+                (s, effect)
+            },
+            (),
+        )
+        .with_clock(clock.clone())
+        .build();
+
+        store.dispatch(0);
+        executor::tick();
+        clock.advance(Duration::from_secs(1));
+        executor::tick();
+        assert_eq!((attempts.load(Ordering::Relaxed), store.get()), (2, 0));
+        clock.advance(Duration::from_secs(2));
+        executor::tick();
+        assert_eq!((attempts.load(Ordering::Relaxed), store.get()), (3, -1));
+    }
+
+    #[test]
+    fn thunk_dispatches_started_then_the_outcome() {
+        #[derive(Clone)]
+        struct Api {
+            fail: bool,
+        }
+
+        enum Msg {
+            Load,
+            Loaded(AsyncAction<i32, &'static str>),
+        }
+
+        init_executor();
+        let reduce = |mut log: Vec<String>, msg: Msg| -> (Vec<String>, Effect<Msg, Api>) {
+            let effect = match msg {
+                Msg::Load => Effect::thunk(Msg::Loaded, |api: Api| async move {
+                    if api.fail { Err("offline") } else { Ok(7) }
+                }),
+                Msg::Loaded(outcome) => {
+                    log.push(format!("{outcome:?}"));
+                    Effect::none()
+                }
+            };
+            (log, effect)
+        };
+        let store = Store::builder_with_deps(Vec::new(), reduce, Api { fail: false }).build();
+        let failing = Store::builder_with_deps(Vec::new(), reduce, Api { fail: true }).build();
+        store.dispatch(Msg::Load);
+        failing.dispatch(Msg::Load);
+        executor::tick();
+
+        assert_eq!(store.get(), vec!["Started", "Succeeded(7)"]);
+        assert_eq!(failing.get(), vec!["Started", "Failed(\"offline\")"]);
+    }
+
+    #[test]
+    fn debounced_effect_runs_only_after_the_burst() {
+        use crate::test::TestClock;
+
+        init_executor();
+        let clock = TestClock::new();
+        let store = Store::builder_with_deps(
+            Vec::new(),
+            |mut searched: Vec<i32>, query: i32| -> (Vec<i32>, Effect<i32>) {
+                if query < 0 {
+                    searched.push(-query);
+                    return (searched, Effect::none());
+                }
+                let effect = Effect::send(-query).debounced("search", Duration::from_millis(300));
+                (searched, effect)
+            },
+            (),
+        )
+        .with_clock(clock.clone())
+        .build();
+
+        for query in 1..=3 {
+            store.dispatch(query);
+            executor::tick();
+            clock.advance(Duration::from_millis(200));
+            executor::tick();
+        }
+        clock.advance(Duration::from_millis(100));
+        executor::tick();
+        assert_eq!(store.get(), vec![3]);
+    }
+
+    #[test]
+    fn throttled_effects_run_once_per_window() {
+        use crate::test::TestClock;
+
+        init_executor();
+        let clock = TestClock::new();
+        let store = Store::builder_with_deps(
+            0,
+            |loads: i32, action: bool| -> (i32, Effect<bool>) {
+                match action {
+                    true => (
+                        loads,
+                        Effect::send(false).throttled("scroll", Duration::from_secs(1)),
+                    ),
+                    false => (loads + 1, Effect::none()),
+                }
+            },
+            (),
+        )
+        .with_clock(clock.clone())
+        .build();
+
+        for _ in 0..4 {
+            store.dispatch(true);
+            executor::tick();
+            clock.advance(Duration::from_millis(400));
+        }
+        assert_eq!(store.get(), 2);
+    }
+
+    #[test]
+    fn settle_waits_for_effect_chains() {
+        use crate::test::TestClock;
+        use futures::FutureExt;
+
+        init_executor();
+        let clock = TestClock::new();
+        let store = Store::builder_with_deps(
+            0,
+            |s: i32, n: i32| -> (i32, Effect<i32>) {
+                if n == 0 {
+                    return (s, Effect::none());
+                }
+                let effect = Effect::new(move |ctx: Context<i32>| async move {
+                    ctx.clock().sleep(Duration::from_secs(1)).await;
+                    ctx.dispatch(n - 1);
+                });
+                (s + n, effect)
+            },
+            (),
+        )
+        .with_clock(clock.clone())
+        .build();
+        assert!(store.settle().now_or_never().is_some());
+
+        store.dispatch(2);
+        let mut settled = store.settle();
+        executor::tick();
+        assert!((&mut settled).now_or_never().is_none());
+
+        clock.advance(Duration::from_secs(1));
+        assert!((&mut settled).now_or_never().is_none());
+        clock.advance(Duration::from_secs(1));
+        assert!(settled.now_or_never().is_some());
+        assert_eq!(store.get(), 3);
+    }
+
+    #[test]
+    fn wait_for_resolves_with_the_first_matching_state() {
+        use futures::FutureExt;
+
+        init_executor();
+        let store = Store::new(0, |s: i32, n: i32| s + n);
+        assert_eq!(store.wait_for(|s| *s == 0).now_or_never(), Some(0));
+
+        let mut waiting = store.wait_for(|s| *s >= 3);
+        store.dispatch(1);
+        executor::tick();
+        assert_eq!((&mut waiting).now_or_never(), None);
+
+        store.dispatch(2);
+        store.dispatch(4);
+        executor::tick();
+        assert_eq!(waiting.now_or_never(), Some(3));
+    }
+
+    #[test]
+    fn state_stream_yields_each_distinct_state() {
+        use futures::{FutureExt, StreamExt};
+
+        init_executor();
+        let store = Store::new_mut(0, |s: &mut i32, n: i32| *s += n);
+        let mut states = store.state_stream();
+        assert_eq!(states.next().now_or_never(), None);
+
+        store.dispatch(1);
+        executor::tick();
+        store.dispatch(0);
+        executor::tick();
+        store.dispatch(2);
+        executor::tick();
+
+        drop(store);
+        executor::tick();
+        assert_eq!(states.collect::<Vec<_>>().now_or_never(), Some(vec![1, 3]));
+    }
+
+    #[test]
+    fn actions_are_broadcast_to_every_subscriber() {
+        use futures::{FutureExt, StreamExt};
+
+        init_executor();
+        let store = Store::new(0, |s: i32, n: i32| s + n);
+        store.dispatch(1);
+        executor::tick();
+
+        let first = store.actions();
+        let second = store.actions();
+        store.dispatch(2);
+        store.dispatch_batch(vec![3, 4]);
+        executor::tick();
+
+        drop(store);
+        executor::tick();
+        assert_eq!(
+            first.collect::<Vec<_>>().now_or_never(),
+            Some(vec![2, 3, 4])
+        );
+        assert_eq!(
+            second.collect::<Vec<_>>().now_or_never(),
+            Some(vec![2, 3, 4])
+        );
+    }
+
+    #[test]
+    fn inspect_sees_each_transition() {
+        init_executor();
+        let store = Store::new(0, |s: i32, n: i32| s + n);
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = seen.clone();
+        store.inspect(move |n, prev, next| log.lock().unwrap().push((*n, *prev, *next)));
 
-        store.dispatch(Action::Done(0));
+        store.dispatch(1);
+        store.dispatch_batch(vec![2, 0]);
         executor::tick();
-        assert_eq!(*count_a.read().unwrap(), 1);
-        assert_eq!(*count_b.read().unwrap(), 1);
+        assert_eq!(*seen.lock().unwrap(), vec![(1, 0, 1), (2, 1, 3), (0, 3, 3)]);
+    }
 
-        drop(reader_a); // disconnect a
+    #[test]
+    fn panicking_inspector_is_removed_and_later_actions_are_reduced() {
+        init_executor();
+        let panics = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let p = panics.clone();
+        let store = Store::builder(0, |s: i32, n: i32| s + n)
+            .on_panic(move |panic| p.lock().unwrap().push(panic.clone()))
+            .build();
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = seen.clone();
+        store.inspect(|n: &i32, _, _| assert_ne!(*n, 2, "inspector failed"));
+        store.inspect(move |n, _, next| log.lock().unwrap().push((*n, *next)));
 
-        store.dispatch(Action::Add("New".into()));
+        store.dispatch(1);
+        store.dispatch(2);
+        store.dispatch(3);
         executor::tick();
-        assert_eq!(*count_a.read().unwrap(), 1); // still 1
-        assert_eq!(*count_b.read().unwrap(), 2); // still active
+        assert_eq!(store.get(), 6);
+        assert_eq!(*seen.lock().unwrap(), vec![(1, 1), (2, 3), (3, 6)]);
+        let panics = panics.lock().unwrap();
+        assert_eq!(panics.len(), 1);
+        assert_eq!(panics[0].origin(), PanicOrigin::Tap);
+
+        let later = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = later.clone();
+        store.inspect(move |n, _, _| log.lock().unwrap().push(*n));
+        store.dispatch(4);
+        executor::tick();
+        assert_eq!(store.get(), 10);
+        assert_eq!(*later.lock().unwrap(), vec![4]);
     }
 
     #[test]
-    fn with_on_two_derived_readers() {
-        use std::sync::{Arc, RwLock};
+    fn builder_names_the_store() {
+        init_executor();
+        let store = Store::new(0, |s: i32, n: i32| s + n);
+        assert_eq!(store.name(), "store");
+        let store = Store::builder(0, |s: i32, n: i32| s + n)
+            .name("counter")
+            .build();
+        assert_eq!(store.name(), "counter");
+    }
 
+    #[test]
+    fn metrics_count_commands_and_queue_depth() {
         init_executor();
+        let store = Store::new(0, |s: i32, n: i32| s + n);
+        store.dispatch(1);
+        store.dispatch_batch(vec![2, 3]);
+        let metrics = store.metrics();
+        assert_eq!(
+            (metrics.queue_depth, metrics.dispatched, metrics.processed),
+            (2, 2, 0)
+        );
 
-        let store = Store::new(
-            ToDo {
-                items: vec![
-                    Item {
-                        what: "A".into(),
-                        done: false,
-                    },
-                    Item {
-                        what: "B".into(),
-                        done: false,
-                    },
-                ],
-            },
-            reducer,
+        executor::tick();
+        let metrics = store.metrics();
+        assert_eq!(
+            (metrics.queue_depth, metrics.dispatched, metrics.processed),
+            (0, 2, 2)
         );
+        assert_eq!(metrics.reduce_latency.count(), 2);
+    }
 
-        let r0 = store.derived(|s| s.items[0].done);
-        let r1 = store.derived(|s| s.items[1].done);
-        let combined = with((r0, r1));
+    #[test]
+    fn unbounded_store_never_reports_full() {
+        init_executor();
+        let store = Store::new_unbounded(0u32, |state: u32, n: u32| state + n);
+        for _ in 0..10_000 {
+            store.try_dispatch(1).unwrap();
+        }
+        executor::tick();
+        assert_eq!(store.get(), 10_000);
 
-        let received: Arc<RwLock<Option<(bool, bool)>>> = Arc::new(RwLock::new(None));
-        let rc = received.clone();
-        combined.watch(move |v| *rc.write().unwrap() = Some(*v));
+        store.shutdown();
+        assert!(store.try_dispatch(1).unwrap_err().is_closed());
+    }
 
-        store.dispatch(Action::Done(0));
+    #[test]
+    fn latest_reports_skipped_updates() {
+        use futures::FutureExt;
+
+        init_executor();
+        let store = Store::new_with_capacity(0u32, |state: u32, n: u32| state + n, 1000);
+        let latest = store.latest();
+
+        for _ in 0..1000 {
+            store.dispatch(1);
+        }
         executor::tick();
-        assert_eq!(*received.read().unwrap(), Some((true, false)));
 
-        store.dispatch(Action::Done(1));
+        let updates = latest
+            .changed()
+            .now_or_never()
+            .expect("a newer value exists");
+        assert_eq!(latest.get(), store.get());
+        assert_eq!(updates, 1000);
+        assert_eq!(latest.generation(), 1000);
+    }
+
+    #[test]
+    fn latest_outlives_unbind() {
+        init_executor();
+        let store = Store::new(0u32, |state: u32, n: u32| state + n);
+        let latest = store.latest();
+        store.unbind();
+        store.dispatch(5);
         executor::tick();
-        assert_eq!(*received.read().unwrap(), Some((true, true)));
+        assert_eq!(latest.get(), 5);
     }
 
     #[test]
@@ -654,12 +2587,14 @@ mod tests {
 
     fn channel_context<A: crate::Action>(sender: futures::channel::mpsc::Sender<A>) -> Context<A> {
         Context {
-            dispatcher: Arc::new(move |action: A| {
-                let mut s = sender.clone();
-                let result = s.try_send(action);
-                handle_dispatch_result(result);
-            }),
+            dispatcher: Dispatcher::new(
+                Arc::new(dispatcher::ChannelSink::new(sender, |a| a)),
+                Arc::new(SystemClock),
+            ),
             deps: (),
+            clock: Arc::new(SystemClock),
+            keyed_effects: Default::default(),
+            state: Arc::new(()),
         }
     }
 
@@ -688,6 +2623,9 @@ mod tests {
         let ctx: Context<i32, MyDeps> = Context {
             dispatcher: base.dispatcher,
             deps: MyDeps { value: 42 },
+            clock: base.clock,
+            keyed_effects: base.keyed_effects,
+            state: base.state,
         };
         let mapped: Context<bool, MyDeps> = ctx.map(|b: bool| if b { 1 } else { 0 });
         assert_eq!(mapped.deps().value, 42);
@@ -717,6 +2655,60 @@ mod tests {
         assert_eq!(store.get().items.len(), 1);
     }
 
+    #[test]
+    fn builder_sets_deps_error_handlers_and_capacity() {
+        init_executor();
+        let store = Store::builder(0, |s: i32, n: i32| s + n)
+            .deps("api")
+            .capacity(4)
+            .build();
+        assert_eq!(*store.context().deps(), "api");
+        store.dispatch(2);
+        executor::tick();
+        assert_eq!(store.get(), 2);
+
+        let errors = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let e = errors.clone();
+        let store =
+            Store::builder_fallible(0, |s: i32, n: i32| if n < 0 { Err(n) } else { Ok(s + n) })
+                .on_error(move |n| e.lock().unwrap().push(n))
+                .name("fallible")
+                .build();
+        store.dispatch(1);
+        store.dispatch(-5);
+        executor::tick();
+        assert_eq!(store.get(), 1);
+        assert_eq!(store.name(), "fallible");
+        assert_eq!(*errors.lock().unwrap(), vec![-5]);
+
+        let store = Store::builder_with_regions(0, |s: &mut i32, n: i32| {
+            *s += n;
+            Changed::ALL
+        })
+        .unbounded()
+        .build();
+        store.dispatch(3);
+        executor::tick();
+        assert_eq!(store.get(), 3);
+    }
+
+    #[test]
+    fn overflow_policy_drops_actions_past_the_capacity() {
+        init_executor();
+        let store = Store::builder(0, |s: i32, n: i32| s + n)
+            .with_capacity(2)
+            .overflow_policy(OverflowPolicy::Drop)
+            .build();
+        for n in [1, 2, 4, 8] {
+            store.dispatch(n);
+        }
+        executor::tick();
+        assert_eq!(store.get(), 3);
+        store.dispatch(4);
+        executor::tick();
+        assert_eq!(store.get(), 7);
+    }
+
     #[test]
     fn builder_with_logger_middleware() {
         init_executor();
@@ -812,4 +2804,517 @@ mod tests {
         executor::tick();
         assert_eq!(store.get(), 50);
     }
+
+    #[test]
+    fn reducer_panic_is_reported_and_the_store_keeps_running() {
+        init_executor();
+        let panics = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let p = panics.clone();
+        let store = Store::builder(0, |s: i32, n: i32| {
+            assert!(n >= 0, "negative action {n}");
+            s + n
+        })
+        .on_panic(move |panic| p.lock().unwrap().push(panic.clone()))
+        .build();
+
+        store.dispatch(1);
+        store.dispatch(-1);
+        store.dispatch(2);
+        executor::tick();
+
+        assert_eq!(store.get(), 3);
+        let panics = panics.lock().unwrap();
+        assert_eq!(panics.len(), 1);
+        assert_eq!(panics[0].origin(), PanicOrigin::Reducer);
+        assert_eq!(panics[0].message(), "negative action -1");
+    }
+
+    #[test]
+    fn in_place_reducer_panic_does_not_poison_the_state() {
+        init_executor();
+        let store = Store::new_mut(Vec::new(), |v: &mut Vec<i32>, n: i32| {
+            v.push(n);
+            assert!(n >= 0);
+        });
+
+        store.dispatch(-1);
+        store.dispatch(1);
+        executor::tick();
+
+        assert_eq!(store.get(), vec![-1, 1]);
+    }
+
+    #[test]
+    fn effect_panic_is_reported() {
+        init_executor();
+        let panics = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let p = panics.clone();
+        let store = Store::builder_with_deps(
+            0,
+            |s: i32, n: i32| -> (i32, Effect<i32>) {
+                let effect = Effect::new(move |_: Context<i32>| async move {
+                    if n == 0 {
+                        panic!("effect failed");
+                    }
+                });
+                (s + n, effect)
+            },
+            (),
+        )
+        .on_panic(move |panic| p.lock().unwrap().push(panic.to_string()))
+        .build();
+
+        store.dispatch(0);
+        store.dispatch(1);
+        executor::tick();
+
+        assert_eq!(store.get(), 1);
+        assert_eq!(
+            *panics.lock().unwrap(),
+            vec!["effect panicked: effect failed"]
+        );
+    }
+
+    #[test]
+    fn supervisor_rolls_back_the_failed_command_and_backs_off() {
+        use crate::test::TestClock;
+
+        init_executor();
+        let clock = TestClock::new();
+        let store = Store::builder(0, |s: i32, n: i32| {
+            assert!(n >= 0);
+            s + n
+        })
+        .with_clock(clock.clone())
+        .supervise(Supervisor::new().backoff(Duration::from_secs(1), Duration::from_secs(1)))
+        .build();
+
+        store.dispatch_batch([1, -1, 2]);
+        store.dispatch(5);
+        executor::tick();
+        assert_eq!(store.get(), 0);
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(store.get(), 5);
+    }
+
+    #[test]
+    fn supervisor_only_rolls_back_the_failed_command_of_a_coalesced_run() {
+        init_executor();
+        let store = Store::builder_with_deps(
+            0,
+            |s: i32, n: i32| {
+                assert!(n >= 0);
+                let effect = if n == 2 {
+                    Effect::send(10)
+                } else {
+                    Effect::none()
+                };
+                (s + n, effect)
+            },
+            (),
+        )
+        .coalesce_notifications()
+        .supervise(Supervisor::new())
+        .build();
+
+        store.dispatch(1);
+        store.dispatch(2);
+        store.dispatch(-1);
+        store.dispatch(3);
+        executor::tick();
+
+        assert_eq!(store.get(), 16);
+    }
+
+    #[test]
+    fn supervised_in_place_reducer_never_shows_a_half_reduced_state() {
+        init_executor();
+        let store = Store::builder_with_regions(Vec::new(), |v: &mut Vec<i32>, n: i32| {
+            v.push(n);
+            assert!(n >= 0);
+            Changed::ALL
+        })
+        .supervise(Supervisor::new())
+        .build();
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let s = seen.clone();
+        store.watch(move |v| s.lock().unwrap().push(v.clone()));
+
+        store.dispatch(1);
+        store.dispatch(-1);
+        executor::tick();
+        assert_eq!(store.get(), vec![1]);
+
+        store.dispatch(2);
+        executor::tick();
+        assert_eq!(*seen.lock().unwrap(), vec![vec![1], vec![1, 2]]);
+    }
+
+    #[test]
+    fn supervisor_shuts_the_store_down_after_too_many_restarts() {
+        init_executor();
+        let store = Store::builder(0, |s: i32, n: i32| {
+            assert!(n >= 0);
+            s + n
+        })
+        .supervise(Supervisor::new().max_restarts(1, Duration::from_secs(60)))
+        .build();
+
+        store.dispatch(-1);
+        store.dispatch(1);
+        store.dispatch(-1);
+        executor::tick();
+
+        assert_eq!(store.get(), 1);
+        assert!(store.try_dispatch(1).unwrap_err().is_closed());
+    }
+
+    // ── Region tests ──────────────────────────────────────────────────────────
+
+    mod regions {
+        use super::*;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static STATE_EQ_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(Clone, Copy)]
+        enum AppRegion {
+            Todos,
+            Filter,
+        }
+
+        impl Region for AppRegion {
+            fn index(self) -> u32 {
+                self as u32
+            }
+        }
+
+        #[derive(Clone, Default)]
+        struct App {
+            todos: Vec<String>,
+            filter: String,
+        }
+
+        impl PartialEq for App {
+            fn eq(&self, other: &Self) -> bool {
+                STATE_EQ_CALLS.fetch_add(1, Ordering::Relaxed);
+                self.todos == other.todos && self.filter == other.filter
+            }
+        }
+
+        enum AppAction {
+            Add(String),
+            SetFilter(String),
+            Noop,
+        }
+
+        fn reducer(state: &mut App, action: AppAction) -> Changed {
+            match action {
+                AppAction::Add(todo) => {
+                    state.todos.push(todo);
+                    AppRegion::Todos.into()
+                }
+                AppAction::SetFilter(filter) => {
+                    state.filter = filter;
+                    AppRegion::Filter.into()
+                }
+                AppAction::Noop => Changed::NONE,
+            }
+        }
+
+        fn counted<T>(
+            count: &Arc<AtomicUsize>,
+            f: impl Fn(&App) -> T + Send + Sync + 'static,
+        ) -> impl Fn(&App) -> T + Send + Sync + 'static {
+            let count = count.clone();
+            move |s| {
+                count.fetch_add(1, Ordering::Relaxed);
+                f(s)
+            }
+        }
+
+        #[test]
+        fn marking_one_region_never_evaluates_another() {
+            init_executor();
+            let store = Store::new_with_regions(App::default(), reducer);
+            let todos_evals = Arc::new(AtomicUsize::new(0));
+            let filter_evals = Arc::new(AtomicUsize::new(0));
+            let todos =
+                store.reader_in_region(AppRegion::Todos, counted(&todos_evals, |s| s.todos.len()));
+            let filter = store.reader_in_region(
+                AppRegion::Filter,
+                counted(&filter_evals, |s| s.filter.clone()),
+            );
+            let (todos_before, filter_before) = (
+                todos_evals.load(Ordering::Relaxed),
+                filter_evals.load(Ordering::Relaxed),
+            );
+
+            store.dispatch(AppAction::Add("a".into()));
+            store.dispatch(AppAction::Add("b".into()));
+            executor::tick();
+
+            assert_eq!(todos.get(), 2);
+            assert_eq!(filter.get(), "");
+            assert_eq!(todos_evals.load(Ordering::Relaxed) - todos_before, 2);
+            assert_eq!(filter_evals.load(Ordering::Relaxed), filter_before);
+        }
+
+        #[test]
+        fn region_reader_watchers_fire_only_for_their_region() {
+            init_executor();
+            let store = Store::new_with_regions(App::default(), reducer);
+            let filter = store.reader_in_region(AppRegion::Filter, |s| s.filter.clone());
+            let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let c = calls.clone();
+            filter.watch(move |f| c.lock().unwrap().push(f.clone()));
+
+            store.dispatch(AppAction::Add("a".into()));
+            store.dispatch(AppAction::SetFilter("done".into()));
+            executor::tick();
+
+            assert_eq!(*calls.lock().unwrap(), vec!["done".to_string()]);
+        }
+
+        #[test]
+        fn root_watch_fires_on_any_marked_change_without_comparing_state() {
+            init_executor();
+            let store = Store::new_with_regions(App::default(), reducer);
+            let calls = Arc::new(AtomicUsize::new(0));
+            let c = calls.clone();
+            store.watch(move |_| {
+                c.fetch_add(1, Ordering::Relaxed);
+            });
+            let eq_before = STATE_EQ_CALLS.load(Ordering::Relaxed);
+
+            store.dispatch(AppAction::Add("a".into()));
+            store.dispatch(AppAction::Noop);
+            store.dispatch(AppAction::SetFilter("x".into()));
+            executor::tick();
+
+            assert_eq!(calls.load(Ordering::Relaxed), 2);
+            assert_eq!(STATE_EQ_CALLS.load(Ordering::Relaxed), eq_before);
+        }
+    }
+
+    // ── ArcState tests ────────────────────────────────────────────────────────
+
+    mod arc_state {
+        use super::*;
+        use std::cell::Cell;
+
+        thread_local! {
+            static DEEP_CLONES: Cell<usize> = const { Cell::new(0) };
+        }
+
+        fn deep_clones() -> usize {
+            DEEP_CLONES.with(Cell::get)
+        }
+
+        #[derive(PartialEq)]
+        struct Payload(Vec<u32>);
+
+        impl Clone for Payload {
+            fn clone(&self) -> Self {
+                DEEP_CLONES.with(|c| c.set(c.get() + 1));
+                Payload(self.0.clone())
+            }
+        }
+
+        #[derive(Clone, PartialEq)]
+        struct Doc {
+            items: ArcState<Payload>,
+            title: String,
+        }
+
+        #[test]
+        fn unshared_state_is_mutated_without_deep_clones() {
+            init_executor();
+            let store = Store::new_arc(Payload(Vec::new()), |p: &mut Payload, n: u32| p.0.push(n));
+            let before = deep_clones();
+
+            for n in 0..100 {
+                store.dispatch(n);
+            }
+            executor::tick();
+
+            assert_eq!(deep_clones(), before);
+            assert_eq!(store.get().0.len(), 100);
+        }
+
+        #[test]
+        fn held_snapshot_forces_exactly_one_copy() {
+            init_executor();
+            let store = Store::new_arc(Payload(Vec::new()), |p: &mut Payload, n: u32| p.0.push(n));
+            let snapshot = store.get();
+            let before = deep_clones();
+
+            for n in 0..10 {
+                store.dispatch(n);
+            }
+            executor::tick();
+
+            assert_eq!(deep_clones() - before, 1);
+            assert!(snapshot.0.is_empty());
+            assert_eq!(store.get().0.len(), 10);
+        }
+
+        #[test]
+        fn selectors_return_cheap_arc_slices() {
+            init_executor();
+            let store = Store::new_arc(
+                Doc {
+                    items: ArcState::new(Payload(vec![1])),
+                    title: "a".into(),
+                },
+                |doc: &mut Doc, title: String| doc.title = title,
+            );
+            let before = deep_clones();
+            let items = store.derived(|doc| doc.items.clone());
+            let calls = Arc::new(std::sync::Mutex::new(0));
+            let c = calls.clone();
+            items.watch(move |_| *c.lock().unwrap() += 1);
+
+            store.dispatch("b".into());
+            executor::tick();
+
+            assert_eq!(deep_clones(), before);
+            assert_eq!(*calls.lock().unwrap(), 0);
+            assert!(items.get().ptr_eq(&store.get().items));
+        }
+
+        #[test]
+        fn reads_and_selectors_do_not_copy_the_state() {
+            init_executor();
+            let store = Store::new_with_regions(Payload(Vec::new()), |p: &mut Payload, n: u32| {
+                p.0.push(n);
+                Changed::ALL
+            });
+            let len = store.derived_in_place(|p| p.0.len());
+            let before = deep_clones();
+
+            store.dispatch(1);
+            executor::tick();
+
+            assert_eq!(len.get(), 1);
+            assert!(Arc::ptr_eq(&store.get_arc(), &store.get_arc()));
+            assert_eq!(deep_clones(), before);
+        }
+
+        #[test]
+        fn watch_arc_shares_the_state_without_copying_it() {
+            init_executor();
+            let store = Store::new_with_regions(Payload(Vec::new()), |p: &mut Payload, n: u32| {
+                p.0.push(n);
+                Changed::ALL
+            });
+            let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let s = seen.clone();
+            store.watch_arc(move |state| s.lock().unwrap().push(state));
+            let before = deep_clones();
+
+            store.dispatch(1);
+            executor::tick();
+
+            assert_eq!(deep_clones(), before);
+            let seen = seen.lock().unwrap();
+            assert_eq!(seen.len(), 1);
+            assert_eq!(seen[0].0, vec![1]);
+        }
+    }
+
+    // ── Projection tests ──────────────────────────────────────────────────────
+
+    mod projection {
+        use super::*;
+        use std::cell::Cell;
+
+        thread_local! {
+            static CLONES: Cell<usize> = const { Cell::new(0) };
+            static COMPARISONS: Cell<usize> = const { Cell::new(0) };
+        }
+
+        fn counters() -> (usize, usize) {
+            (CLONES.with(Cell::get), COMPARISONS.with(Cell::get))
+        }
+
+        struct Element(u32);
+
+        impl Clone for Element {
+            fn clone(&self) -> Self {
+                CLONES.with(|c| c.set(c.get() + 1));
+                Element(self.0)
+            }
+        }
+
+        impl PartialEq for Element {
+            fn eq(&self, other: &Self) -> bool {
+                COMPARISONS.with(|c| c.set(c.get() + 1));
+                self.0 == other.0
+            }
+        }
+
+        #[derive(Clone, PartialEq)]
+        struct Big {
+            items: Vec<Element>,
+            ticks: u32,
+        }
+
+        const LEN: usize = 1_000;
+        const ACTIONS: usize = 10;
+
+        fn big_store() -> Store<Big, ()> {
+            let items = (0..LEN as u32).map(Element).collect();
+            Store::new_with_regions(Big { items, ticks: 0 }, |big: &mut Big, ()| {
+                big.ticks += 1;
+                Changed::ALL
+            })
+        }
+
+        fn per_action_cost(store: &Store<Big, ()>) -> (usize, usize) {
+            let (clones, comparisons) = counters();
+            for _ in 0..ACTIONS {
+                store.dispatch(());
+            }
+            executor::tick();
+            let (clones_after, comparisons_after) = counters();
+            (
+                (clones_after - clones) / ACTIONS,
+                (comparisons_after - comparisons) / ACTIONS,
+            )
+        }
+
+        #[test]
+        fn project_does_not_clone_unchanged_selection() {
+            init_executor();
+
+            let store = big_store();
+            let reader = store.derived(|big| big.items.clone());
+            reader.watch(|_| {});
+            let (reader_clones, reader_comparisons) = per_action_cost(&store);
+            drop(reader);
+
+            let store = big_store();
+            let projection = store.project(|big| &big.items);
+            projection.watch(|_| {});
+            let (project_clones, project_comparisons) = per_action_cost(&store);
+
+            assert!(reader_clones >= LEN, "reader clones {reader_clones}");
+            assert_eq!(reader_comparisons, LEN);
+            assert_eq!(project_clones, 0);
+            assert_eq!(project_comparisons, LEN);
+            assert_eq!(projection.with(|items| items.len()), LEN);
+        }
+
+        #[test]
+        fn unwatched_projection_costs_nothing() {
+            init_executor();
+            let store = big_store();
+            let projection = store.project(|big| &big.items);
+            let (clones, comparisons) = per_action_cost(&store);
+            assert_eq!((clones, comparisons), (0, 0));
+            assert_eq!(projection.with(|items| items[3].0), 3);
+        }
+    }
 }
@@ -0,0 +1,271 @@
+//! A store for state and actions which are not thread-safe, such as the
+//! widget handles of GUI toolkits.
+
+use std::cell::RefCell;
+use std::rc::{Rc, Weak as RcWeak};
+use std::sync::Weak;
+
+use futures::StreamExt;
+use futures::channel::mpsc::{UnboundedSender, unbounded};
+use futures::future::LocalBoxFuture;
+
+use crate::subscription::{Connections, Subscription};
+
+type LocalEffectFn<A, D> = Box<dyn FnOnce(LocalContext<A, D>) -> LocalBoxFuture<'static, ()>>;
+type LocalWatcher<S> = (Weak<()>, Rc<dyn Fn(&S)>);
+
+/// The side effect returned by the reducer of a [`LocalStore`]: like
+/// [`Effect`](crate::Effect), but neither it nor its future need be `Send`.
+pub struct LocalEffect<A, D = ()> {
+    inner: Option<LocalEffectFn<A, D>>,
+}
+
+impl<A: 'static, D: 'static> LocalEffect<A, D> {
+    pub fn new<F, Fut>(f: F) -> Self
+    where
+        F: FnOnce(LocalContext<A, D>) -> Fut + 'static,
+        Fut: std::future::Future<Output = ()> + 'static,
+    {
+        LocalEffect {
+            inner: Some(Box::new(move |ctx| Box::pin(f(ctx)))),
+        }
+    }
+
+    pub fn none() -> Self {
+        LocalEffect { inner: None }
+    }
+
+    /// An effect which dispatches `action` as soon as it runs.
+    pub fn send(action: A) -> Self {
+        Self::new(move |ctx| async move { ctx.dispatch(action) })
+    }
+}
+
+/// What an effect of a [`LocalStore`] can reach: the store's dispatcher and
+/// dependencies.
+pub struct LocalContext<A, D = ()> {
+    dispatcher: LocalDispatcher<A>,
+    deps: D,
+}
+
+impl<A, D: Clone> Clone for LocalContext<A, D> {
+    fn clone(&self) -> Self {
+        LocalContext {
+            dispatcher: self.dispatcher.clone(),
+            deps: self.deps.clone(),
+        }
+    }
+}
+
+impl<A, D> LocalContext<A, D> {
+    pub fn dispatch(&self, action: A) {
+        self.dispatcher.dispatch(action);
+    }
+
+    /// Returns a cloneable handle which dispatches through this context.
+    pub fn dispatcher(&self) -> LocalDispatcher<A> {
+        self.dispatcher.clone()
+    }
+
+    pub fn deps(&self) -> &D {
+        &self.deps
+    }
+}
+
+/// A cloneable handle which dispatches into a [`LocalStore`]. Once the store
+/// has been dropped, actions dispatched through it are dropped too.
+pub struct LocalDispatcher<A> {
+    sender: UnboundedSender<A>,
+}
+
+impl<A> Clone for LocalDispatcher<A> {
+    fn clone(&self) -> Self {
+        LocalDispatcher {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<A> LocalDispatcher<A> {
+    /// Queues `action` without waiting.
+    pub fn dispatch(&self, action: A) {
+        let _ = self.sender.unbounded_send(action);
+    }
+}
+
+struct Shared<S, A, D> {
+    /// Only `None` while the reducer runs.
+    state: RefCell<Option<S>>,
+    watchers: RefCell<Vec<LocalWatcher<S>>>,
+    context: LocalContext<A, D>,
+}
+
+/// A store whose state, actions and dependencies need not be `Send` or
+/// `Sync`, pinned to the thread which created it.
+///
+/// Its reducer task and effects are spawned with
+/// [`Executor::spawn_local`](any_spawner::Executor::spawn_local), so the
+/// executor must support local tasks, as wasm-bindgen does, or tokio within a
+/// `LocalSet`. The queue is unbounded. State need not be `PartialEq` either,
+/// so watchers are called after every action.
+///
+/// ```
+/// use std::rc::Rc;
+/// use uniflow::LocalStore;
+///
+/// uniflow::manual_spawner::init().expect("init");
+///
+/// let store = LocalStore::new(Vec::new(), |mut labels: Vec<Rc<str>>, label: Rc<str>| {
+///     labels.push(label);
+///     labels
+/// });
+/// store.dispatch(Rc::from("ok"));
+/// uniflow::manual_spawner::step();
+/// assert_eq!(store.with(|labels| labels.len()), 1);
+/// ```
+pub struct LocalStore<S, A, D = ()> {
+    shared: Rc<Shared<S, A, D>>,
+    connections: Connections,
+}
+
+impl<S: 'static, A: 'static> LocalStore<S, A> {
+    pub fn new<R: FnMut(S, A) -> S + 'static>(state: S, mut reducer: R) -> Self {
+        Self::new_with_deps(state, move |s, a| (reducer(s, a), LocalEffect::none()), ())
+    }
+}
+
+impl<S: 'static, A: 'static, D: Clone + 'static> LocalStore<S, A, D> {
+    pub fn new_with_deps<R>(state: S, mut reducer: R, deps: D) -> Self
+    where
+        R: FnMut(S, A) -> (S, LocalEffect<A, D>) + 'static,
+    {
+        let (sender, mut receiver) = unbounded();
+        let shared = Rc::new(Shared {
+            state: RefCell::new(Some(state)),
+            watchers: RefCell::new(Vec::new()),
+            context: LocalContext {
+                dispatcher: LocalDispatcher { sender },
+                deps,
+            },
+        });
+        // The task holds the store weakly, so that dropping the store closes
+        // the queue once no dispatcher is left.
+        let task_shared: RcWeak<Shared<S, A, D>> = Rc::downgrade(&shared);
+        any_spawner::Executor::spawn_local(async move {
+            while let Some(action) = receiver.next().await {
+                let Some(shared) = task_shared.upgrade() else {
+                    break;
+                };
+                let state = shared.state.borrow_mut().take().expect("not reducing");
+                let (state, effect) = reducer(state, action);
+                *shared.state.borrow_mut() = Some(state);
+                shared.notify();
+                if let Some(f) = effect.inner {
+                    any_spawner::Executor::spawn_local(f(shared.context.clone()));
+                }
+            }
+        });
+        LocalStore {
+            shared,
+            connections: Connections::new(),
+        }
+    }
+}
+
+impl<S, A, D> LocalStore<S, A, D> {
+    /// Returns a copy of the current state.
+    pub fn get(&self) -> S
+    where
+        S: Clone,
+    {
+        self.with(S::clone)
+    }
+
+    /// Calls `f` with the current state, without cloning it.
+    pub fn with<R>(&self, f: impl FnOnce(&S) -> R) -> R {
+        f(self.shared.state.borrow().as_ref().expect("not reducing"))
+    }
+
+    pub fn dispatch(&self, action: A) {
+        self.shared.context.dispatch(action);
+    }
+
+    /// Returns a cloneable handle which dispatches into this store.
+    pub fn dispatcher(&self) -> LocalDispatcher<A> {
+        self.shared.context.dispatcher()
+    }
+
+    pub fn deps(&self) -> &D {
+        self.shared.context.deps()
+    }
+
+    /// Calls `f` with the new state after every action, until the returned
+    /// subscription is unsubscribed or the store is [unbound](Self::unbind).
+    pub fn watch<F: Fn(&S) + 'static>(&self, f: F) -> Subscription {
+        let (subscription, alive) = self.connections.connect();
+        self.shared.watchers.borrow_mut().push((alive, Rc::new(f)));
+        subscription
+    }
+
+    /// Disconnects every watcher of the store.
+    pub fn unbind(&self) {
+        self.connections.clear();
+    }
+}
+
+impl<S, A, D> Shared<S, A, D> {
+    fn notify(&self) {
+        // Watchers may watch the store in turn, so the list is not borrowed
+        // while they run.
+        let watchers: Vec<_> = {
+            let mut watchers = self.watchers.borrow_mut();
+            watchers.retain(|(alive, _)| alive.strong_count() > 0);
+            watchers.iter().map(|(_, f)| f.clone()).collect()
+        };
+        let state = self.state.borrow();
+        let state = state.as_ref().expect("not reducing");
+        for watcher in watchers {
+            watcher(state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{init as init_executor, tick};
+    use std::cell::Cell;
+
+    #[derive(Clone)]
+    struct Widget(Rc<Cell<u32>>);
+
+    enum Click {
+        Once,
+        Twice,
+    }
+
+    #[test]
+    fn reduces_non_send_state_and_runs_effects() {
+        init_executor();
+        let widget = Widget(Rc::new(Cell::new(0)));
+        let store = LocalStore::new_with_deps(
+            widget.clone(),
+            |widget: Widget, click: Click| {
+                widget.0.set(widget.0.get() + 1);
+                let effect = match click {
+                    Click::Once => LocalEffect::none(),
+                    Click::Twice => LocalEffect::send(Click::Once),
+                };
+                (widget, effect)
+            },
+            (),
+        );
+        let seen = Rc::new(Cell::new(0));
+        let watched = seen.clone();
+        store.watch(move |widget: &Widget| watched.set(widget.0.get()));
+        store.dispatch(Click::Twice);
+        tick();
+        assert_eq!(widget.0.get(), 2);
+        assert_eq!(seen.get(), 2);
+    }
+}
@@ -0,0 +1,248 @@
+use std::fmt::{self, Debug};
+use std::sync::Mutex;
+
+use crate::Middleware;
+
+/// The severity a [`LoggerMiddleware`] logs at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LogLevel::Error => "ERROR",
+            LogLevel::Warn => "WARN",
+            LogLevel::Info => "INFO",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Trace => "TRACE",
+        })
+    }
+}
+
+type Filter<A> = Box<dyn Fn(&A) -> bool + Send + Sync>;
+type Summary<S> = Box<dyn Fn(&S, &S) -> String + Send + Sync>;
+type Output = Box<dyn Fn(LogLevel, &str) + Send + Sync>;
+
+/// A [`Middleware`] which logs every action with a summary of the state
+/// change it caused.
+///
+/// Each reduced action produces a line at [`LogLevel::Info`], such as
+/// `Add("milk"): state changed`, and, when the state changed, one at
+/// [`LogLevel::Debug`] with a diff of its `Debug` representation, such as
+/// `Add("milk"): -items: [], +items: ["milk"]`. By default lines are emitted
+/// as `tracing` events with the `tracing` feature, and as `log` records
+/// otherwise.
+///
+/// ```
+/// use uniflow::{LogLevel, LoggerMiddleware, Store};
+///
+/// # let _ = uniflow::manual_spawner::init();
+/// let store = Store::builder(0, |s: i32, n: i32| s + n)
+///     .middleware(
+///         LoggerMiddleware::new()
+///             .level(LogLevel::Debug)
+///             .filter(|n: &i32| *n != 0)
+///             .summary(|prev: &i32, next: &i32| format!("{prev} -> {next}")),
+///     )
+///     .build();
+/// ```
+pub struct LoggerMiddleware<S, A> {
+    level: LogLevel,
+    filter: Option<Filter<A>>,
+    summary: Summary<S>,
+    output: Output,
+    /// The action being reduced, formatted by `before` for `after`.
+    pending: Mutex<Option<String>>,
+}
+
+impl<S: Debug, A> LoggerMiddleware<S, A> {
+    /// Logs every action, up to [`LogLevel::Info`].
+    pub fn new() -> Self {
+        LoggerMiddleware {
+            level: LogLevel::Info,
+            filter: None,
+            summary: Box::new(|previous: &S, next: &S| {
+                diff(&format!("{previous:#?}"), &format!("{next:#?}"))
+            }),
+            output: Box::new(emit),
+            pending: Mutex::new(None),
+        }
+    }
+}
+
+impl<S, A> LoggerMiddleware<S, A> {
+    /// Only logs lines up to `level`: [`Debug`](LogLevel::Debug) and beyond
+    /// include the summary of each state change.
+    pub fn level(mut self, level: LogLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Only logs the actions for which `f` returns `true`.
+    pub fn filter<F: Fn(&A) -> bool + Send + Sync + 'static>(mut self, f: F) -> Self {
+        self.filter = Some(Box::new(f));
+        self
+    }
+
+    /// Describes the state change with `f`, which receives the states before
+    /// and after the action, instead of diffing them.
+    pub fn summary<F: Fn(&S, &S) -> String + Send + Sync + 'static>(mut self, f: F) -> Self {
+        self.summary = Box::new(f);
+        self
+    }
+
+    /// Sends the lines to `f` instead of the default output.
+    pub fn output<F: Fn(LogLevel, &str) + Send + Sync + 'static>(mut self, f: F) -> Self {
+        self.output = Box::new(f);
+        self
+    }
+}
+
+impl<S: Debug, A> Default for LoggerMiddleware<S, A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, A> Middleware<S, A> for LoggerMiddleware<S, A>
+where
+    S: PartialEq + Send + 'static,
+    A: Debug + Send + 'static,
+{
+    fn before(&self, _: &S, action: A) -> Option<A> {
+        let logged =
+            self.level >= LogLevel::Info && self.filter.as_ref().is_none_or(|f| f(&action));
+        *self.pending.lock().unwrap() = logged.then(|| format!("{action:?}"));
+        Some(action)
+    }
+
+    fn after(&self, previous: &S, next: &S) {
+        let Some(action) = self.pending.lock().unwrap().take() else {
+            return;
+        };
+        if previous == next {
+            (self.output)(LogLevel::Info, &format!("{action}: state unchanged"));
+            return;
+        }
+        (self.output)(LogLevel::Info, &format!("{action}: state changed"));
+        if self.level >= LogLevel::Debug {
+            let summary = (self.summary)(previous, next);
+            (self.output)(LogLevel::Debug, &format!("{action}: {summary}"));
+        }
+    }
+}
+
+/// Summarises the lines which differ between two `Debug` representations,
+/// skipping those they start and end with in common.
+fn diff(previous: &str, next: &str) -> String {
+    let previous: Vec<&str> = previous.lines().collect();
+    let next: Vec<&str> = next.lines().collect();
+    let start = previous
+        .iter()
+        .zip(&next)
+        .take_while(|(p, n)| p == n)
+        .count();
+    let (previous, next) = (&previous[start..], &next[start..]);
+    let end = (previous.iter().rev().zip(next.iter().rev()))
+        .take_while(|(p, n)| p == n)
+        .count();
+    let line = |sign: char, line: &&str| format!("{sign}{}", line.trim().trim_end_matches(','));
+    let removed = previous[..previous.len() - end]
+        .iter()
+        .map(|l| line('-', l));
+    let added = next[..next.len() - end].iter().map(|l| line('+', l));
+    removed.chain(added).collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(feature = "tracing")]
+fn emit(level: LogLevel, line: &str) {
+    match level {
+        LogLevel::Error => tracing::error!("{line}"),
+        LogLevel::Warn => tracing::warn!("{line}"),
+        LogLevel::Info => tracing::info!("{line}"),
+        LogLevel::Debug => tracing::debug!("{line}"),
+        LogLevel::Trace => tracing::trace!("{line}"),
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+fn emit(level: LogLevel, line: &str) {
+    let level = match level {
+        LogLevel::Error => log::Level::Error,
+        LogLevel::Warn => log::Level::Warn,
+        LogLevel::Info => log::Level::Info,
+        LogLevel::Debug => log::Level::Debug,
+        LogLevel::Trace => log::Level::Trace,
+    };
+    log::log!(level, "{line}");
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{init as init_executor, tick};
+    use crate::{Dispatch, Store};
+    use std::sync::Arc;
+
+    #[test]
+    fn logs_filtered_actions_with_a_summary() {
+        init_executor();
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let sink = lines.clone();
+        let store = Store::builder(0, |s: i32, n: i32| s + n)
+            .middleware(
+                LoggerMiddleware::new()
+                    .level(LogLevel::Debug)
+                    .filter(|n: &i32| *n >= 0)
+                    .output(move |level, line| {
+                        sink.lock().unwrap().push(format!("{level} {line}"))
+                    }),
+            )
+            .build();
+        store.dispatch(2);
+        store.dispatch(-1);
+        store.dispatch(0);
+        tick();
+        assert_eq!(
+            *lines.lock().unwrap(),
+            vec![
+                "INFO 2: state changed",
+                "DEBUG 2: -0, +2",
+                "INFO 0: state unchanged"
+            ]
+        );
+    }
+
+    #[test]
+    fn logs_up_to_the_level() {
+        init_executor();
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let sink = lines.clone();
+        let store =
+            Store::builder(0, |s: i32, n: i32| s + n)
+                .middleware(LoggerMiddleware::new().output(move |level, line| {
+                    sink.lock().unwrap().push(format!("{level} {line}"))
+                }))
+                .build();
+        store.dispatch(1);
+        tick();
+        assert_eq!(*lines.lock().unwrap(), vec!["INFO 1: state changed"]);
+    }
+
+    #[test]
+    fn diffs_only_the_changed_lines() {
+        let (previous, next) = (vec![1, 2, 3], vec![1, 5, 3]);
+        assert_eq!(
+            diff(&format!("{previous:#?}"), &format!("{next:#?}")),
+            "-2, +5"
+        );
+    }
+}
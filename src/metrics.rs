@@ -0,0 +1,163 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Upper bounds of the reduce latency buckets. A last, unbounded bucket
+/// counts everything slower.
+const BOUNDS: [Duration; 7] = [
+    Duration::from_micros(1),
+    Duration::from_micros(10),
+    Duration::from_micros(100),
+    Duration::from_millis(1),
+    Duration::from_millis(10),
+    Duration::from_millis(100),
+    Duration::from_secs(1),
+];
+
+/// A snapshot of a store's runtime metrics, returned by
+/// [`Store::metrics`](crate::Store::metrics).
+///
+/// Commands count dispatched actions, with a batch counting once. With the
+/// `metrics` feature the same figures are also reported through the
+/// [`metrics`](https://docs.rs/metrics) facade, labelled with the store name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoreMetrics {
+    /// Commands queued or being reduced.
+    pub queue_depth: usize,
+    /// Commands accepted into the store's queues.
+    pub dispatched: u64,
+    /// Commands the reducer task has finished with.
+    pub processed: u64,
+    /// How long the reducer took per command.
+    pub reduce_latency: LatencyHistogram,
+}
+
+/// A histogram of durations, in buckets bounded by powers of ten from one
+/// microsecond to one second.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    counts: [u64; BOUNDS.len() + 1],
+    total: Duration,
+}
+
+impl LatencyHistogram {
+    /// The number of durations recorded.
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// The sum of the durations recorded.
+    pub fn total(&self) -> Duration {
+        self.total
+    }
+
+    /// The mean duration, or zero if none was recorded.
+    pub fn mean(&self) -> Duration {
+        match self.count() {
+            0 => Duration::ZERO,
+            count => self.total.div_f64(count as f64),
+        }
+    }
+
+    /// Each bucket's inclusive upper bound and count, fastest first. The last
+    /// bucket is bounded by `Duration::MAX`.
+    pub fn buckets(&self) -> impl Iterator<Item = (Duration, u64)> + '_ {
+        BOUNDS
+            .iter()
+            .copied()
+            .chain([Duration::MAX])
+            .zip(self.counts.iter().copied())
+    }
+}
+
+/// The counters behind [`StoreMetrics`], updated by the store's queues and
+/// reducer task.
+#[derive(Default)]
+pub(crate) struct Counters {
+    dispatched: AtomicU64,
+    processed: AtomicU64,
+    latency: [AtomicU64; BOUNDS.len() + 1],
+    latency_nanos: AtomicU64,
+}
+
+impl Counters {
+    pub(crate) fn dispatched(&self, store: &str) {
+        self.dispatched.fetch_add(1, Ordering::Relaxed);
+        export::dispatched(store);
+    }
+
+    pub(crate) fn processed(&self, store: &str, commands: usize, queue_depth: usize) {
+        self.processed.fetch_add(commands as u64, Ordering::Relaxed);
+        export::processed(store, commands, queue_depth);
+    }
+
+    pub(crate) fn reduced(&self, store: &str, latency: Duration) {
+        let bucket = BOUNDS.partition_point(|bound| *bound < latency);
+        self.latency[bucket].fetch_add(1, Ordering::Relaxed);
+        let nanos = u64::try_from(latency.as_nanos()).unwrap_or(u64::MAX);
+        self.latency_nanos.fetch_add(nanos, Ordering::Relaxed);
+        export::reduced(store, latency);
+    }
+
+    pub(crate) fn snapshot(&self, queue_depth: usize) -> StoreMetrics {
+        StoreMetrics {
+            queue_depth,
+            dispatched: self.dispatched.load(Ordering::Relaxed),
+            processed: self.processed.load(Ordering::Relaxed),
+            reduce_latency: LatencyHistogram {
+                counts: std::array::from_fn(|i| self.latency[i].load(Ordering::Relaxed)),
+                total: Duration::from_nanos(self.latency_nanos.load(Ordering::Relaxed)),
+            },
+        }
+    }
+}
+
+#[cfg(feature = "metrics")]
+mod export {
+    use std::time::Duration;
+
+    pub(super) fn dispatched(store: &str) {
+        metrics::counter!("uniflow_dispatched_total", "store" => store.to_owned()).increment(1);
+    }
+
+    pub(super) fn processed(store: &str, commands: usize, queue_depth: usize) {
+        metrics::counter!("uniflow_processed_total", "store" => store.to_owned())
+            .increment(commands as u64);
+        metrics::gauge!("uniflow_queue_depth", "store" => store.to_owned()).set(queue_depth as f64);
+    }
+
+    pub(super) fn reduced(store: &str, latency: Duration) {
+        metrics::histogram!("uniflow_reduce_seconds", "store" => store.to_owned())
+            .record(latency.as_secs_f64());
+    }
+}
+
+#[cfg(not(feature = "metrics"))]
+mod export {
+    use std::time::Duration;
+
+    pub(super) fn dispatched(_store: &str) {}
+
+    pub(super) fn processed(_store: &str, _commands: usize, _queue_depth: usize) {}
+
+    pub(super) fn reduced(_store: &str, _latency: Duration) {}
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latencies_fall_into_inclusive_buckets() {
+        let counters = Counters::default();
+        for micros in [1, 2, 10, 5_000, 2_000_000] {
+            counters.reduced("store", Duration::from_micros(micros));
+        }
+        let histogram = counters.snapshot(0).reduce_latency;
+        let counts: Vec<_> = histogram.buckets().map(|(_, count)| count).collect();
+        assert_eq!(counts, [1, 2, 0, 0, 1, 0, 0, 1]);
+        assert_eq!(histogram.count(), 5);
+        assert_eq!(histogram.total(), Duration::from_micros(2_005_013));
+    }
+}
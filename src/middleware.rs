@@ -0,0 +1,124 @@
+use crate::{Action, Deps, Effect, EffectReducer, Value};
+
+/// Intercepts actions on their way to the reducer.
+///
+/// Attach middlewares with [`StoreBuilder::middleware`](crate::StoreBuilder::middleware).
+/// A middleware can observe an action, replace it, or swallow it by returning
+/// `None` from [`before`](Middleware::before); swallowed actions never reach
+/// the reducer and leave the state untouched. [`after`](Middleware::after)
+/// observes the state transition produced by the reducer.
+pub trait Middleware<S, A>: Send + 'static {
+    fn before(&self, state: &S, action: A) -> Option<A> {
+        let _ = state;
+        Some(action)
+    }
+
+    fn after(&self, previous: &S, next: &S) {
+        let _ = (previous, next);
+    }
+}
+
+/// Wraps `inner` so that every action passes through `middleware` first.
+pub(crate) fn apply<S, A, D, R, M>(middleware: M, inner: R) -> impl EffectReducer<S, A, D>
+where
+    S: Value,
+    A: Action,
+    D: Deps,
+    R: EffectReducer<S, A, D>,
+    M: Middleware<S, A>,
+{
+    move |state: S, action: A| -> (S, Effect<A, D>) {
+        let Some(action) = middleware.before(&state, action) else {
+            return (state, Effect::none());
+        };
+        let previous = state.clone();
+        let (next, effect) = inner(state, action);
+        middleware.after(&previous, &next);
+        (next, effect)
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{init as init_executor, tick};
+    use crate::{Dispatch, Read, Store};
+    use std::sync::{Arc, Mutex};
+
+    fn add(state: i32, n: i32) -> i32 {
+        state + n
+    }
+
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl Middleware<i32, i32> for Recorder {
+        fn before(&self, state: &i32, action: i32) -> Option<i32> {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("before {state} {action}"));
+            Some(action)
+        }
+
+        fn after(&self, previous: &i32, next: &i32) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("after {previous} {next}"));
+        }
+    }
+
+    struct DropNegative;
+
+    impl Middleware<i32, i32> for DropNegative {
+        fn before(&self, _: &i32, action: i32) -> Option<i32> {
+            (action >= 0).then_some(action)
+        }
+    }
+
+    struct Double;
+
+    impl Middleware<i32, i32> for Double {
+        fn before(&self, _: &i32, action: i32) -> Option<i32> {
+            Some(action * 2)
+        }
+    }
+
+    #[test]
+    fn middleware_observes_action_and_transition() {
+        init_executor();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let store = Store::builder(1, add)
+            .middleware(Recorder(log.clone()))
+            .build();
+        store.dispatch(2);
+        tick();
+        assert_eq!(*log.lock().unwrap(), vec!["before 1 2", "after 1 3"]);
+    }
+
+    #[test]
+    fn middleware_can_swallow_actions() {
+        init_executor();
+        let store = Store::builder(0, add).middleware(DropNegative).build();
+        store.dispatch(-5);
+        store.dispatch(3);
+        tick();
+        assert_eq!(store.get(), 3);
+    }
+
+    #[test]
+    fn last_attached_middleware_is_outermost() {
+        init_executor();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let store = Store::builder(0, add)
+            .middleware(Double)
+            .middleware(Recorder(log.clone()))
+            .build();
+        store.dispatch(2);
+        tick();
+        assert_eq!(store.get(), 4);
+        assert_eq!(*log.lock().unwrap(), vec!["before 0 2", "after 0 4"]);
+    }
+}
@@ -1,4 +1,6 @@
 use crate::Value;
+use crate::region::Changed;
+use std::panic::{AssertUnwindSafe, catch_unwind, resume_unwind};
 use std::sync::{Arc, Mutex, Weak};
 
 // ── Core traits ───────────────────────────────────────────────────────────────
@@ -51,9 +53,15 @@ impl_merge_sources!((A, 0), (B, 1), (C, 2), (D, 3), (E, 4));
 // ── SourceNode ────────────────────────────────────────────────────────────────
 
 struct SourceNodeInner<T> {
-    value: T,
+    /// Shared so that notifications hand out the value without cloning it.
+    value: Arc<T>,
+    changed: Changed,
+    /// Depth of nested [`SourceNode::batch`] calls; updates are not
+    /// propagated while non-zero.
+    batch_depth: usize,
     needs_notify: bool,
     watchers: Vec<WatchSlot<T>>,
+    arc_watchers: Vec<WatchSlot<Arc<T>>>,
     children: Vec<Weak<dyn Propagate>>,
 }
 
@@ -65,9 +73,12 @@ impl<T: Value> SourceNode<T> {
     pub(crate) fn new(value: T) -> Arc<Self> {
         Arc::new(SourceNode {
             inner: Mutex::new(SourceNodeInner {
-                value,
+                value: Arc::new(value),
+                changed: Changed::NONE,
+                batch_depth: 0,
                 needs_notify: false,
                 watchers: Vec::new(),
+                arc_watchers: Vec::new(),
                 children: Vec::new(),
             }),
         })
@@ -76,16 +87,127 @@ impl<T: Value> SourceNode<T> {
     pub(crate) fn set(&self, new_value: T) {
         {
             let mut guard = self.inner.lock().unwrap();
-            if guard.value == new_value {
+            if *guard.value == new_value {
                 return;
             }
-            guard.value = new_value;
+            guard.value = Arc::new(new_value);
+            guard.changed = Changed::ALL;
+            guard.needs_notify = true;
+            if guard.batch_depth > 0 {
+                return;
+            }
+        }
+        self.send_down();
+        self.notify();
+    }
+
+    /// Mutates the value in place. `f` reports which regions it changed; the
+    /// value is not compared, and nothing is propagated if `f` reports no change.
+    ///
+    /// If `f` panics, nothing is propagated before the panic resumes. When a
+    /// snapshot of the value is held elsewhere, as a supervised store holds
+    /// one, `f` mutates a copy and the value is restored; an unshared value
+    /// is kept as `f` left it, as restoring it would mean copying it on
+    /// every call.
+    pub(crate) fn modify(&self, f: impl FnOnce(&mut T) -> Changed) {
+        // The panic is caught before the guard is dropped, so the lock is
+        // released without being poisoned.
+        let result = {
+            let mut guard = self.inner.lock().unwrap();
+            // Free to keep: `make_mut` copies a shared value anyway.
+            let before = (Arc::strong_count(&guard.value) > 1).then(|| guard.value.clone());
+            let result = catch_unwind(AssertUnwindSafe(|| f(Arc::make_mut(&mut guard.value))));
+            if let (Err(_), Some(before)) = (&result, before) {
+                guard.value = before;
+            }
+            result
+        };
+        match result {
+            Ok(changed) => self.mark_changed(changed),
+            Err(payload) => resume_unwind(payload),
+        }
+    }
+
+    fn mark_changed(&self, changed: Changed) {
+        {
+            let mut guard = self.inner.lock().unwrap();
+            if changed.is_empty() {
+                return;
+            }
+            if guard.batch_depth > 0 {
+                guard.changed |= changed;
+                guard.needs_notify = true;
+                return;
+            }
+            guard.changed = changed;
             guard.needs_notify = true;
         }
         self.send_down();
         self.notify();
     }
 
+    /// Puts back a previously shared value, propagating it as a change to
+    /// every region.
+    pub(crate) fn restore(&self, value: Arc<T>) {
+        {
+            let mut guard = self.inner.lock().unwrap();
+            if Arc::ptr_eq(&guard.value, &value) {
+                return;
+            }
+            guard.value = value;
+        }
+        self.mark_changed(Changed::ALL);
+    }
+
+    /// Runs `f`, deferring propagation of every update it makes until it
+    /// returns; children and watchers then see the final value once.
+    pub(crate) fn batch<R>(&self, f: impl FnOnce() -> R) -> R {
+        {
+            let mut guard = self.inner.lock().unwrap();
+            if guard.batch_depth == 0 {
+                guard.changed = Changed::NONE;
+            }
+            guard.batch_depth += 1;
+        }
+        let result = catch_unwind(AssertUnwindSafe(f));
+        let flush = {
+            let mut guard = self.inner.lock().unwrap();
+            guard.batch_depth -= 1;
+            guard.batch_depth == 0 && guard.needs_notify
+        };
+        if flush {
+            self.send_down();
+            self.notify();
+        }
+        result.unwrap_or_else(|payload| resume_unwind(payload))
+    }
+
+    /// Calls `f` with a reference to the current value, without cloning it.
+    ///
+    /// The node is locked while `f` runs, so `f` must not access this node.
+    pub(crate) fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        let result = {
+            let guard = self.inner.lock().unwrap();
+            catch_unwind(AssertUnwindSafe(|| f(&guard.value)))
+        };
+        result.unwrap_or_else(|payload| resume_unwind(payload))
+    }
+
+    /// Returns the shared value: a pointer copy rather than a clone.
+    pub(crate) fn get_arc(&self) -> Arc<T> {
+        self.inner.lock().unwrap().value.clone()
+    }
+
+    /// Registers a watcher which receives the shared value itself.
+    pub(crate) fn add_arc_watcher(&self, slot: WatchSlot<Arc<T>>) {
+        self.inner.lock().unwrap().arc_watchers.push(slot);
+    }
+
+    /// The regions touched by the most recent update.
+    pub(crate) fn last_changed(&self) -> Changed {
+        self.inner.lock().unwrap().changed
+    }
+
     pub(crate) fn send_down(&self) {
         let children = self.inner.lock().unwrap().children.clone();
         for weak in &children {
@@ -96,21 +218,30 @@ impl<T: Value> SourceNode<T> {
     }
 
     pub(crate) fn notify(&self) {
-        let (cbs, v, children) = {
+        let (cbs, arc_cbs, v, children) = {
             let mut guard = self.inner.lock().unwrap();
             if !guard.needs_notify {
                 return;
             }
             guard.needs_notify = false;
             guard.watchers.retain(|s| s.alive.upgrade().is_some());
+            guard.arc_watchers.retain(|s| s.alive.upgrade().is_some());
             let cbs: Vec<_> = guard.watchers.iter().map(|s| s.callback.clone()).collect();
+            let arc_cbs: Vec<_> = guard
+                .arc_watchers
+                .iter()
+                .map(|s| s.callback.clone())
+                .collect();
             let v = guard.value.clone();
             let children = guard.children.clone();
-            (cbs, v, children)
+            (cbs, arc_cbs, v, children)
         };
         for cb in &cbs {
             cb(&v);
         }
+        for cb in &arc_cbs {
+            cb(&v);
+        }
         for weak in &children {
             if let Some(child) = weak.upgrade() {
                 child.notify();
@@ -121,7 +252,7 @@ impl<T: Value> SourceNode<T> {
 
 impl<T: Value> ReadableNode<T> for SourceNode<T> {
     fn get(&self) -> T {
-        self.inner.lock().unwrap().value.clone()
+        T::clone(&self.inner.lock().unwrap().value)
     }
 
     fn add_watcher(&self, slot: WatchSlot<T>) {
@@ -345,6 +476,140 @@ impl<T: MergeSources> ReadableNode<T> for MergeNode<T> {
     }
 }
 
+// ── RegionNode ────────────────────────────────────────────────────────────────
+
+struct RegionNodeInner<T> {
+    cached: T,
+    needs_notify: bool,
+    watchers: Vec<WatchSlot<T>>,
+    children: Vec<Weak<dyn Propagate>>,
+}
+
+type Equality<T> = Box<dyn Fn(&T, &T) -> bool + Send + Sync>;
+
+/// A projection of a [`SourceNode`] which only re-evaluates its selector when
+/// the source reports a change to one of the node's regions.
+pub(crate) struct RegionNode<S, T>
+where
+    S: Value,
+    T: Value,
+{
+    source: Arc<SourceNode<S>>,
+    regions: Changed,
+    selector: Arc<dyn Fn(&S) -> T + Send + Sync>,
+    /// Decides whether a re-evaluated value is unchanged.
+    eq: Equality<T>,
+    inner: Mutex<RegionNodeInner<T>>,
+}
+
+impl<S, T> RegionNode<S, T>
+where
+    S: Value,
+    T: Value,
+{
+    pub(crate) fn new(
+        source: Arc<SourceNode<S>>,
+        regions: Changed,
+        selector: impl Fn(&S) -> T + Send + Sync + 'static,
+    ) -> Arc<Self> {
+        Self::new_with_eq(source, regions, selector, T::eq)
+    }
+
+    /// Like [`new`](RegionNode::new), but deduplicates values with `eq`
+    /// instead of `PartialEq`.
+    pub(crate) fn new_with_eq(
+        source: Arc<SourceNode<S>>,
+        regions: Changed,
+        selector: impl Fn(&S) -> T + Send + Sync + 'static,
+        eq: impl Fn(&T, &T) -> bool + Send + Sync + 'static,
+    ) -> Arc<Self> {
+        let initial = source.with(&selector);
+        let node = Arc::new(RegionNode {
+            source: source.clone(),
+            regions,
+            selector: Arc::new(selector),
+            eq: Box::new(eq),
+            inner: Mutex::new(RegionNodeInner {
+                cached: initial,
+                needs_notify: false,
+                watchers: Vec::new(),
+                children: Vec::new(),
+            }),
+        });
+        let arc_prop: Arc<dyn Propagate> = node.clone();
+        source.add_child(Arc::downgrade(&arc_prop));
+        node
+    }
+}
+
+impl<S, T> Propagate for RegionNode<S, T>
+where
+    S: Value,
+    T: Value,
+{
+    fn send_down(&self) {
+        if !self.source.last_changed().intersects(self.regions) {
+            return;
+        }
+        let new_value = self.source.with(|s| (self.selector)(s));
+        let children = {
+            let mut guard = self.inner.lock().unwrap();
+            if (self.eq)(&guard.cached, &new_value) {
+                return;
+            }
+            guard.cached = new_value;
+            guard.needs_notify = true;
+            guard.children.clone()
+        };
+        for weak in &children {
+            if let Some(child) = weak.upgrade() {
+                child.send_down();
+            }
+        }
+    }
+
+    fn notify(&self) {
+        let (cbs, v, children) = {
+            let mut guard = self.inner.lock().unwrap();
+            if !guard.needs_notify {
+                return;
+            }
+            guard.needs_notify = false;
+            guard.watchers.retain(|s| s.alive.upgrade().is_some());
+            let cbs: Vec<_> = guard.watchers.iter().map(|s| s.callback.clone()).collect();
+            let v = guard.cached.clone();
+            let children = guard.children.clone();
+            (cbs, v, children)
+        };
+        for cb in &cbs {
+            cb(&v);
+        }
+        for weak in &children {
+            if let Some(child) = weak.upgrade() {
+                child.notify();
+            }
+        }
+    }
+}
+
+impl<S, T> ReadableNode<T> for RegionNode<S, T>
+where
+    S: Value,
+    T: Value,
+{
+    fn get(&self) -> T {
+        self.inner.lock().unwrap().cached.clone()
+    }
+
+    fn add_watcher(&self, slot: WatchSlot<T>) {
+        self.inner.lock().unwrap().watchers.push(slot);
+    }
+
+    fn add_child(&self, child: Weak<dyn Propagate>) {
+        self.inner.lock().unwrap().children.push(child);
+    }
+}
+
 #[cfg(test)]
 pub(crate) fn merge_2<A: Value, B: Value>(
     a: Arc<dyn ReadableNode<A>>,
@@ -358,11 +623,11 @@ pub(crate) fn merge_2<A: Value, B: Value>(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::subscription::Subscription;
+    use crate::subscription::Connection;
     use std::sync::{Arc, Mutex};
 
-    fn make_slot<T: Value>(received: Arc<Mutex<Vec<T>>>) -> (WatchSlot<T>, Subscription) {
-        let (sub, weak) = Subscription::new();
+    fn make_slot<T: Value>(received: Arc<Mutex<Vec<T>>>) -> (WatchSlot<T>, Connection) {
+        let (sub, weak) = Connection::new();
         let slot = WatchSlot {
             alive: weak,
             callback: Arc::new(move |v: &T| received.lock().unwrap().push(v.clone())),
@@ -385,6 +650,24 @@ mod tests {
         assert_eq!(node.get(), 100);
     }
 
+    #[test]
+    fn source_node_modify_restores_a_shared_value_after_a_panic() {
+        let node = SourceNode::new(vec![1]);
+        let calls = Arc::new(Mutex::new(vec![]));
+        let (slot, _sub) = make_slot(calls.clone());
+        node.add_watcher(slot);
+        let snapshot = node.get_arc();
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            node.modify(|v| {
+                v.push(2);
+                panic!("half way");
+            })
+        }));
+        assert!(result.is_err());
+        assert!(Arc::ptr_eq(&node.get_arc(), &snapshot));
+        assert!(calls.lock().unwrap().is_empty());
+    }
+
     #[test]
     fn source_node_set_equal_value_is_noop() {
         let node = SourceNode::new(42i32);
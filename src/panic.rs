@@ -0,0 +1,70 @@
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
+/// Where a caught [`Panic`] was raised.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PanicOrigin {
+    /// While reducing an action.
+    Reducer,
+    /// Inside an effect.
+    Effect,
+    /// Inside a callback observing the reduced actions, such as an
+    /// [`inspect`](crate::Store::inspect) callback or the reducer of an
+    /// injected slice. The callback is removed from the store.
+    Tap,
+}
+
+/// A panic caught by a store.
+///
+/// Panics raised by the reducer, by effects or by the callbacks observing the
+/// reduced actions do not stop the store: the action, effect or callback is
+/// abandoned, the panic is reported to the hook installed
+/// with [`StoreBuilder::on_panic`](crate::StoreBuilder::on_panic), and later
+/// dispatches are reduced as usual.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Panic {
+    origin: PanicOrigin,
+    message: String,
+}
+
+impl Panic {
+    pub(crate) fn new(origin: PanicOrigin, payload: Box<dyn Any + Send>) -> Self {
+        let message = match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(payload) => match payload.downcast::<&'static str>() {
+                Ok(message) => (*message).to_owned(),
+                Err(_) => "Box<dyn Any>".to_owned(),
+            },
+        };
+        Panic { origin, message }
+    }
+
+    pub fn origin(&self) -> PanicOrigin {
+        self.origin
+    }
+
+    /// The panic message, if the panic was raised with one.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Panic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.origin {
+            PanicOrigin::Reducer => write!(f, "reducer panicked: {}", self.message),
+            PanicOrigin::Effect => write!(f, "effect panicked: {}", self.message),
+            PanicOrigin::Tap => write!(f, "tap panicked: {}", self.message),
+        }
+    }
+}
+
+pub(crate) type PanicHook = Arc<dyn Fn(&Panic) + Send + Sync>;
+
+/// Passes `panic` to `hook`, if one is installed.
+pub(crate) fn report(hook: Option<&PanicHook>, panic: Panic) {
+    if let Some(hook) = hook {
+        hook(&panic);
+    }
+}
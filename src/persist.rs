@@ -0,0 +1,655 @@
+//! Persisting store state across runs.
+//!
+//! A [`Persist`] describes which slices of the state are saved, and to which
+//! [`Storage`]. Attach it with [`StoreBuilder::persist`](crate::StoreBuilder::persist):
+//! the initial state is rehydrated from storage, and every slice is saved again
+//! whenever an action changes it.
+//!
+//! ```
+//! use uniflow::persist::{MemoryStorage, Persist};
+//! use uniflow::{Dispatch, Read, Store};
+//!
+//! #[derive(Clone, PartialEq)]
+//! struct App {
+//!     volume: u8,
+//!     playing: bool,
+//! }
+//!
+//! uniflow::manual_spawner::init().expect("init");
+//!
+//! let storage = MemoryStorage::new();
+//! let persist = || {
+//!     Persist::new(storage.clone()).slice("volume", |s: &App| &s.volume, |s, v| s.volume = v)
+//! };
+//! let reducer = |s: App, v: u8| App { volume: v, ..s };
+//! let initial = App { volume: 5, playing: false };
+//!
+//! let store = Store::builder(initial.clone(), reducer).persist(persist()).build();
+//! store.dispatch(9);
+//! uniflow::manual_spawner::step();
+//!
+//! let restarted = Store::builder(initial, reducer).persist(persist()).build();
+//! assert_eq!(restarted.get().volume, 9);
+//! ```
+
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as Json;
+
+use crate::node::SourceNode;
+use crate::{Middleware, Value};
+
+#[cfg(feature = "encryption")]
+mod encrypted;
+#[cfg(feature = "sled")]
+mod sled;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(all(feature = "web-storage", target_arch = "wasm32"))]
+mod web;
+#[cfg(feature = "encryption")]
+pub use encrypted::{Encrypted, KeyProvider};
+#[cfg(feature = "sled")]
+pub use sled::SledStorage;
+#[cfg(feature = "sqlite")]
+pub use sqlite::{Sqlite, SqliteEventLog, SqliteStorage};
+#[cfg(all(feature = "web-storage", target_arch = "wasm32"))]
+pub use web::{IndexedDbStorage, LocalStorage};
+
+// ── Storage ───────────────────────────────────────────────────────────────────
+
+/// A key-value store for serialized state.
+pub trait Storage: Send + Sync + 'static {
+    /// Returns the bytes saved under `key`, or `Ok(None)` if there are none.
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>>;
+
+    fn save(&self, key: &str, value: &[u8]) -> io::Result<()>;
+}
+
+/// A [`Storage`] kept in memory. Clones share the same entries.
+#[derive(Clone, Default)]
+pub struct MemoryStorage {
+    entries: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.entries.lock().unwrap().get(key).cloned())
+    }
+
+    fn save(&self, key: &str, value: &[u8]) -> io::Result<()> {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(key.to_owned(), value.to_vec());
+        Ok(())
+    }
+}
+
+/// A [`Storage`] writing one `<key>.json` file per key into a directory.
+#[derive(Clone)]
+pub struct FileStorage {
+    dir: PathBuf,
+}
+
+impl FileStorage {
+    /// Uses `dir`, which is created on the first save if missing.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        FileStorage { dir: dir.into() }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+}
+
+impl Storage for FileStorage {
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        match std::fs::read(self.path(key)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn save(&self, key: &str, value: &[u8]) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        // Write to a temporary file first so a crash never leaves a
+        // truncated snapshot behind.
+        let path = self.path(key);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, value)?;
+        std::fs::rename(tmp, path)
+    }
+}
+
+// ── Migrations ────────────────────────────────────────────────────────────────
+
+/// Upgrades a persisted snapshot by one version.
+pub trait Migration: Send + Sync + 'static {
+    fn migrate(&self, value: Json) -> io::Result<Json>;
+}
+
+struct FnMigration<F>(F);
+
+impl<F: Fn(Json) -> io::Result<Json> + Send + Sync + 'static> Migration for FnMigration<F> {
+    fn migrate(&self, value: Json) -> io::Result<Json> {
+        (self.0)(value)
+    }
+}
+
+/// An ordered list of [`Migration`]s.
+///
+/// The `n`th migration upgrades a snapshot from version `n` to version
+/// `n + 1`, so the current version is the number of migrations.
+///
+/// ```
+/// use serde_json::json;
+/// use uniflow::persist::Migrator;
+///
+/// // Version 1 renamed `name` to `title`.
+/// let migrator = Migrator::new().step(|mut v| {
+///     v["title"] = v["name"].take();
+///     Ok(v)
+/// });
+/// assert_eq!(migrator.version(), 1);
+/// assert_eq!(
+///     migrator.migrate(0, json!({ "name": "a" })).unwrap(),
+///     json!({ "name": null, "title": "a" })
+/// );
+/// ```
+#[derive(Default)]
+pub struct Migrator {
+    migrations: Vec<Box<dyn Migration>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Versioned<T> {
+    version: u32,
+    data: T,
+}
+
+impl Migrator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `migration` as the upgrade from the current version.
+    pub fn migration(mut self, migration: impl Migration) -> Self {
+        self.migrations.push(Box::new(migration));
+        self
+    }
+
+    /// Appends a closure as the upgrade from the current version.
+    pub fn step(self, f: impl Fn(Json) -> io::Result<Json> + Send + Sync + 'static) -> Self {
+        self.migration(FnMigration(f))
+    }
+
+    pub fn version(&self) -> u32 {
+        self.migrations.len() as u32
+    }
+
+    /// Upgrades `value`, saved at `version`, to the current version.
+    ///
+    /// Fails with [`io::ErrorKind::InvalidData`] if `version` is newer than
+    /// the current version.
+    pub fn migrate(&self, version: u32, mut value: Json) -> io::Result<Json> {
+        if version > self.version() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "snapshot version {version} is newer than {}",
+                    self.version()
+                ),
+            ));
+        }
+        for migration in &self.migrations[version as usize..] {
+            value = migration.migrate(value)?;
+        }
+        Ok(value)
+    }
+
+    fn upgrade<T: DeserializeOwned>(&self, saved: Json) -> io::Result<T> {
+        let (version, data) = match serde_json::from_value::<Versioned<Json>>(saved.clone()) {
+            Ok(v) => (v.version, v.data),
+            Err(_) => (0, saved),
+        };
+        Ok(serde_json::from_value(self.migrate(version, data)?)?)
+    }
+}
+
+// ── Rehydration ───────────────────────────────────────────────────────────────
+
+/// Whether a store's persisted slices have been restored. See
+/// [`Store::rehydration`](crate::Store::rehydration).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Rehydration {
+    /// Storage has not been read yet: the store holds its initial state.
+    Pending,
+    /// Every slice found in storage has been restored, or failed to load.
+    Done(Result<(), RehydrationError>),
+}
+
+/// The keys of the slices which failed to load, in order. The errors were
+/// passed to [`Persist::on_error`]; the slices kept their initial values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RehydrationError {
+    pub keys: Vec<String>,
+}
+
+impl std::fmt::Display for RehydrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failed to rehydrate {}", self.keys.join(", "))
+    }
+}
+
+impl std::error::Error for RehydrationError {}
+
+// ── Persist ───────────────────────────────────────────────────────────────────
+
+type ErrorHandler = Arc<dyn Fn(&str, io::Error) + Send + Sync>;
+type Changed<S> = Box<dyn Fn(&S, &S) -> bool + Send + Sync>;
+type Save<S> = Box<dyn Fn(&S) -> io::Result<Vec<u8>> + Send + Sync>;
+type Restore<S> = Box<dyn Fn(&mut S, &[u8]) -> io::Result<()> + Send + Sync>;
+
+struct Slice<S> {
+    key: String,
+    changed: Changed<S>,
+    save: Save<S>,
+    restore: Restore<S>,
+}
+
+/// Saves selected slices of the state to a [`Storage`] and restores them.
+///
+/// Slices are serialized as JSON. Errors are passed to the handler set with
+/// [`on_error`](Persist::on_error) and otherwise ignored: a slice which fails
+/// to load keeps its initial value.
+pub struct Persist<S> {
+    storage: Arc<dyn Storage>,
+    slices: Vec<Slice<S>>,
+    on_error: Option<ErrorHandler>,
+    deferred: bool,
+    status: Arc<SourceNode<Rehydration>>,
+}
+
+impl<S: Value> Persist<S> {
+    pub fn new(storage: impl Storage) -> Self {
+        Persist {
+            storage: Arc::new(storage),
+            slices: Vec::new(),
+            on_error: None,
+            deferred: false,
+            status: SourceNode::new(Rehydration::Pending),
+        }
+    }
+
+    /// Persists the slice selected by `get` under `key`; `set` writes a
+    /// restored value back into the state.
+    pub fn slice<T, G, F>(self, key: impl Into<String>, get: G, set: F) -> Self
+    where
+        T: Serialize + DeserializeOwned + PartialEq,
+        G: Fn(&S) -> &T + Send + Sync + 'static,
+        F: Fn(&mut S, T) + Send + Sync + 'static,
+    {
+        self.push_slice(key.into(), None, get, set)
+    }
+
+    /// Like [`slice`](Persist::slice), but saves the slice together with the
+    /// [`version`](Migrator::version) of `migrator`, and upgrades snapshots
+    /// saved by older versions through its migrations before restoring them.
+    ///
+    /// Snapshots saved without a version, e.g. by [`slice`](Persist::slice),
+    /// are treated as version `0`.
+    pub fn versioned_slice<T, G, F>(
+        self,
+        key: impl Into<String>,
+        migrator: Migrator,
+        get: G,
+        set: F,
+    ) -> Self
+    where
+        T: Serialize + DeserializeOwned + PartialEq,
+        G: Fn(&S) -> &T + Send + Sync + 'static,
+        F: Fn(&mut S, T) + Send + Sync + 'static,
+    {
+        self.push_slice(key.into(), Some(migrator), get, set)
+    }
+
+    fn push_slice<T, G, F>(
+        mut self,
+        key: String,
+        migrator: Option<Migrator>,
+        get: G,
+        set: F,
+    ) -> Self
+    where
+        T: Serialize + DeserializeOwned + PartialEq,
+        G: Fn(&S) -> &T + Send + Sync + 'static,
+        F: Fn(&mut S, T) + Send + Sync + 'static,
+    {
+        let get = Arc::new(get);
+        let g = get.clone();
+        let migrator = migrator.map(Arc::new);
+        let m = migrator.clone();
+        self.slices.push(Slice {
+            key,
+            changed: Box::new(move |previous, next| g(previous) != g(next)),
+            save: Box::new(move |state| {
+                let data = get(state);
+                let bytes = match &m {
+                    Some(m) => serde_json::to_vec(&Versioned {
+                        version: m.version(),
+                        data,
+                    }),
+                    None => serde_json::to_vec(data),
+                };
+                bytes.map_err(io::Error::from)
+            }),
+            restore: Box::new(move |state, bytes| {
+                let value = match &migrator {
+                    Some(m) => m.upgrade(serde_json::from_slice(bytes)?)?,
+                    None => serde_json::from_slice(bytes)?,
+                };
+                set(state, value);
+                Ok(())
+            }),
+        });
+        self
+    }
+
+    /// Calls `f` with the slice key whenever loading or saving it fails.
+    pub fn on_error(mut self, f: impl Fn(&str, io::Error) + Send + Sync + 'static) -> Self {
+        self.on_error = Some(Arc::new(f));
+        self
+    }
+
+    /// Restores the slices on the reducer task once the store starts, rather
+    /// than while building it, so that building never waits on storage.
+    ///
+    /// Until then the store holds its initial state and its
+    /// [`rehydration`](crate::Store::rehydration) is
+    /// [`Pending`](Rehydration::Pending); actions dispatched meanwhile are
+    /// reduced after the restore. A [`wrap`](crate::StoreBuilder::wrap)
+    /// attached later restores the slices there and then, as the state it
+    /// produces may no longer hold them.
+    pub fn deferred(mut self) -> Self {
+        self.deferred = true;
+        self
+    }
+
+    pub(crate) fn is_deferred(&self) -> bool {
+        self.deferred
+    }
+
+    pub(crate) fn status(&self) -> Arc<SourceNode<Rehydration>> {
+        self.status.clone()
+    }
+
+    /// Returns `state` with every slice found in storage restored.
+    pub fn rehydrate(&self, mut state: S) -> S {
+        let mut failed = Vec::new();
+        for slice in &self.slices {
+            let result = self.storage.load(&slice.key).and_then(|bytes| match bytes {
+                Some(bytes) => (slice.restore)(&mut state, &bytes),
+                None => Ok(()),
+            });
+            if result.is_err() {
+                failed.push(slice.key.clone());
+            }
+            self.report(&slice.key, result);
+        }
+        let result = if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(RehydrationError { keys: failed })
+        };
+        self.status.set(Rehydration::Done(result));
+        state
+    }
+
+    /// Saves every slice of `state`, whether it changed or not.
+    pub fn save_all(&self, state: &S) {
+        for slice in &self.slices {
+            self.save(slice, state);
+        }
+    }
+
+    fn save(&self, slice: &Slice<S>, state: &S) {
+        let result = (slice.save)(state).and_then(|bytes| self.storage.save(&slice.key, &bytes));
+        self.report(&slice.key, result);
+    }
+
+    fn report(&self, key: &str, result: io::Result<()>) {
+        if let (Err(e), Some(on_error)) = (result, &self.on_error) {
+            on_error(key, e);
+        }
+    }
+}
+
+impl<S: Value, A> Middleware<S, A> for Persist<S> {
+    fn after(&self, previous: &S, next: &S) {
+        for slice in &self.slices {
+            if (slice.changed)(previous, next) {
+                self.save(slice, next);
+            }
+        }
+    }
+}
+
+/// A [`Persist`] shared between its middleware and a deferred rehydration.
+pub(crate) struct Shared<S>(pub(crate) Arc<Persist<S>>);
+
+impl<S: Value, A> Middleware<S, A> for Shared<S> {
+    fn after(&self, previous: &S, next: &S) {
+        Middleware::<S, A>::after(&*self.0, previous, next);
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{init as init_executor, tick};
+    use crate::{Dispatch, Read, Store};
+    use futures::FutureExt;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Prefs {
+        theme: String,
+        zoom: u32,
+        scratch: u32,
+    }
+
+    enum Msg {
+        Theme(&'static str),
+        Zoom(u32),
+        Scratch(u32),
+    }
+
+    fn reduce(s: Prefs, msg: Msg) -> Prefs {
+        match msg {
+            Msg::Theme(theme) => Prefs {
+                theme: theme.into(),
+                ..s
+            },
+            Msg::Zoom(zoom) => Prefs { zoom, ..s },
+            Msg::Scratch(scratch) => Prefs { scratch, ..s },
+        }
+    }
+
+    fn initial() -> Prefs {
+        Prefs {
+            theme: "light".into(),
+            zoom: 100,
+            scratch: 0,
+        }
+    }
+
+    fn persist(storage: impl Storage) -> Persist<Prefs> {
+        Persist::new(storage)
+            .slice("theme", |s: &Prefs| &s.theme, |s, v| s.theme = v)
+            .slice("zoom", |s: &Prefs| &s.zoom, |s, v| s.zoom = v)
+    }
+
+    #[test]
+    fn saves_changed_slices_only() {
+        init_executor();
+        let storage = MemoryStorage::new();
+        let store = Store::builder(initial(), reduce)
+            .persist(persist(storage.clone()))
+            .build();
+        store.dispatch(Msg::Zoom(150));
+        store.dispatch(Msg::Scratch(7));
+        tick();
+        assert_eq!(storage.load("zoom").unwrap(), Some(b"150".to_vec()));
+        assert_eq!(storage.load("theme").unwrap(), None);
+    }
+
+    #[test]
+    fn rehydrates_at_construction() {
+        init_executor();
+        let storage = MemoryStorage::new();
+        let store = Store::builder(initial(), reduce)
+            .persist(persist(storage.clone()))
+            .build();
+        store.dispatch(Msg::Theme("dark"));
+        store.dispatch(Msg::Scratch(7));
+        tick();
+
+        let restarted = Store::builder(initial(), reduce)
+            .persist(persist(storage))
+            .build();
+        assert_eq!(
+            restarted.get(),
+            Prefs {
+                theme: "dark".into(),
+                zoom: 100,
+                scratch: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn deferred_rehydration_is_pending_until_the_store_starts() {
+        init_executor();
+        let storage = MemoryStorage::new();
+        storage.save("theme", b"\"dark\"").unwrap();
+        storage.save("zoom", b"not json").unwrap();
+        let store = Store::builder(initial(), reduce)
+            .persist(persist(storage).deferred())
+            .build();
+        let mut rehydrated = Box::pin(store.rehydrated());
+        assert_eq!(store.rehydration(), Rehydration::Pending);
+        assert_eq!(store.get().theme, "light");
+        assert_eq!((&mut rehydrated).now_or_never(), None);
+
+        // Dispatched before the restore, but reduced after it.
+        store.dispatch(Msg::Scratch(7));
+        tick();
+        let failed = RehydrationError {
+            keys: vec!["zoom".into()],
+        };
+        assert_eq!(rehydrated.now_or_never(), Some(Err(failed.clone())));
+        assert_eq!(store.rehydration(), Rehydration::Done(Err(failed)));
+        assert_eq!((store.get().theme, store.get().scratch), ("dark".into(), 7));
+    }
+
+    #[test]
+    fn corrupt_slice_keeps_initial_value_and_reports() {
+        let storage = MemoryStorage::new();
+        storage.save("zoom", b"not json").unwrap();
+        storage.save("theme", b"\"dark\"").unwrap();
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let e = errors.clone();
+        let state = persist(storage)
+            .on_error(move |key, _| e.lock().unwrap().push(key.to_owned()))
+            .rehydrate(initial());
+        assert_eq!(state.zoom, 100);
+        assert_eq!(state.theme, "dark");
+        assert_eq!(*errors.lock().unwrap(), vec!["zoom"]);
+    }
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    struct Window {
+        width: u32,
+        height: u32,
+    }
+
+    fn window_migrator() -> Migrator {
+        // v1: `size` was split into `width` and `height`.
+        Migrator::new().step(|v| {
+            let size = v["size"].clone();
+            Ok(serde_json::json!({ "width": size, "height": size }))
+        })
+    }
+
+    fn restore_window(storage: MemoryStorage) -> Window {
+        Persist::new(storage)
+            .versioned_slice("window", window_migrator(), |w: &Window| w, |w, v| *w = v)
+            .rehydrate(Window {
+                width: 0,
+                height: 0,
+            })
+    }
+
+    #[test]
+    fn unversioned_snapshot_is_migrated() {
+        let storage = MemoryStorage::new();
+        storage.save("window", br#"{"size":300}"#).unwrap();
+        let window = restore_window(storage);
+        assert_eq!(
+            window,
+            Window {
+                width: 300,
+                height: 300,
+            }
+        );
+    }
+
+    #[test]
+    fn current_snapshot_is_restored_unchanged() {
+        let storage = MemoryStorage::new();
+        let window = Window {
+            width: 640,
+            height: 480,
+        };
+        Persist::new(storage.clone())
+            .versioned_slice("window", window_migrator(), |w: &Window| w, |w, v| *w = v)
+            .save_all(&window);
+        assert_eq!(
+            storage.load("window").unwrap().unwrap(),
+            br#"{"version":1,"data":{"width":640,"height":480}}"#
+        );
+        assert_eq!(restore_window(storage), window);
+    }
+
+    #[test]
+    fn newer_snapshot_is_rejected() {
+        let migrator = window_migrator();
+        let err = migrator.migrate(2, serde_json::json!({})).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn file_storage_round_trips() {
+        let dir = std::env::temp_dir().join(format!("uniflow-persist-{}", std::process::id()));
+        let storage = FileStorage::new(&dir);
+        assert_eq!(storage.load("zoom").unwrap(), None);
+        storage.save("zoom", b"120").unwrap();
+        assert_eq!(storage.load("zoom").unwrap(), Some(b"120".to_vec()));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,143 @@
+use std::io;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+
+use super::Storage;
+
+/// Supplies the 256-bit key an [`Encrypted`] storage seals values with.
+///
+/// The key is asked for on every load and save, so a provider may fetch it
+/// from the platform keychain, or fail while the app is locked. Fixed keys
+/// and closures are providers.
+pub trait KeyProvider: Send + Sync + 'static {
+    fn key(&self) -> io::Result<[u8; 32]>;
+}
+
+impl KeyProvider for [u8; 32] {
+    fn key(&self) -> io::Result<[u8; 32]> {
+        Ok(*self)
+    }
+}
+
+impl<F: Fn() -> io::Result<[u8; 32]> + Send + Sync + 'static> KeyProvider for F {
+    fn key(&self) -> io::Result<[u8; 32]> {
+        self()
+    }
+}
+
+/// A [`Storage`] encrypting values before they reach another (feature
+/// `encryption`).
+///
+/// Values are sealed with XChaCha20-Poly1305 under a fresh random nonce,
+/// and bound to their key, so a value moved to another key fails to load.
+/// What reaches the inner storage is base64 text, which any storage,
+/// `localStorage` included, can keep. A value which was tampered with, or
+/// sealed with another key, fails to load with
+/// [`InvalidData`](io::ErrorKind::InvalidData).
+///
+/// ```
+/// use uniflow::persist::{Encrypted, MemoryStorage, Persist, Storage};
+///
+/// #[derive(Clone, PartialEq)]
+/// struct App {
+///     token: String,
+/// }
+///
+/// let disk = MemoryStorage::new();
+/// let storage = Encrypted::new(disk.clone(), [7; 32]);
+/// storage.save("token", b"\"secret\"")?;
+/// assert_ne!(disk.load("token")?, Some(b"\"secret\"".to_vec()));
+/// assert_eq!(storage.load("token")?, Some(b"\"secret\"".to_vec()));
+///
+/// let persist = Persist::new(storage).slice("token", |s: &App| &s.token, |s, t| s.token = t);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct Encrypted<St, K> {
+    inner: St,
+    keys: K,
+}
+
+impl<St: Storage, K: KeyProvider> Encrypted<St, K> {
+    /// Keeps values in `inner`, sealed with the key `keys` provides.
+    pub fn new(inner: St, keys: K) -> Self {
+        Encrypted { inner, keys }
+    }
+
+    fn cipher(&self) -> io::Result<XChaCha20Poly1305> {
+        Ok(XChaCha20Poly1305::new(&self.keys.key()?.into()))
+    }
+}
+
+impl<St: Storage, K: KeyProvider> Storage for Encrypted<St, K> {
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        let Some(sealed) = self.inner.load(key)? else {
+            return Ok(None);
+        };
+        let invalid = |message| io::Error::new(io::ErrorKind::InvalidData, message);
+        let sealed = BASE64
+            .decode(sealed)
+            .map_err(|_| invalid("encrypted value is not base64"))?;
+        let nonce_len = size_of::<XNonce>();
+        if sealed.len() < nonce_len {
+            return Err(invalid("encrypted value is truncated"));
+        }
+        let (nonce, msg) = sealed.split_at(nonce_len);
+        let payload = Payload {
+            msg,
+            aad: key.as_bytes(),
+        };
+        let value = self
+            .cipher()?
+            .decrypt(XNonce::from_slice(nonce), payload)
+            .map_err(|_| invalid("encrypted value failed authentication"))?;
+        Ok(Some(value))
+    }
+
+    fn save(&self, key: &str, value: &[u8]) -> io::Result<()> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let payload = Payload {
+            msg: value,
+            aad: key.as_bytes(),
+        };
+        let ciphertext = self
+            .cipher()?
+            .encrypt(&nonce, payload)
+            .map_err(|_| io::Error::other("encryption failed"))?;
+        let mut sealed = nonce.to_vec();
+        sealed.extend(ciphertext);
+        self.inner.save(key, BASE64.encode(sealed).as_bytes())
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persist::MemoryStorage;
+
+    #[test]
+    fn values_only_load_with_their_key_and_slot() {
+        let disk = MemoryStorage::new();
+        let storage = Encrypted::new(disk.clone(), [1; 32]);
+        storage.save("token", b"secret").unwrap();
+        storage.save("other", b"public").unwrap();
+        let sealed = disk.load("token").unwrap().unwrap();
+        assert!(!sealed.windows(6).any(|w| w == b"secret"));
+        assert_eq!(storage.load("token").unwrap(), Some(b"secret".to_vec()));
+        assert_eq!(storage.load("missing").unwrap(), None);
+
+        let wrong_key = Encrypted::new(disk.clone(), || Ok([2; 32]));
+        let error = wrong_key.load("token").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // A value copied to another slot is rejected too.
+        disk.save("other", &sealed).unwrap();
+        let error = storage.load("other").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
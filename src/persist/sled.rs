@@ -0,0 +1,103 @@
+use std::io;
+use std::path::Path;
+
+use super::Storage;
+
+/// A [`Storage`] in a [sled](https://docs.rs/sled) tree, keeping each slice
+/// under its key (feature `sled`). Clones share the tree.
+///
+/// Every save is flushed to disk before it returns, as with
+/// [`FileStorage`](super::FileStorage).
+///
+/// ```no_run
+/// use uniflow::persist::{Persist, SledStorage};
+///
+/// #[derive(Clone, PartialEq)]
+/// struct App {
+///     volume: u8,
+/// }
+///
+/// let storage = SledStorage::open("app-state")?;
+/// let persist = Persist::new(storage).slice("volume", |s: &App| &s.volume, |s, v| s.volume = v);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct SledStorage {
+    tree: sled::Tree,
+}
+
+impl SledStorage {
+    /// Opens the database in the directory `path`, creating it if missing,
+    /// and uses its default tree.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let db = sled::open(path).map_err(io::Error::other)?;
+        Ok(SledStorage {
+            tree: (*db).clone(),
+        })
+    }
+
+    /// Uses `tree`, such as a tree of the app's own database set aside for
+    /// its state.
+    pub fn new(tree: sled::Tree) -> Self {
+        SledStorage { tree }
+    }
+}
+
+impl Storage for SledStorage {
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        let value = self.tree.get(key).map_err(io::Error::other)?;
+        Ok(value.map(|value| value.to_vec()))
+    }
+
+    fn save(&self, key: &str, value: &[u8]) -> io::Result<()> {
+        self.tree.insert(key, value).map_err(io::Error::other)?;
+        self.tree.flush().map_err(io::Error::other)?;
+        Ok(())
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{init as init_executor, tick};
+    use crate::persist::Persist;
+    use crate::{Dispatch, Read, Store};
+
+    #[test]
+    fn slices_survive_reopening_the_database() {
+        init_executor();
+        let dir = std::env::temp_dir().join(format!("uniflow-sled-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let build = |storage: SledStorage| {
+            Store::builder((0, 0), |(volume, _): (u8, u8), n: u8| (volume + n, n))
+                .persist(Persist::new(storage).slice("volume", |s: &(u8, u8)| &s.0, |s, v| s.0 = v))
+                .build()
+        };
+
+        let store = build(SledStorage::open(&dir).unwrap());
+        store.dispatch(3);
+        tick();
+        // The reducer task, and the database lock with it, ends once the
+        // store is gone.
+        drop(store);
+        tick();
+
+        // sled's flusher thread may hold the lock a moment longer.
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        let storage = loop {
+            match SledStorage::open(&dir) {
+                Ok(storage) => break storage,
+                Err(_) if std::time::Instant::now() < deadline => {
+                    std::thread::sleep(std::time::Duration::from_millis(10))
+                }
+                Err(e) => panic!("{e}"),
+            }
+        };
+        let store = build(storage);
+        assert_eq!(store.get(), (3, 0));
+        drop(store);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
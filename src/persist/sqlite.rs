@@ -0,0 +1,279 @@
+use std::io;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use super::Storage;
+use crate::EventLog;
+
+/// The crate's tables, one entry per schema version. Databases at an older
+/// version, as recorded in `PRAGMA user_version`, are brought up to date on
+/// opening. Tables are prefixed so that apps can keep their own alongside.
+const SCHEMA: &[&str] = &["
+    CREATE TABLE uniflow_state (
+        key TEXT PRIMARY KEY,
+        value BLOB NOT NULL
+    );
+    CREATE TABLE uniflow_snapshots (
+        log TEXT PRIMARY KEY,
+        state TEXT NOT NULL
+    );
+    CREATE TABLE uniflow_actions (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        log TEXT NOT NULL,
+        action TEXT NOT NULL
+    );
+    CREATE INDEX uniflow_actions_by_log ON uniflow_actions (log, id);
+"];
+
+/// A SQLite database holding persisted state and event logs (feature
+/// `sqlite`). Clones share the connection.
+///
+/// ```
+/// use uniflow::persist::{Persist, Sqlite};
+/// use uniflow::{EventSourcing, Store};
+///
+/// #[derive(Clone, PartialEq)]
+/// struct App {
+///     volume: u8,
+/// }
+///
+/// uniflow::manual_spawner::init().expect("init");
+///
+/// let db = Sqlite::open_in_memory()?;
+/// let counter = Store::builder(0, |s: i32, n: i32| s + n)
+///     .event_sourced(EventSourcing::new(db.event_log::<i32, i32>("counter")))
+///     .build();
+/// let app = Store::builder(App { volume: 5 }, |_, volume: u8| App { volume })
+///     .persist(Persist::new(db.storage()).slice("volume", |s: &App| &s.volume, |s, v| s.volume = v))
+///     .build();
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct Sqlite {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl Sqlite {
+    /// Opens the database at `path`, creating it if missing, and brings the
+    /// crate's tables up to date.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::new(Connection::open(path).map_err(io::Error::other)?)
+    }
+
+    /// Opens a database which only lives as long as the returned handle and
+    /// its clones.
+    pub fn open_in_memory() -> io::Result<Self> {
+        Self::new(Connection::open_in_memory().map_err(io::Error::other)?)
+    }
+
+    /// Uses an open connection, bringing the crate's tables up to date.
+    pub fn new(mut connection: Connection) -> io::Result<Self> {
+        migrate(&mut connection)?;
+        Ok(Sqlite {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    /// A [`Storage`] for [`Persist`](super::Persist), keeping each key in a
+    /// row.
+    pub fn storage(&self) -> SqliteStorage {
+        SqliteStorage { db: self.clone() }
+    }
+
+    /// The [`EventLog`] called `name`. A database holds any number of logs.
+    pub fn event_log<S, A>(&self, name: impl Into<String>) -> SqliteEventLog<S, A> {
+        SqliteEventLog {
+            db: self.clone(),
+            name: name.into(),
+            _types: PhantomData,
+        }
+    }
+
+    fn with<T>(&self, f: impl FnOnce(&mut Connection) -> rusqlite::Result<T>) -> io::Result<T> {
+        f(&mut self.connection.lock().unwrap()).map_err(io::Error::other)
+    }
+}
+
+fn migrate(connection: &mut Connection) -> io::Result<()> {
+    let transaction = connection.transaction().map_err(io::Error::other)?;
+    let version: u32 = transaction
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(io::Error::other)?;
+    let current = SCHEMA.len() as u32;
+    if version > current {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("database schema version {version} is newer than {current}"),
+        ));
+    }
+    let upgrade = || -> rusqlite::Result<()> {
+        for schema in &SCHEMA[version as usize..] {
+            transaction.execute_batch(schema)?;
+        }
+        transaction.pragma_update(None, "user_version", current)
+    };
+    upgrade().map_err(io::Error::other)?;
+    transaction.commit().map_err(io::Error::other)
+}
+
+// ── Storage ───────────────────────────────────────────────────────────────────
+
+/// A [`Storage`] in a [`Sqlite`] database.
+#[derive(Clone)]
+pub struct SqliteStorage {
+    db: Sqlite,
+}
+
+impl Storage for SqliteStorage {
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        self.db.with(|connection| {
+            connection
+                .query_row(
+                    "SELECT value FROM uniflow_state WHERE key = ?1",
+                    [key],
+                    |row| row.get(0),
+                )
+                .optional()
+        })
+    }
+
+    fn save(&self, key: &str, value: &[u8]) -> io::Result<()> {
+        self.db.with(|connection| {
+            connection.execute(
+                "INSERT INTO uniflow_state (key, value) VALUES (?1, ?2)
+                 ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+                params![key, value],
+            )?;
+            Ok(())
+        })
+    }
+}
+
+// ── Event log ─────────────────────────────────────────────────────────────────
+
+/// An [`EventLog`] in a [`Sqlite`] database, with the snapshot and actions
+/// serialized as JSON. Compaction replaces the snapshot and drops the actions
+/// in one transaction.
+pub struct SqliteEventLog<S, A> {
+    db: Sqlite,
+    name: String,
+    _types: PhantomData<fn() -> (S, A)>,
+}
+
+impl<S, A> Clone for SqliteEventLog<S, A> {
+    fn clone(&self) -> Self {
+        SqliteEventLog {
+            db: self.db.clone(),
+            name: self.name.clone(),
+            _types: PhantomData,
+        }
+    }
+}
+
+impl<S, A> EventLog<S, A> for SqliteEventLog<S, A>
+where
+    S: Serialize + DeserializeOwned + 'static,
+    A: Serialize + DeserializeOwned + 'static,
+{
+    fn append(&self, action: &A) -> io::Result<()> {
+        let action = serde_json::to_string(action)?;
+        self.db.with(|connection| {
+            connection.execute(
+                "INSERT INTO uniflow_actions (log, action) VALUES (?1, ?2)",
+                params![self.name, action],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn load(&self) -> io::Result<(Option<S>, Vec<A>)> {
+        let (snapshot, actions) = self.db.with(|connection| {
+            let transaction = connection.transaction()?;
+            let snapshot: Option<String> = transaction
+                .query_row(
+                    "SELECT state FROM uniflow_snapshots WHERE log = ?1",
+                    [&self.name],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let actions = transaction
+                .prepare("SELECT action FROM uniflow_actions WHERE log = ?1 ORDER BY id")?
+                .query_map([&self.name], |row| row.get::<_, String>(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok((snapshot, actions))
+        })?;
+        let snapshot = snapshot.map(|s| serde_json::from_str(&s)).transpose()?;
+        let actions = actions
+            .iter()
+            .map(|a| serde_json::from_str(a))
+            .collect::<Result<_, _>>()?;
+        Ok((snapshot, actions))
+    }
+
+    fn compact(&self, snapshot: &S) -> io::Result<()> {
+        let snapshot = serde_json::to_string(snapshot)?;
+        self.db.with(|connection| {
+            let transaction = connection.transaction()?;
+            transaction.execute(
+                "INSERT INTO uniflow_snapshots (log, state) VALUES (?1, ?2)
+                 ON CONFLICT (log) DO UPDATE SET state = excluded.state",
+                params![self.name, snapshot],
+            )?;
+            transaction.execute("DELETE FROM uniflow_actions WHERE log = ?1", [&self.name])?;
+            transaction.commit()
+        })
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{init as init_executor, tick};
+    use crate::{Dispatch, EventSourcing, Read, Store};
+
+    #[test]
+    fn reopened_databases_keep_state_and_logs() {
+        init_executor();
+        let path = std::env::temp_dir().join(format!("uniflow-sqlite-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let counter = |db: &Sqlite| {
+            Store::builder(0, |s: i32, n: i32| s + n)
+                .event_sourced(EventSourcing::new(db.event_log::<i32, i32>("counter")))
+                .build()
+        };
+
+        let db = Sqlite::open(&path).unwrap();
+        db.storage().save("theme", b"dark").unwrap();
+        let store = counter(&db);
+        store.dispatch(2);
+        store.dispatch(3);
+        tick();
+        drop((store, db));
+
+        let db = Sqlite::open(&path).unwrap();
+        assert_eq!(db.storage().load("theme").unwrap(), Some(b"dark".to_vec()));
+        assert_eq!(counter(&db).get(), 5);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn compaction_replaces_the_snapshot_and_drops_actions() {
+        let db = Sqlite::open_in_memory().unwrap();
+        let log = db.event_log::<i32, i32>("counter");
+        let other = db.event_log::<i32, i32>("other");
+        log.append(&1).unwrap();
+        other.append(&7).unwrap();
+        log.compact(&1).unwrap();
+        log.append(&2).unwrap();
+
+        assert_eq!(log.load().unwrap(), (Some(1), vec![2]));
+        assert_eq!(other.load().unwrap(), (None, vec![7]));
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use futures::StreamExt;
+use futures::channel::mpsc::{UnboundedSender, unbounded};
+use js_sys::{Array, Promise, Uint8Array};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{IdbDatabase, IdbOpenDbRequest, IdbRequest, IdbTransactionMode};
+
+use super::Storage;
+
+// ── localStorage ──────────────────────────────────────────────────────────────
+
+/// A [`Storage`] in the browser's `localStorage`, for small states (feature
+/// `web-storage`, wasm32 only).
+///
+/// Keys are prefixed so that several apps, or several stores, can share the
+/// origin's storage. Values must be UTF-8, as the JSON [`Persist`] saves is.
+///
+/// [`Persist`]: super::Persist
+#[derive(Clone, Default)]
+pub struct LocalStorage {
+    prefix: String,
+}
+
+impl LocalStorage {
+    /// Keeps each key under `prefix` followed by the key.
+    pub fn new(prefix: impl Into<String>) -> Self {
+        LocalStorage {
+            prefix: prefix.into(),
+        }
+    }
+
+    /// The storage is looked up on every call: browser objects cannot be kept
+    /// in a `Send` value.
+    fn storage() -> io::Result<web_sys::Storage> {
+        let window = web_sys::window().ok_or_else(|| io::Error::other("no window"))?;
+        window
+            .local_storage()
+            .map_err(js_error)?
+            .ok_or_else(|| io::Error::other("localStorage is unavailable"))
+    }
+}
+
+impl Storage for LocalStorage {
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        let value = Self::storage()?
+            .get_item(&format!("{}{key}", self.prefix))
+            .map_err(js_error)?;
+        Ok(value.map(String::into_bytes))
+    }
+
+    fn save(&self, key: &str, value: &[u8]) -> io::Result<()> {
+        let value = std::str::from_utf8(value)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Self::storage()?
+            .set_item(&format!("{}{key}", self.prefix), value)
+            .map_err(js_error)
+    }
+}
+
+// ── IndexedDB ─────────────────────────────────────────────────────────────────
+
+const OBJECT_STORE: &str = "uniflow";
+
+/// A [`Storage`] in an IndexedDB database, for states too large for
+/// `localStorage` (feature `web-storage`, wasm32 only).
+///
+/// IndexedDB is asynchronous while [`Storage`] is not, so every entry is
+/// read into memory when the storage is opened, before the store is built,
+/// and saves are written in the background, in order. A failed write is
+/// not reported.
+///
+/// ```no_run
+/// # async fn run() -> std::io::Result<()> {
+/// use uniflow::persist::{IndexedDbStorage, Persist};
+///
+/// #[derive(Clone, PartialEq)]
+/// struct App {
+///     document: String,
+/// }
+///
+/// let storage = IndexedDbStorage::open("my-app").await?;
+/// let store = uniflow::Store::builder(App { document: String::new() }, |_, document| App { document })
+///     .persist(Persist::new(storage).slice("document", |s: &App| &s.document, |s, d| s.document = d))
+///     .build();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct IndexedDbStorage {
+    entries: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+    writes: UnboundedSender<(String, Vec<u8>)>,
+}
+
+impl IndexedDbStorage {
+    /// Opens the database called `name`, creating it if missing, and reads
+    /// its entries.
+    pub async fn open(name: &str) -> io::Result<Self> {
+        let db = open_database(name).await?;
+        let entries = read_entries(&db).await?;
+        let (writes, mut pending) = unbounded::<(String, Vec<u8>)>();
+        wasm_bindgen_futures::spawn_local(async move {
+            while let Some((key, value)) = pending.next().await {
+                let _ = write(&db, &key, &value);
+            }
+        });
+        Ok(IndexedDbStorage {
+            entries: Arc::new(Mutex::new(entries)),
+            writes,
+        })
+    }
+}
+
+impl Storage for IndexedDbStorage {
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.entries.lock().unwrap().get(key).cloned())
+    }
+
+    fn save(&self, key: &str, value: &[u8]) -> io::Result<()> {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(key.to_owned(), value.to_vec());
+        self.writes
+            .unbounded_send((key.to_owned(), value.to_vec()))
+            .map_err(io::Error::other)
+    }
+}
+
+async fn open_database(name: &str) -> io::Result<IdbDatabase> {
+    let factory = web_sys::window()
+        .ok_or_else(|| io::Error::other("no window"))?
+        .indexed_db()
+        .map_err(js_error)?
+        .ok_or_else(|| io::Error::other("IndexedDB is unavailable"))?;
+    let request = factory.open_with_u32(name, 1).map_err(js_error)?;
+    let upgrade = Closure::<dyn FnMut(web_sys::Event)>::new(|event: web_sys::Event| {
+        let request: IdbOpenDbRequest = event.target().expect("target").unchecked_into();
+        let db: IdbDatabase = request.result().expect("database").unchecked_into();
+        let _ = db.create_object_store(OBJECT_STORE);
+    });
+    request.set_onupgradeneeded(Some(upgrade.as_ref().unchecked_ref()));
+    let db = settle(&request).await?;
+    Ok(db.unchecked_into())
+}
+
+async fn read_entries(db: &IdbDatabase) -> io::Result<HashMap<String, Vec<u8>>> {
+    let store = db
+        .transaction_with_str(OBJECT_STORE)
+        .and_then(|transaction| transaction.object_store(OBJECT_STORE))
+        .map_err(js_error)?;
+    let keys = settle(&store.get_all_keys().map_err(js_error)?).await?;
+    let values = settle(&store.get_all().map_err(js_error)?).await?;
+    let (keys, values) = (Array::from(&keys), Array::from(&values));
+    let keys = keys.iter().filter_map(|key| key.as_string());
+    let values = values.iter().map(|value| Uint8Array::new(&value).to_vec());
+    Ok(keys.zip(values).collect())
+}
+
+fn write(db: &IdbDatabase, key: &str, value: &[u8]) -> Result<(), JsValue> {
+    db.transaction_with_str_and_mode(OBJECT_STORE, IdbTransactionMode::Readwrite)?
+        .object_store(OBJECT_STORE)?
+        .put_with_key(&Uint8Array::from(value), &JsValue::from_str(key))?;
+    Ok(())
+}
+
+/// Resolves with the result of `request` once it succeeds.
+async fn settle(request: &IdbRequest) -> io::Result<JsValue> {
+    let done = Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+    });
+    JsFuture::from(done).await.map_err(js_error)?;
+    request.result().map_err(js_error)
+}
+
+fn js_error(error: JsValue) -> io::Error {
+    io::Error::other(format!("{error:?}"))
+}
@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+type Embed<A, B> = Arc<dyn Fn(B) -> A + Send + Sync>;
+type Extract<A, B> = Arc<dyn Fn(A) -> Result<B, A> + Send + Sync>;
+
+/// A typed path from an action `A` to one of its cases `B`: wraps a `B` into
+/// an `A`, and unwraps an `A` which holds a `B`.
+///
+/// The action counterpart of a [`Lens`](crate::Lens). Build one for an enum
+/// variant with [`prism!`](crate::prism).
+pub struct Prism<A, B> {
+    embed: Embed<A, B>,
+    extract: Extract<A, B>,
+}
+
+impl<A: 'static, B: 'static> Prism<A, B> {
+    pub fn new<E, X>(embed: E, extract: X) -> Self
+    where
+        E: Fn(B) -> A + Send + Sync + 'static,
+        X: Fn(A) -> Result<B, A> + Send + Sync + 'static,
+    {
+        Prism {
+            embed: Arc::new(embed),
+            extract: Arc::new(extract),
+        }
+    }
+
+    pub fn embed(&self, action: B) -> A {
+        (self.embed)(action)
+    }
+
+    /// Returns the `B` held by `action`, or hands `action` back if it holds
+    /// another case.
+    pub fn extract(&self, action: A) -> Result<B, A> {
+        (self.extract)(action)
+    }
+}
+
+impl<A, B> Clone for Prism<A, B> {
+    fn clone(&self) -> Self {
+        Prism {
+            embed: self.embed.clone(),
+            extract: self.extract.clone(),
+        }
+    }
+}
+
+/// Builds a [`Prism`] for a single-field tuple variant of an enum.
+///
+/// ```
+/// use uniflow::prism;
+///
+/// enum Action {
+///     Counter(i32),
+///     Reset,
+/// }
+///
+/// let counter = prism!(Action::Counter);
+/// assert!(matches!(counter.embed(2), Action::Counter(2)));
+/// assert!(counter.extract(Action::Reset).is_err());
+/// ```
+#[macro_export]
+macro_rules! prism {
+    ($($variant:ident)::+) => {
+        $crate::Prism::new($($variant)::+, |action| match action {
+            $($variant)::+(inner) => Ok(inner),
+            #[allow(unreachable_patterns)]
+            other => Err(other),
+        })
+    };
+}
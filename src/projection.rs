@@ -0,0 +1,223 @@
+use std::sync::{Arc, Mutex};
+
+use crate::Value;
+use crate::node::{Propagate, ReadableNode, SourceNode, WatchSlot};
+use crate::subscription::{Connections, Subscription};
+
+// ── ProjectionNode ────────────────────────────────────────────────────────────
+
+pub(crate) trait ProjectionSource<R>: Send + Sync {
+    fn with_dyn(&self, f: &mut dyn FnMut(&R));
+    fn add_watcher(&self, slot: WatchSlot<R>);
+}
+
+struct ProjectionNodeInner<R> {
+    cached: Option<Arc<R>>,
+    needs_notify: bool,
+    watchers: Vec<WatchSlot<R>>,
+}
+
+/// A borrowed view into a [`SourceNode`].
+///
+/// The projected value is read in place. A copy is only kept while watchers
+/// are connected, and only refreshed when the projected value actually changes.
+pub(crate) struct ProjectionNode<S: Value, R: Value> {
+    source: Arc<SourceNode<S>>,
+    selector: Box<dyn Fn(&S) -> &R + Send + Sync>,
+    inner: Mutex<ProjectionNodeInner<R>>,
+}
+
+impl<S: Value, R: Value> ProjectionNode<S, R> {
+    pub(crate) fn new(
+        source: Arc<SourceNode<S>>,
+        selector: impl Fn(&S) -> &R + Send + Sync + 'static,
+    ) -> Arc<Self> {
+        let node = Arc::new(ProjectionNode {
+            source: source.clone(),
+            selector: Box::new(selector),
+            inner: Mutex::new(ProjectionNodeInner {
+                cached: None,
+                needs_notify: false,
+                watchers: Vec::new(),
+            }),
+        });
+        let arc_prop: Arc<dyn Propagate> = node.clone();
+        source.add_child(Arc::downgrade(&arc_prop));
+        node
+    }
+}
+
+impl<S: Value, R: Value> Propagate for ProjectionNode<S, R> {
+    fn send_down(&self) {
+        let mut guard = self.inner.lock().unwrap();
+        guard.watchers.retain(|s| s.alive.upgrade().is_some());
+        if guard.watchers.is_empty() {
+            guard.cached = None;
+            return;
+        }
+        let changed = self.source.with(|s| {
+            let current = (self.selector)(s);
+            match &guard.cached {
+                Some(cached) if **cached == *current => None,
+                _ => Some(Arc::new(current.clone())),
+            }
+        });
+        if let Some(value) = changed {
+            guard.cached = Some(value);
+            guard.needs_notify = true;
+        }
+    }
+
+    fn notify(&self) {
+        let (cbs, v) = {
+            let mut guard = self.inner.lock().unwrap();
+            if !guard.needs_notify {
+                return;
+            }
+            guard.needs_notify = false;
+            let cbs: Vec<_> = guard.watchers.iter().map(|s| s.callback.clone()).collect();
+            (cbs, guard.cached.clone())
+        };
+        if let Some(v) = v {
+            for cb in &cbs {
+                cb(&v);
+            }
+        }
+    }
+}
+
+impl<S: Value, R: Value> ProjectionSource<R> for ProjectionNode<S, R> {
+    fn with_dyn(&self, f: &mut dyn FnMut(&R)) {
+        self.source.with(|s| f((self.selector)(s)));
+    }
+
+    fn add_watcher(&self, slot: WatchSlot<R>) {
+        let mut guard = self.inner.lock().unwrap();
+        if guard.cached.is_none() {
+            guard.cached = Some(Arc::new(self.source.with(|s| (self.selector)(s).clone())));
+        }
+        guard.watchers.push(slot);
+    }
+}
+
+// ── Projection ────────────────────────────────────────────────────────────────
+
+/// A borrow-based view of part of the store state.
+///
+/// Unlike a [`Reader`](crate::Reader), a `Projection` does not keep its own
+/// copy of the selected value: [`with`](Projection::with) reads it in place.
+/// Watchers receive a reference to a copy which is only refreshed when the
+/// projected value changes, so unchanged actions cost one in-place comparison
+/// and no clone.
+pub struct Projection<R: Value> {
+    node: Arc<dyn ProjectionSource<R>>,
+    connections: Connections,
+}
+
+impl<R: Value> Projection<R> {
+    pub(crate) fn new(node: Arc<dyn ProjectionSource<R>>) -> Self {
+        Projection {
+            node,
+            connections: Connections::new(),
+        }
+    }
+
+    /// Calls `f` with a reference to the projected value.
+    ///
+    /// The store state is locked while `f` runs: `f` must not read from or
+    /// dispatch into the store.
+    pub fn with<U>(&self, f: impl FnOnce(&R) -> U) -> U {
+        let mut f = Some(f);
+        let mut out = None;
+        self.node.with_dyn(&mut |r| out = f.take().map(|f| f(r)));
+        out.expect("projection calls back exactly once")
+    }
+
+    /// Returns a copy of the projected value.
+    pub fn get(&self) -> R {
+        self.with(R::clone)
+    }
+
+    /// Calls `f` whenever the projected value changes.
+    pub fn watch<F: Fn(&R) + Send + Sync + 'static>(&self, f: F) -> Subscription {
+        let (subscription, alive) = self.connections.connect();
+        self.node.add_watcher(WatchSlot {
+            alive,
+            callback: Arc::new(f),
+        });
+        subscription
+    }
+
+    /// Drops all watchers connected through this projection.
+    pub fn unbind(&self) {
+        self.connections.clear();
+    }
+}
+
+impl<R: Value> Clone for Projection<R> {
+    fn clone(&self) -> Self {
+        Projection::new(self.node.clone())
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, PartialEq)]
+    struct Doc {
+        items: Vec<i32>,
+        title: &'static str,
+    }
+
+    fn doc_projection() -> (Arc<SourceNode<Doc>>, Projection<Vec<i32>>) {
+        let source = SourceNode::new(Doc {
+            items: vec![1, 2],
+            title: "a",
+        });
+        let projection = Projection::new(ProjectionNode::new(source.clone(), |d: &Doc| &d.items));
+        (source, projection)
+    }
+
+    #[test]
+    fn with_reads_in_place() {
+        let (_source, projection) = doc_projection();
+        assert_eq!(projection.with(|items| items.len()), 2);
+        assert_eq!(projection.get(), vec![1, 2]);
+    }
+
+    #[test]
+    fn watch_fires_only_when_projection_changes() {
+        let (source, projection) = doc_projection();
+        let calls = Arc::new(Mutex::new(vec![]));
+        let c = calls.clone();
+        projection.watch(move |items| c.lock().unwrap().push(items.clone()));
+
+        source.set(Doc {
+            items: vec![1, 2],
+            title: "b",
+        });
+        source.set(Doc {
+            items: vec![3],
+            title: "b",
+        });
+
+        assert_eq!(*calls.lock().unwrap(), vec![vec![3]]);
+    }
+
+    #[test]
+    fn unbind_stops_callbacks() {
+        let (source, projection) = doc_projection();
+        let calls = Arc::new(Mutex::new(0));
+        let c = calls.clone();
+        projection.watch(move |_| *c.lock().unwrap() += 1);
+        projection.unbind();
+        source.set(Doc {
+            items: vec![],
+            title: "a",
+        });
+        assert_eq!(*calls.lock().unwrap(), 0);
+    }
+}
@@ -0,0 +1,425 @@
+//! Declarative data fetching with the results cached in store state.
+//!
+//! An [`Endpoint`] describes one kind of request, keyed by its arguments.
+//! Its reducer keeps a [`QueryCache`] of the results and turns
+//! [`QueryAction`]s into requests: a key is fetched once, while it is in
+//! flight or fresh further fetches are served from the cache, and stale
+//! results stay readable while they are fetched again. Results are tagged,
+//! so that a mutation can invalidate every result it affects.
+//!
+//! ```
+//! use uniflow::query::{Endpoint, QueryAction, QueryCache};
+//! use uniflow::{Dispatch, Read, Store};
+//!
+//! #[derive(Clone)]
+//! struct Api;
+//!
+//! impl Api {
+//!     async fn user(&self, id: u32) -> Result<String, String> {
+//!         Ok(format!("user {id}"))
+//!     }
+//! }
+//!
+//! uniflow::manual_spawner::init().expect("init");
+//!
+//! let users = Endpoint::new(|api: Api, id: u32| async move { api.user(id).await })
+//!     .provides(|id, _| vec![format!("user/{id}")]);
+//! let store = Store::builder_with_deps(QueryCache::new(), users.reducer(), Api).build();
+//!
+//! store.dispatch(QueryAction::Fetch(1));
+//! uniflow::manual_spawner::step();
+//! assert_eq!(store.get().get(&1).unwrap().data(), Some(&"user 1".to_string()));
+//!
+//! // After renaming user 1, fetch it again.
+//! store.dispatch(QueryAction::Invalidate("user/1".into()));
+//! ```
+//!
+//! Keep one cache per endpoint in the app state, and compose the endpoints'
+//! reducers with the app's as with any other slice.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+
+use crate::{Context, Deps, Effect, EffectReducer, Value};
+
+// ── Cache ─────────────────────────────────────────────────────────────────────
+
+/// The cached result of one key of an [`Endpoint`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Query<T, E> {
+    data: Option<T>,
+    error: Option<E>,
+    fetching: bool,
+    stale: bool,
+    /// The tags invalidated mid-flight: the result on its way may predate
+    /// the change, so it is fetched again once it lands if it carries one,
+    /// including a first result, whose tags are not known yet.
+    invalidated: Vec<String>,
+    tags: Vec<String>,
+    /// Counts results, so that the expiry of an earlier one is ignored.
+    generation: u64,
+}
+
+impl<T, E> Default for Query<T, E> {
+    fn default() -> Self {
+        Query {
+            data: None,
+            error: None,
+            fetching: false,
+            stale: false,
+            invalidated: Vec::new(),
+            tags: Vec::new(),
+            generation: 0,
+        }
+    }
+}
+
+impl<T, E> Query<T, E> {
+    /// The last successful result, kept while it is fetched again.
+    pub fn data(&self) -> Option<&T> {
+        self.data.as_ref()
+    }
+
+    /// The error of the last request, if it failed.
+    pub fn error(&self) -> Option<&E> {
+        self.error.as_ref()
+    }
+
+    /// Whether a request is in flight.
+    pub fn is_fetching(&self) -> bool {
+        self.fetching
+    }
+
+    /// Whether a request is in flight and there is no result to show
+    /// meanwhile.
+    pub fn is_loading(&self) -> bool {
+        self.fetching && self.data.is_none()
+    }
+
+    /// Whether the result has expired or been invalidated. The next
+    /// [`Fetch`](QueryAction::Fetch) requests it again.
+    pub fn is_stale(&self) -> bool {
+        self.stale
+    }
+}
+
+/// The results of an [`Endpoint`], by key. Keep it in the store state.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueryCache<K: Ord, T, E> {
+    entries: BTreeMap<K, Query<T, E>>,
+}
+
+impl<K: Ord, T, E> Default for QueryCache<K, T, E> {
+    fn default() -> Self {
+        QueryCache {
+            entries: BTreeMap::new(),
+        }
+    }
+}
+
+impl<K: Ord, T, E> QueryCache<K, T, E> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The entry for `key`, if it has ever been fetched.
+    pub fn get(&self, key: &K) -> Option<&Query<T, E>> {
+        self.entries.get(key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &Query<T, E>)> {
+        self.entries.iter()
+    }
+}
+
+/// The actions of an [`Endpoint`]'s reducer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum QueryAction<K, T, E> {
+    /// Requests `key`, unless its result is fresh or already in flight.
+    Fetch(K),
+    /// Requests `key` even if its result is fresh, unless already in flight.
+    Refetch(K),
+    /// Marks every result tagged with the tag stale, and requests it again.
+    Invalidate(String),
+    /// Dispatched by the endpoint when a request for `key` completes.
+    Settled { key: K, result: Result<T, E> },
+    /// Dispatched by the endpoint once a result has been fresh for the
+    /// endpoint's [`stale_after`](Endpoint::stale_after).
+    Expired { key: K, generation: u64 },
+}
+
+// ── Endpoint ──────────────────────────────────────────────────────────────────
+
+type Request<K, T, E, D> = Arc<dyn Fn(D, K) -> BoxFuture<'static, Result<T, E>> + Send + Sync>;
+type Provides<K, T> = Arc<dyn Fn(&K, &T) -> Vec<String> + Send + Sync>;
+type Reduced<K, T, E, D> = (QueryCache<K, T, E>, Effect<QueryAction<K, T, E>, D>);
+
+/// One kind of request, such as fetching a user by id, and how long its
+/// results stay fresh. See the [module docs](self).
+pub struct Endpoint<K, T, E, D = ()> {
+    request: Request<K, T, E, D>,
+    stale_after: Option<Duration>,
+    provides: Provides<K, T>,
+}
+
+impl<K, T, E, D> Clone for Endpoint<K, T, E, D> {
+    fn clone(&self) -> Self {
+        Endpoint {
+            request: self.request.clone(),
+            stale_after: self.stale_after,
+            provides: self.provides.clone(),
+        }
+    }
+}
+
+impl<K, T, E, D> Endpoint<K, T, E, D>
+where
+    K: Ord + Value,
+    T: Value,
+    E: Value,
+    D: Deps,
+{
+    /// Fetches a key by calling `request` with the store's deps. Results
+    /// stay fresh until invalidated or refetched, unless
+    /// [`stale_after`](Endpoint::stale_after) says otherwise.
+    pub fn new<F, Fut>(request: F) -> Self
+    where
+        F: Fn(D, K) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, E>> + Send + 'static,
+    {
+        Endpoint {
+            request: Arc::new(move |deps, key| Box::pin(request(deps, key))),
+            stale_after: None,
+            provides: Arc::new(|_, _| Vec::new()),
+        }
+    }
+
+    /// Marks results stale once they are `duration` old on the store's
+    /// clock, so that the next fetch revalidates them.
+    pub fn stale_after(mut self, duration: Duration) -> Self {
+        self.stale_after = Some(duration);
+        self
+    }
+
+    /// Tags each result with the tags `f` returns, for
+    /// [`Invalidate`](QueryAction::Invalidate).
+    pub fn provides(mut self, f: impl Fn(&K, &T) -> Vec<String> + Send + Sync + 'static) -> Self {
+        self.provides = Arc::new(f);
+        self
+    }
+
+    /// The endpoint as a reducer of its cache, for
+    /// [`Store::builder_with_deps`](crate::Store::builder_with_deps) or to
+    /// compose into the app's reducer.
+    pub fn reducer(self) -> impl EffectReducer<QueryCache<K, T, E>, QueryAction<K, T, E>, D> {
+        move |cache, action| self.reduce(cache, action)
+    }
+
+    pub fn reduce(
+        &self,
+        mut cache: QueryCache<K, T, E>,
+        action: QueryAction<K, T, E>,
+    ) -> Reduced<K, T, E, D> {
+        let effect = match action {
+            QueryAction::Fetch(key) => {
+                let query = cache.entries.entry(key.clone()).or_default();
+                let fresh = query.data.is_some() && !query.stale;
+                if query.fetching || fresh {
+                    Effect::none()
+                } else {
+                    self.start(query, key)
+                }
+            }
+            QueryAction::Refetch(key) => {
+                let query = cache.entries.entry(key.clone()).or_default();
+                if query.fetching {
+                    Effect::none()
+                } else {
+                    self.start(query, key)
+                }
+            }
+            QueryAction::Invalidate(tag) => {
+                let mut effects = Vec::new();
+                for (key, query) in cache.entries.iter_mut() {
+                    let tagged = query.tags.contains(&tag);
+                    query.stale |= tagged;
+                    if query.fetching {
+                        if !query.invalidated.contains(&tag) {
+                            query.invalidated.push(tag.clone());
+                        }
+                    } else if tagged {
+                        effects.push(self.start(query, key.clone()));
+                    }
+                }
+                Effect::merge(effects)
+            }
+            QueryAction::Settled { key, result } => {
+                let query = cache.entries.entry(key.clone()).or_default();
+                query.fetching = false;
+                query.generation += 1;
+                let succeeded = result.is_ok();
+                let invalidated = std::mem::take(&mut query.invalidated);
+                let mut refetch = invalidated.iter().any(|tag| query.tags.contains(tag));
+                match result {
+                    Ok(data) => {
+                        query.tags = (self.provides)(&key, &data);
+                        refetch |= invalidated.iter().any(|tag| query.tags.contains(tag));
+                        query.data = Some(data);
+                        query.error = None;
+                        query.stale = false;
+                    }
+                    Err(error) => query.error = Some(error),
+                }
+                if refetch {
+                    self.start(query, key)
+                } else if succeeded {
+                    self.expire(query, key)
+                } else {
+                    Effect::none()
+                }
+            }
+            QueryAction::Expired { key, generation } => {
+                if let Some(query) = cache.entries.get_mut(&key)
+                    && query.generation == generation
+                {
+                    query.stale = true;
+                }
+                Effect::none()
+            }
+        };
+        (cache, effect)
+    }
+
+    fn start(&self, query: &mut Query<T, E>, key: K) -> Effect<QueryAction<K, T, E>, D> {
+        query.fetching = true;
+        let request = self.request.clone();
+        Effect::new(move |ctx: Context<QueryAction<K, T, E>, D>| async move {
+            let result = request(ctx.deps().clone(), key.clone()).await;
+            ctx.dispatch(QueryAction::Settled { key, result });
+        })
+    }
+
+    fn expire(&self, query: &Query<T, E>, key: K) -> Effect<QueryAction<K, T, E>, D> {
+        let Some(duration) = self.stale_after else {
+            return Effect::none();
+        };
+        let generation = query.generation;
+        Effect::new(move |ctx: Context<QueryAction<K, T, E>, D>| async move {
+            ctx.clock().sleep(duration).await;
+            ctx.dispatch(QueryAction::Expired { key, generation });
+        })
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{init as init_executor, tick};
+    use crate::test::TestClock;
+    use crate::{Dispatch, Read, Store};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Counts requests, and answers each with the count so far.
+    #[derive(Clone, Default)]
+    struct Api {
+        requests: Arc<AtomicU32>,
+    }
+
+    fn endpoint() -> Endpoint<u32, u32, String, Api> {
+        Endpoint::new(|api: Api, id: u32| async move {
+            Ok(id * 100 + api.requests.fetch_add(1, Ordering::SeqCst) + 1)
+        })
+        .provides(|id, _| vec![format!("user/{id}"), "users".into()])
+    }
+
+    #[test]
+    fn fetches_are_deduplicated_and_cached() {
+        init_executor();
+        let api = Api::default();
+        let store =
+            Store::builder_with_deps(QueryCache::new(), endpoint().reducer(), api.clone()).build();
+        store.dispatch(QueryAction::Fetch(1));
+        store.dispatch(QueryAction::Fetch(1));
+        tick();
+        store.dispatch(QueryAction::Fetch(1));
+        tick();
+
+        assert_eq!(api.requests.load(Ordering::SeqCst), 1);
+        let cache = store.get();
+        let user = cache.get(&1).unwrap();
+        assert_eq!((user.data(), user.is_fetching()), (Some(&101), false));
+    }
+
+    #[test]
+    fn stale_results_are_kept_while_revalidating() {
+        init_executor();
+        let clock = TestClock::new();
+        let api = Api::default();
+        let endpoint = endpoint().stale_after(Duration::from_secs(60));
+        let store = Store::builder_with_deps(QueryCache::new(), endpoint.reducer(), api.clone())
+            .with_clock(clock.clone())
+            .build();
+        store.dispatch(QueryAction::Fetch(1));
+        tick();
+        clock.advance(Duration::from_secs(60));
+        assert!(store.get().get(&1).unwrap().is_stale());
+
+        // The stale result is what the UI shows while the request is out.
+        store.dispatch(QueryAction::Fetch(1));
+        store.dispatch(QueryAction::Fetch(1));
+        let revalidating = store.wait_for(|cache| cache.get(&1).unwrap().is_fetching());
+        tick();
+        let cache = futures::executor::block_on(revalidating);
+        assert_eq!(cache.get(&1).unwrap().data(), Some(&101));
+        assert!(!cache.get(&1).unwrap().is_loading());
+        assert_eq!(store.get().get(&1).unwrap().data(), Some(&102));
+        assert!(!store.get().get(&1).unwrap().is_stale());
+    }
+
+    #[test]
+    fn invalidating_a_tag_refetches_what_it_tags() {
+        init_executor();
+        let api = Api::default();
+        let store =
+            Store::builder_with_deps(QueryCache::new(), endpoint().reducer(), api.clone()).build();
+        store.dispatch(QueryAction::Fetch(1));
+        store.dispatch(QueryAction::Fetch(2));
+        tick();
+        store.dispatch(QueryAction::Invalidate("user/2".into()));
+        tick();
+
+        let cache = store.get();
+        let data = |id| cache.get(&id).unwrap().data().copied();
+        assert_eq!((data(1), data(2)), (Some(101), Some(203)));
+
+        // Invalidated mid-flight, a result is fetched again as it lands.
+        store.dispatch(QueryAction::Refetch(1));
+        store.dispatch(QueryAction::Invalidate("users".into()));
+        tick();
+        assert_eq!(api.requests.load(Ordering::SeqCst), 6);
+        assert!(store.get().iter().all(|(_, query)| !query.is_stale()));
+    }
+
+    #[test]
+    fn invalidating_a_first_fetch_in_flight_refetches_it() {
+        init_executor();
+        let api = Api::default();
+        let store =
+            Store::builder_with_deps(QueryCache::new(), endpoint().reducer(), api.clone()).build();
+        store.dispatch(QueryAction::Fetch(1));
+        store.dispatch(QueryAction::Fetch(2));
+        store.dispatch(QueryAction::Invalidate("user/1".into()));
+        tick();
+
+        assert_eq!(api.requests.load(Ordering::SeqCst), 3);
+        let cache = store.get();
+        let data = |id| cache.get(&id).unwrap().data().copied();
+        assert_eq!((data(1), data(2)), (Some(103), Some(202)));
+    }
+}
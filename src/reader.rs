@@ -1,7 +1,8 @@
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Weak};
 
+use crate::changes::Changes;
 use crate::node::{DerivedNode, MergeNode, ReadableNode, WatchSlot};
-use crate::subscription::Subscription;
+use crate::subscription::{Connections, Subscription};
 use crate::{Read, Value};
 
 pub struct Reader<T>
@@ -9,17 +10,27 @@ where
     T: Value,
 {
     pub(crate) node: Arc<dyn ReadableNode<T>>,
-    connections: Mutex<Vec<Subscription>>,
+    connections: Connections,
 }
 
 impl<T: Value> Reader<T> {
     pub(crate) fn new(node: Arc<dyn ReadableNode<T>>) -> Self {
         Reader {
             node,
-            connections: Mutex::new(Vec::new()),
+            connections: Connections::new(),
         }
     }
 
+    /// Makes a connection tied to this reader: it is released on
+    /// [`unbind`](Read::unbind) or when the reader is dropped.
+    pub(crate) fn connect(&self) -> (Subscription, Weak<()>) {
+        self.connections.connect()
+    }
+
+    /// Returns a `Reader<U>` which applies `f` to this reader's value.
+    ///
+    /// The mapped value is cached: its watchers, and readers mapped from it in
+    /// turn, only run when the mapped value changes.
     pub fn map<U, F>(&self, f: F) -> Reader<U>
     where
         U: Clone + PartialEq + Send + Sync + 'static,
@@ -27,6 +38,21 @@ impl<T: Value> Reader<T> {
     {
         Reader::new(DerivedNode::new(self.node.clone(), f))
     }
+
+    /// Returns a reader over the pair of this reader's value and `other`'s,
+    /// which updates whenever either changes. See [`Merge`] for more readers.
+    pub fn zip<U: Value>(&self, other: &Reader<U>) -> Reader<(T, U)> {
+        with((self.clone(), other.clone()))
+    }
+
+    /// Returns a stream which yields every new value of the reader, skipping
+    /// values equal to the one yielded before.
+    ///
+    /// The current value is not yielded. The stream keeps the reader's
+    /// selection alive, so the reader itself may be dropped.
+    pub fn stream(&self) -> Changes<T> {
+        Changes::keep(self.node.clone())
+    }
 }
 
 impl<T: Value> Read<T> for Reader<T> {
@@ -34,23 +60,22 @@ impl<T: Value> Read<T> for Reader<T> {
         self.node.get()
     }
 
-    fn watch<F: Fn(&T) + Send + Sync + 'static>(&self, f: F) -> &Self {
-        let (sub, weak) = Subscription::new();
+    fn watch<F: Fn(&T) + Send + Sync + 'static>(&self, f: F) -> Subscription {
+        let (subscription, alive) = self.connections.connect();
         self.node.add_watcher(WatchSlot {
-            alive: weak,
+            alive,
             callback: Arc::new(f),
         });
-        self.connections.lock().unwrap().push(sub);
-        self
+        subscription
     }
 
-    fn bind<F: Fn(&T) + Send + Sync + 'static>(&self, f: F) -> &Self {
+    fn bind<F: Fn(&T) + Send + Sync + 'static>(&self, f: F) -> Subscription {
         f(&self.get());
         self.watch(f)
     }
 
     fn unbind(&self) {
-        self.connections.lock().unwrap().clear();
+        self.connections.clear();
     }
 }
 
@@ -58,7 +83,7 @@ impl<T: Value> Clone for Reader<T> {
     fn clone(&self) -> Self {
         Reader {
             node: self.node.clone(),
-            connections: Mutex::new(Vec::new()),
+            connections: Connections::new(),
         }
     }
 }
@@ -98,6 +123,30 @@ pub fn with<R: Merge>(readers: R) -> Reader<R::Combined> {
     readers.merge()
 }
 
+/// Combines several readers into one memoized reader over an expression of
+/// their values.
+///
+/// `combine!((a, b) => expr)` evaluates `expr` with each name bound to the
+/// value of the reader of the same name, and only re-evaluates when one of
+/// them changes. The readers are cloned, not consumed. Takes 2–5 readers.
+///
+/// ```
+/// use uniflow::{Read, State, Write, combine};
+///
+/// let width = State::new(800);
+/// let height = State::new(600);
+/// let (w, h) = (width.reader(), height.reader());
+/// let area = combine!((w, h) => w * h);
+/// width.set(400);
+/// assert_eq!(area.get(), 240_000);
+/// ```
+#[macro_export]
+macro_rules! combine {
+    (($($reader:ident),+ $(,)?) => $body:expr) => {
+        $crate::with(($($reader.clone(),)+)).map(move |($($reader,)+)| $body)
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -161,6 +210,21 @@ mod tests {
         assert_eq!(*calls.lock().unwrap(), vec![5, 10]); // then watches
     }
 
+    #[test]
+    fn stream_outlives_the_reader() {
+        use futures::{FutureExt, StreamExt};
+
+        let (source, reader) = source_reader(0i32);
+        let mut values = reader.map(|v| v / 2).stream();
+        drop(reader);
+        for v in 1..=4 {
+            source.set(v);
+        }
+        assert_eq!(values.next().now_or_never(), Some(Some(1)));
+        assert_eq!(values.next().now_or_never(), Some(Some(2)));
+        assert_eq!(values.next().now_or_never(), None);
+    }
+
     #[test]
     fn clone_shares_node_not_connections() {
         let (source, reader) = source_reader(0i32);
@@ -205,6 +269,36 @@ mod tests {
         assert_eq!(*calls.lock().unwrap(), vec![50]);
     }
 
+    #[test]
+    fn chained_maps_only_rerun_when_their_input_changes() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let (source, reader) = source_reader(1i32);
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counted = runs.clone();
+        let parity = reader.map(|v| v % 2);
+        let label = parity.map(move |p| {
+            counted.fetch_add(1, Ordering::Relaxed);
+            if p == 0 { "even" } else { "odd" }
+        });
+        source.set(3);
+        source.set(4);
+        assert_eq!(label.get(), "even");
+        assert_eq!(runs.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn zip_and_combine_track_both_readers() {
+        let (a_source, a) = source_reader(1i32);
+        let (b_source, b) = source_reader(2i32);
+        let zipped = a.zip(&b);
+        let sum = crate::combine!((a, b) => a + b);
+        a_source.set(10);
+        b_source.set(20);
+        assert_eq!(zipped.get(), (10, 20));
+        assert_eq!(sum.get(), 30);
+    }
+
     #[test]
     fn with_combines_two_readers() {
         let (_, r1) = source_reader(1i32);
@@ -0,0 +1,209 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use web_time::Instant;
+
+use crate::{Action, Clock, Deps, Dispatch, Middleware, Store, SystemClock, Value};
+
+/// An action together with the time it was dispatched, relative to the start
+/// of the recording.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecordedAction<A> {
+    pub at: Duration,
+    pub action: A,
+}
+
+/// A recorded session, as returned by [`ActionRecorder::log`].
+///
+/// With the `serde` feature the log can be serialized, e.g. to attach it to a
+/// bug report, and [`replay`]ed later.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ActionLog<A> {
+    pub actions: Vec<RecordedAction<A>>,
+}
+
+impl<A> Default for ActionLog<A> {
+    fn default() -> Self {
+        ActionLog {
+            actions: Vec::new(),
+        }
+    }
+}
+
+struct RecorderInner<A> {
+    clock: Arc<dyn Clock>,
+    started: Option<Instant>,
+    log: ActionLog<A>,
+}
+
+/// Records every action reaching the reducer.
+///
+/// Attach it as the outermost [`Middleware`] so it sees actions before any
+/// other middleware rewrites them, and [`attach`](ActionRecorder::attach) it
+/// to the built store to timestamp actions with the store's clock:
+///
+/// ```
+/// use uniflow::{ActionRecorder, Dispatch, Read, Store, replay};
+///
+/// uniflow::manual_spawner::init().expect("init");
+///
+/// let recorder = ActionRecorder::new();
+/// let store = Store::builder(0, |s: i32, n: i32| s + n)
+///     .middleware(recorder.clone())
+///     .build();
+/// recorder.attach(&store);
+/// store.dispatch(1);
+/// store.dispatch(2);
+/// uniflow::manual_spawner::step();
+///
+/// let fresh = Store::new(0, |s: i32, n: i32| s + n);
+/// replay(&fresh, &recorder.log());
+/// uniflow::manual_spawner::step();
+/// assert_eq!(fresh.get(), store.get());
+/// ```
+///
+/// Actions dispatched by effects are recorded too. Replay into a store whose
+/// effects are disabled, or those actions will be dispatched twice.
+pub struct ActionRecorder<A> {
+    inner: Arc<Mutex<RecorderInner<A>>>,
+}
+
+impl<A: Action + Clone> ActionRecorder<A> {
+    pub fn new() -> Self {
+        ActionRecorder {
+            inner: Arc::new(Mutex::new(RecorderInner {
+                clock: Arc::new(SystemClock),
+                started: None,
+                log: ActionLog::default(),
+            })),
+        }
+    }
+
+    /// Timestamps actions with the clock of `store`.
+    pub fn attach<S: Value, D: Deps>(&self, store: &Store<S, A, D>) {
+        self.inner.lock().unwrap().clock = store.clock_handle();
+    }
+
+    /// Returns a copy of everything recorded so far.
+    pub fn log(&self) -> ActionLog<A> {
+        self.inner.lock().unwrap().log.clone()
+    }
+
+    /// Forgets the recorded actions and restarts the timestamps.
+    pub fn clear(&self) {
+        let mut guard = self.inner.lock().unwrap();
+        guard.started = None;
+        guard.log.actions.clear();
+    }
+}
+
+impl<A: Action + Clone> Default for ActionRecorder<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A> Clone for ActionRecorder<A> {
+    fn clone(&self) -> Self {
+        ActionRecorder {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<S, A: Action + Clone> Middleware<S, A> for ActionRecorder<A> {
+    fn before(&self, _: &S, action: A) -> Option<A> {
+        let mut guard = self.inner.lock().unwrap();
+        let now = guard.clock.now();
+        let started = *guard.started.get_or_insert(now);
+        guard.log.actions.push(RecordedAction {
+            at: now - started,
+            action: action.clone(),
+        });
+        Some(action)
+    }
+}
+
+/// Dispatches every action of `log` into `store`, in order.
+///
+/// Replay ignores the recorded timestamps: actions are queued back to back,
+/// so the resulting state only depends on the initial state and the log.
+pub fn replay<A: Action + Clone>(store: &impl Dispatch<A>, log: &ActionLog<A>) {
+    for recorded in &log.actions {
+        store.dispatch(recorded.action.clone());
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Read;
+    use crate::executor::{init as init_executor, tick};
+    use crate::test::TestClock;
+
+    fn recorded_store(clock: TestClock) -> (Store<i32, i32>, ActionRecorder<i32>) {
+        let recorder = ActionRecorder::new();
+        let store = Store::builder(0, |s: i32, n: i32| s * 2 + n)
+            .middleware(recorder.clone())
+            .with_clock(clock)
+            .build();
+        recorder.attach(&store);
+        (store, recorder)
+    }
+
+    #[test]
+    fn records_actions_with_clock_timestamps() {
+        init_executor();
+        let clock = TestClock::new();
+        let (store, recorder) = recorded_store(clock.clone());
+        clock.advance(Duration::from_secs(5));
+        store.dispatch(1);
+        tick();
+        clock.advance(Duration::from_millis(250));
+        store.dispatch(2);
+        tick();
+
+        assert_eq!(
+            recorder.log().actions,
+            vec![
+                RecordedAction {
+                    at: Duration::ZERO,
+                    action: 1,
+                },
+                RecordedAction {
+                    at: Duration::from_millis(250),
+                    action: 2,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn replay_reproduces_final_state() {
+        init_executor();
+        let (store, recorder) = recorded_store(TestClock::new());
+        for n in [3, 1, 4, 1, 5] {
+            store.dispatch(n);
+        }
+        tick();
+
+        let fresh = Store::new(0, |s: i32, n: i32| s * 2 + n);
+        replay(&fresh, &recorder.log());
+        tick();
+        assert_eq!(fresh.get(), store.get());
+    }
+
+    #[test]
+    fn clear_forgets_recorded_actions() {
+        init_executor();
+        let (store, recorder) = recorded_store(TestClock::new());
+        store.dispatch(1);
+        tick();
+        recorder.clear();
+        assert!(recorder.log().actions.is_empty());
+    }
+}
@@ -0,0 +1,120 @@
+use std::ops::{BitOr, BitOrAssign};
+
+/// A named part of the store state, used for reducer-driven change detection.
+///
+/// Implement it on a fieldless enum that lists the regions of your state.
+/// Up to 64 regions are supported; `index` must return a value below 64.
+///
+/// ```
+/// #[derive(Clone, Copy)]
+/// enum AppRegion { Todos, Filter }
+///
+/// impl uniflow::Region for AppRegion {
+///     fn index(self) -> u32 { self as u32 }
+/// }
+/// ```
+pub trait Region: Copy {
+    fn index(self) -> u32;
+}
+
+/// The set of regions a reducer declares it touched while handling an action.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Changed(u64);
+
+impl Changed {
+    /// Nothing changed: watchers and readers are not notified.
+    pub const NONE: Changed = Changed(0);
+
+    /// Every region may have changed.
+    pub const ALL: Changed = Changed(u64::MAX);
+
+    pub fn region<R: Region>(region: R) -> Self {
+        let index = region.index();
+        debug_assert!(index < 64, "region index out of range: {index}");
+        Changed(1 << index)
+    }
+
+    /// Adds `region` to the set.
+    pub fn with<R: Region>(self, region: R) -> Self {
+        self | Changed::region(region)
+    }
+
+    pub fn contains<R: Region>(self, region: R) -> bool {
+        self.intersects(Changed::region(region))
+    }
+
+    pub fn intersects(self, other: Changed) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl<R: Region> From<R> for Changed {
+    fn from(region: R) -> Self {
+        Changed::region(region)
+    }
+}
+
+impl BitOr for Changed {
+    type Output = Changed;
+
+    fn bitor(self, rhs: Changed) -> Changed {
+        Changed(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Changed {
+    fn bitor_assign(&mut self, rhs: Changed) {
+        self.0 |= rhs.0;
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy)]
+    enum R {
+        A,
+        B,
+        C,
+    }
+
+    impl Region for R {
+        fn index(self) -> u32 {
+            self as u32
+        }
+    }
+
+    #[test]
+    fn none_is_empty() {
+        assert!(Changed::NONE.is_empty());
+        assert!(!Changed::NONE.contains(R::A));
+    }
+
+    #[test]
+    fn all_contains_every_region() {
+        assert!(Changed::ALL.contains(R::A));
+        assert!(Changed::ALL.contains(R::C));
+    }
+
+    #[test]
+    fn with_accumulates_regions() {
+        let changed = Changed::region(R::A).with(R::C);
+        assert!(changed.contains(R::A));
+        assert!(!changed.contains(R::B));
+        assert!(changed.contains(R::C));
+    }
+
+    #[test]
+    fn bitor_unions_sets() {
+        let mut changed = Changed::from(R::A) | Changed::from(R::B);
+        changed |= R::C.into();
+        assert!(changed.contains(R::A) && changed.contains(R::B) && changed.contains(R::C));
+    }
+}
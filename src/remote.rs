@@ -0,0 +1,442 @@
+//! Mirroring a store in other processes over [gRPC](https://grpc.io)
+//! (feature `remote`).
+//!
+//! A [`RemoteServer`] serves a store as the `uniflow.Remote` gRPC service:
+//! `Watch` streams its state, starting with the current one, and `Dispatch`
+//! accepts actions. A [`RemoteStore`] connects to it and mirrors the state
+//! locally, behind the same [`Read`] API as a store, and dispatches actions
+//! back to the server if the action type is serializable. Messages are JSON,
+//! so the two ends only need to agree on the serde representation of the
+//! state and actions.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! use uniflow::remote::{RemoteServer, RemoteStore};
+//! use uniflow::{Dispatch, Read, Store};
+//!
+//! let store = Store::new(0, |s: i32, n: i32| s + n);
+//! tokio::spawn(
+//!     tonic::transport::Server::builder()
+//!         .add_service(RemoteServer::new(&store))
+//!         .serve("127.0.0.1:50051".parse()?),
+//! );
+//!
+//! let mirror: RemoteStore<i32, i32> = RemoteStore::connect("http://127.0.0.1:50051").await?;
+//! mirror.dispatch(1);
+//! mirror.watch(|count| println!("count: {count}"));
+//! # Ok(())
+//! # }
+//! ```
+
+use std::convert::Infallible;
+use std::marker::PhantomData;
+use std::task::{Context, Poll};
+
+use bytes::{Buf, BufMut};
+use futures::StreamExt;
+use futures::future::BoxFuture;
+use futures::stream::{self, BoxStream};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
+use tonic::body::Body;
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use tonic::codegen::{Body as HttpBody, Service, StdError, http};
+use tonic::server::{Grpc, NamedService, ServerStreamingService, UnaryService};
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Request, Response, Status};
+
+use crate::reader::Reader;
+use crate::subscription::Subscription;
+use crate::{Action, Deps, Dispatch, Dispatcher, Read, State, Store, Value, Write};
+
+const DISPATCH: &str = "/uniflow.Remote/Dispatch";
+const WATCH: &str = "/uniflow.Remote/Watch";
+
+// ── Server ────────────────────────────────────────────────────────────────────
+
+/// Serves a store as the `uniflow.Remote` gRPC service, for
+/// [`tonic::transport::Server::add_service`].
+pub struct RemoteServer<S: Value, A: Action> {
+    reader: Reader<S>,
+    /// `None` for read-only servers.
+    dispatcher: Option<Dispatcher<A>>,
+}
+
+impl<S, A> RemoteServer<S, A>
+where
+    S: Value + Serialize,
+    A: Action + DeserializeOwned,
+{
+    /// Serves `store`, accepting actions from clients.
+    pub fn new<D: Deps>(store: &Store<S, A, D>) -> Self {
+        RemoteServer {
+            reader: store.reader(),
+            dispatcher: Some(store.dispatcher()),
+        }
+    }
+
+    /// Serves the state of `store`, refusing actions from clients with
+    /// `PERMISSION_DENIED`.
+    pub fn read_only<D: Deps>(store: &Store<S, A, D>) -> Self {
+        RemoteServer {
+            reader: store.reader(),
+            dispatcher: None,
+        }
+    }
+
+    fn dispatch(&self, action: &str) -> Result<(), Status> {
+        let Some(dispatcher) = &self.dispatcher else {
+            return Err(Status::permission_denied("the store is read-only"));
+        };
+        let action = serde_json::from_str(action)
+            .map_err(|e| Status::invalid_argument(format!("invalid action: {e}")))?;
+        dispatcher.dispatch(action);
+        Ok(())
+    }
+
+    /// The current state, then every change.
+    fn states(&self) -> BoxStream<'static, Result<String, Status>> {
+        let changes = self.reader.stream();
+        stream::once(std::future::ready(self.reader.get()))
+            .chain(changes)
+            .map(|state| serde_json::to_string(&state).map_err(|e| Status::internal(e.to_string())))
+            .boxed()
+    }
+}
+
+impl<S: Value, A: Action> Clone for RemoteServer<S, A> {
+    fn clone(&self) -> Self {
+        RemoteServer {
+            reader: self.reader.clone(),
+            dispatcher: self.dispatcher.clone(),
+        }
+    }
+}
+
+impl<S: Value, A: Action> NamedService for RemoteServer<S, A> {
+    const NAME: &'static str = "uniflow.Remote";
+}
+
+impl<S, A, B> Service<http::Request<B>> for RemoteServer<S, A>
+where
+    S: Value + Serialize,
+    A: Action + DeserializeOwned,
+    B: HttpBody + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<Body>;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Self::Response, Infallible>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let server = self.clone();
+        match request.uri().path() {
+            DISPATCH => Box::pin(async move {
+                Ok(Grpc::new(JsonCodec)
+                    .unary(DispatchMethod(server), request)
+                    .await)
+            }),
+            WATCH => Box::pin(async move {
+                Ok(Grpc::new(JsonCodec)
+                    .server_streaming(WatchMethod(server), request)
+                    .await)
+            }),
+            path => {
+                let status = Status::unimplemented(format!("no method {path}"));
+                Box::pin(std::future::ready(Ok(status.into_http())))
+            }
+        }
+    }
+}
+
+struct DispatchMethod<S: Value, A: Action>(RemoteServer<S, A>);
+
+impl<S, A> UnaryService<String> for DispatchMethod<S, A>
+where
+    S: Value + Serialize,
+    A: Action + DeserializeOwned,
+{
+    type Response = String;
+    type Future = std::future::Ready<Result<Response<String>, Status>>;
+
+    fn call(&mut self, request: Request<String>) -> Self::Future {
+        let result = self.0.dispatch(request.get_ref());
+        std::future::ready(result.map(|()| Response::new(String::new())))
+    }
+}
+
+struct WatchMethod<S: Value, A: Action>(RemoteServer<S, A>);
+
+impl<S, A> ServerStreamingService<String> for WatchMethod<S, A>
+where
+    S: Value + Serialize,
+    A: Action + DeserializeOwned,
+{
+    type Response = String;
+    type ResponseStream = BoxStream<'static, Result<String, Status>>;
+    type Future = std::future::Ready<Result<Response<Self::ResponseStream>, Status>>;
+
+    fn call(&mut self, _: Request<String>) -> Self::Future {
+        std::future::ready(Ok(Response::new(self.0.states())))
+    }
+}
+
+// ── Client ────────────────────────────────────────────────────────────────────
+
+/// A local mirror of a store served by a [`RemoteServer`].
+///
+/// Reads and watches see the latest state received from the server. If `A`
+/// is serializable the mirror also implements [`Dispatch`], sending actions
+/// to the server without waiting for them; the default, [`Infallible`],
+/// makes a read-only mirror. The mirror stops following the server when it
+/// is dropped, or keeps its last state if the connection is lost.
+pub struct RemoteStore<S: Value, A = Infallible> {
+    state: State<S>,
+    client: tonic::client::Grpc<Channel>,
+    runtime: Handle,
+    follow: JoinHandle<()>,
+    _action: PhantomData<fn(A)>,
+}
+
+impl<S, A> RemoteStore<S, A>
+where
+    S: Value + DeserializeOwned,
+{
+    /// Connects to the server at `endpoint`, e.g. `"http://[::1]:50051"`, and
+    /// waits for its current state. Must be called within a Tokio runtime.
+    pub async fn connect<E>(endpoint: E) -> Result<Self, Status>
+    where
+        E: TryInto<Endpoint>,
+        E::Error: std::fmt::Display,
+    {
+        let endpoint = endpoint
+            .try_into()
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let channel = endpoint
+            .connect()
+            .await
+            .map_err(|e| Status::unavailable(e.to_string()))?;
+        Self::from_channel(channel).await
+    }
+
+    /// Like [`connect`](RemoteStore::connect), over an existing channel.
+    pub async fn from_channel(channel: Channel) -> Result<Self, Status> {
+        let mut client = tonic::client::Grpc::new(channel);
+        client
+            .ready()
+            .await
+            .map_err(|e| Status::unavailable(e.to_string()))?;
+        let mut states = client
+            .server_streaming(
+                Request::new(String::new()),
+                WATCH.parse().unwrap(),
+                JsonCodec,
+            )
+            .await?
+            .into_inner()
+            .map(|json| json.and_then(|json| from_json::<S>(&json)));
+        let first = match states.next().await {
+            Some(state) => state?,
+            None => return Err(Status::unavailable("the server sent no state")),
+        };
+
+        let state = State::new(first);
+        let mirror = state.clone();
+        let follow = tokio::spawn(async move {
+            while let Some(Ok(state)) = states.next().await {
+                mirror.set(state);
+            }
+        });
+        Ok(RemoteStore {
+            state,
+            client,
+            runtime: Handle::current(),
+            follow,
+            _action: PhantomData,
+        })
+    }
+
+    /// A reader of the mirrored state.
+    pub fn reader(&self) -> Reader<S> {
+        self.state.reader()
+    }
+}
+
+impl<S, A> RemoteStore<S, A>
+where
+    S: Value,
+    A: Action + Serialize,
+{
+    /// Sends `action` to the server, resolving once the server has accepted
+    /// it. The mirror reflects the result when the server reports the change.
+    pub async fn send(&self, action: A) -> Result<(), Status> {
+        send(self.client.clone(), action).await
+    }
+}
+
+async fn send<A: Serialize>(
+    mut client: tonic::client::Grpc<Channel>,
+    action: A,
+) -> Result<(), Status> {
+    let action = serde_json::to_string(&action).map_err(|e| Status::internal(e.to_string()))?;
+    client
+        .ready()
+        .await
+        .map_err(|e| Status::unavailable(e.to_string()))?;
+    client
+        .unary::<_, String, _>(Request::new(action), DISPATCH.parse().unwrap(), JsonCodec)
+        .await?;
+    Ok(())
+}
+
+impl<S, A> Dispatch<A> for RemoteStore<S, A>
+where
+    S: Value,
+    A: Action + Serialize,
+{
+    fn dispatch(&self, action: A) {
+        self.runtime.spawn(send(self.client.clone(), action));
+    }
+}
+
+impl<S: Value, A> Read<S> for RemoteStore<S, A>
+where
+    A: 'static,
+{
+    fn get(&self) -> S {
+        self.state.get()
+    }
+
+    fn watch<F: Fn(&S) + Send + Sync + 'static>(&self, f: F) -> Subscription {
+        self.state.watch(f)
+    }
+
+    fn bind<F: Fn(&S) + Send + Sync + 'static>(&self, f: F) -> Subscription {
+        self.state.bind(f)
+    }
+
+    fn unbind(&self) {
+        self.state.unbind();
+    }
+}
+
+impl<S: Value, A> Drop for RemoteStore<S, A> {
+    fn drop(&mut self) {
+        self.follow.abort();
+    }
+}
+
+fn from_json<T: DeserializeOwned>(json: &str) -> Result<T, Status> {
+    serde_json::from_str(json).map_err(|e| Status::internal(format!("invalid state: {e}")))
+}
+
+// ── Codec ─────────────────────────────────────────────────────────────────────
+
+/// Frames JSON strings as gRPC messages.
+#[derive(Clone, Copy, Default)]
+struct JsonCodec;
+
+impl Codec for JsonCodec {
+    type Encode = String;
+    type Decode = String;
+    type Encoder = JsonCodec;
+    type Decoder = JsonCodec;
+
+    fn encoder(&mut self) -> JsonCodec {
+        JsonCodec
+    }
+
+    fn decoder(&mut self) -> JsonCodec {
+        JsonCodec
+    }
+}
+
+impl Encoder for JsonCodec {
+    type Item = String;
+    type Error = Status;
+
+    fn encode(&mut self, item: String, dst: &mut EncodeBuf<'_>) -> Result<(), Status> {
+        dst.put_slice(item.as_bytes());
+        Ok(())
+    }
+}
+
+impl Decoder for JsonCodec {
+    type Item = String;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<String>, Status> {
+        let bytes = src.copy_to_bytes(src.remaining());
+        String::from_utf8(bytes.to_vec())
+            .map(Some)
+            .map_err(|e| Status::internal(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{init as init_executor, tick};
+    use std::time::{Duration, Instant};
+    use tonic::transport::Server;
+    use tonic::transport::server::TcpIncoming;
+
+    /// Ticks the store's executor until `done`, or panics after a second.
+    fn tick_until(done: impl Fn() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while !done() {
+            assert!(Instant::now() < deadline, "timed out");
+            tick();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    fn serve(server: RemoteServer<i32, i32>, runtime: &tokio::runtime::Runtime) -> String {
+        let incoming = runtime
+            .block_on(async { TcpIncoming::bind("127.0.0.1:0".parse().unwrap()) })
+            .unwrap();
+        let address = incoming.local_addr().unwrap();
+        runtime.spawn(
+            Server::builder()
+                .add_service(server)
+                .serve_with_incoming(incoming),
+        );
+        format!("http://{address}")
+    }
+
+    #[test]
+    fn mirrors_state_and_dispatches_actions() {
+        init_executor();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let store = Store::new(1, |s: i32, n: i32| s + n);
+        let address = serve(RemoteServer::new(&store), &runtime);
+
+        let mirror: RemoteStore<i32, i32> =
+            runtime.block_on(RemoteStore::connect(address)).unwrap();
+        assert_eq!(mirror.get(), 1);
+
+        mirror.dispatch(2);
+        tick_until(|| mirror.get() == 3);
+        store.dispatch(4);
+        tick_until(|| mirror.get() == 7);
+    }
+
+    #[test]
+    fn read_only_servers_refuse_actions() {
+        init_executor();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let store = Store::new(1, |s: i32, n: i32| s + n);
+        let address = serve(RemoteServer::read_only(&store), &runtime);
+
+        let mirror: RemoteStore<i32, i32> =
+            runtime.block_on(RemoteStore::connect(address)).unwrap();
+        let refused = runtime.block_on(mirror.send(2)).unwrap_err();
+        assert_eq!(refused.code(), tonic::Code::PermissionDenied);
+    }
+}
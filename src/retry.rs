@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+/// How [`Effect::retrying`](crate::Effect::retrying) retries failed work:
+/// up to a number of attempts, waiting an exponentially growing delay in
+/// between.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_delay: Duration,
+    multiplier: f64,
+    max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Retries after `initial_delay`, doubling the delay after every further
+    /// failure. Makes 3 attempts in all unless told otherwise.
+    pub fn exponential(initial_delay: Duration) -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_delay,
+            multiplier: 2.0,
+            max_delay: Duration::MAX,
+        }
+    }
+
+    /// The number of attempts, including the first, before giving up.
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// The factor the delay grows by after each failed retry.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Caps the delay between attempts.
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// The delay before attempt `attempt`, counting from 1, or `None` once
+    /// attempts are exhausted.
+    pub(crate) fn delay(&self, attempt: u32) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+        let factor = self.multiplier.powi(attempt.saturating_sub(1) as i32);
+        let delay = Duration::try_from_secs_f64(self.initial_delay.as_secs_f64() * factor)
+            .unwrap_or(Duration::MAX);
+        Some(delay.min(self.max_delay))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::exponential(Duration::from_millis(100))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delays_grow_up_to_the_cap_until_attempts_run_out() {
+        let policy = RetryPolicy::exponential(Duration::from_secs(1))
+            .max_attempts(5)
+            .max_delay(Duration::from_secs(5));
+        let delays: Vec<_> = (1..=5).map(|attempt| policy.delay(attempt)).collect();
+        let secs = |s| Some(Duration::from_secs(s));
+        assert_eq!(delays, vec![secs(1), secs(2), secs(4), secs(5), None]);
+    }
+}
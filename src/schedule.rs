@@ -0,0 +1,48 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use futures::future::{AbortHandle, Abortable};
+
+/// Cancels an action scheduled for later dispatch.
+///
+/// Dropping the handle does not cancel the scheduled action.
+#[derive(Clone, Debug)]
+pub struct ScheduleHandle {
+    abort: AbortHandle,
+    finished: Arc<AtomicBool>,
+}
+
+impl ScheduleHandle {
+    /// Cancels the scheduled dispatch, if it has not happened yet.
+    pub fn cancel(&self) {
+        self.abort.abort();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.abort.is_aborted()
+    }
+
+    /// Whether the schedule ran to completion: the delayed action was
+    /// dispatched, or the store went away.
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Acquire)
+    }
+}
+
+/// Spawns `task` on the store executor, returning a handle which aborts it.
+pub(crate) fn spawn_cancellable<F>(task: F) -> ScheduleHandle
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let (abort, registration) = AbortHandle::new_pair();
+    let finished = Arc::new(AtomicBool::new(false));
+    let done = finished.clone();
+    let task = Abortable::new(task, registration);
+    any_spawner::Executor::spawn(async move {
+        if task.await.is_ok() {
+            done.store(true, Ordering::Release);
+        }
+    });
+    ScheduleHandle { abort, finished }
+}
@@ -0,0 +1,87 @@
+use crate::dispatcher::Dispatcher;
+use crate::lens::Lens;
+use crate::prism::Prism;
+use crate::reader::Reader;
+use crate::subscription::Subscription;
+use crate::{Action, Dispatch, Read, Value};
+
+/// A view of a [`Store`](crate::Store) narrowed to a part of its state and a
+/// case of its action type.
+///
+/// Returned by [`Store::scope`](crate::Store::scope). A feature can be written
+/// against its own state and action types and handed a `ScopedStore`: it
+/// reads its slice of the app state, and the actions it dispatches are
+/// wrapped into app actions on their way to the store.
+pub struct ScopedStore<S: Value, A: Action> {
+    reader: Reader<S>,
+    dispatcher: Dispatcher<A>,
+}
+
+impl<S: Value, A: Action> ScopedStore<S, A> {
+    pub(crate) fn new(reader: Reader<S>, dispatcher: Dispatcher<A>) -> Self {
+        ScopedStore { reader, dispatcher }
+    }
+
+    /// Returns a new `Reader<S>` over the scoped state with no connections.
+    pub fn reader(&self) -> Reader<S> {
+        self.reader.clone()
+    }
+
+    /// Returns a `Reader<T>` over the part of the scoped state focused by
+    /// `lens`.
+    pub fn reader_at<T: Value>(&self, lens: impl Into<Lens<S, T>>) -> Reader<T> {
+        let lens = lens.into();
+        self.reader.map(move |s| lens.get(&s).clone())
+    }
+
+    /// Returns a cloneable handle which dispatches scoped actions.
+    pub fn dispatcher(&self) -> Dispatcher<A> {
+        self.dispatcher.clone()
+    }
+
+    /// Narrows this view further. See [`Store::scope`](crate::Store::scope).
+    pub fn scope<T, B>(
+        &self,
+        state: impl Into<Lens<S, T>>,
+        action: Prism<A, B>,
+    ) -> ScopedStore<T, B>
+    where
+        T: Value,
+        B: Action,
+    {
+        ScopedStore::new(
+            self.reader_at(state),
+            self.dispatcher.map(move |b| action.embed(b)),
+        )
+    }
+}
+
+impl<S: Value, A: Action> Read<S> for ScopedStore<S, A> {
+    fn get(&self) -> S {
+        self.reader.get()
+    }
+
+    fn watch<F: Fn(&S) + Send + Sync + 'static>(&self, f: F) -> Subscription {
+        self.reader.watch(f)
+    }
+
+    fn bind<F: Fn(&S) + Send + Sync + 'static>(&self, f: F) -> Subscription {
+        self.reader.bind(f)
+    }
+
+    fn unbind(&self) {
+        self.reader.unbind();
+    }
+}
+
+impl<S: Value, A: Action> Dispatch<A> for ScopedStore<S, A> {
+    fn dispatch(&self, action: A) {
+        self.dispatcher.dispatch(action);
+    }
+}
+
+impl<S: Value, A: Action> Clone for ScopedStore<S, A> {
+    fn clone(&self) -> Self {
+        ScopedStore::new(self.reader.clone(), self.dispatcher.clone())
+    }
+}
@@ -0,0 +1,48 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::Value;
+use crate::node::ReadableNode;
+
+type Family<K, T> = HashMap<K, Weak<dyn ReadableNode<T>>>;
+
+/// Nodes created by parameterized selectors, keyed by the selector's type and
+/// its argument, so that asking again for the same selection reuses the node.
+/// Selectors are zero-sized, so that their type says all they compute.
+///
+/// Only weak references are kept: a node lives as long as some reader over it.
+#[derive(Default)]
+pub(crate) struct SelectorCache {
+    families: Mutex<HashMap<TypeId, Box<dyn Any + Send + Sync>>>,
+}
+
+impl SelectorCache {
+    /// Returns the live node cached for selector `F` and `key`, or caches the
+    /// one returned by `make`.
+    pub(crate) fn get_or_insert<F, K, T>(
+        &self,
+        key: K,
+        make: impl FnOnce(K) -> Arc<dyn ReadableNode<T>>,
+    ) -> Arc<dyn ReadableNode<T>>
+    where
+        F: 'static,
+        K: Hash + Eq + Clone + Send + Sync + 'static,
+        T: Value,
+    {
+        let mut families = self.families.lock().unwrap();
+        let family = families
+            .entry(TypeId::of::<(F, K)>())
+            .or_insert_with(|| Box::new(Family::<K, T>::new()))
+            .downcast_mut::<Family<K, T>>()
+            .expect("one family per selector and key type");
+        if let Some(node) = family.get(&key).and_then(Weak::upgrade) {
+            return node;
+        }
+        family.retain(|_, node| node.strong_count() > 0);
+        let node = make(key.clone());
+        family.insert(key, Arc::downgrade(&node));
+        node
+    }
+}
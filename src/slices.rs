@@ -0,0 +1,79 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::Value;
+use crate::node::{ReadableNode, SourceNode};
+use crate::tap::Tap;
+
+/// The state slices injected into a store after it was created, by key.
+#[derive(Default)]
+pub(crate) struct Slices {
+    slices: Mutex<HashMap<Arc<str>, Arc<dyn Any + Send + Sync>>>,
+}
+
+impl Slices {
+    /// Returns the slice under `key`, or `None` if there is none or it holds
+    /// another type.
+    pub(crate) fn get<T: Value>(&self, key: &str) -> Option<Arc<SourceNode<T>>> {
+        let slices = self.slices.lock().unwrap();
+        slices.get(key)?.clone().downcast().ok()
+    }
+
+    /// Returns the slice under `key`, creating it with `make` if there is
+    /// none, along with whether it was created.
+    ///
+    /// Panics if the existing slice holds another type.
+    pub(crate) fn get_or_insert<T: Value>(
+        &self,
+        key: Arc<str>,
+        make: impl FnOnce() -> Arc<SourceNode<T>>,
+    ) -> (Arc<SourceNode<T>>, bool) {
+        let mut slices = self.slices.lock().unwrap();
+        if let Some(slice) = slices.get(&key) {
+            let slice = slice.clone().downcast().unwrap_or_else(|_| {
+                panic!("slice {key:?} was injected with another type");
+            });
+            return (slice, false);
+        }
+        let slice = make();
+        slices.insert(key, slice.clone());
+        (slice, true)
+    }
+}
+
+/// Reduces an injected slice with every action reduced by the store. The new
+/// slice value is published once the store state has been updated.
+pub(crate) struct SliceTap<T: Value, R> {
+    slice: Arc<SourceNode<T>>,
+    reducer: R,
+    pending: Option<T>,
+}
+
+impl<T: Value, R> SliceTap<T, R> {
+    pub(crate) fn new(slice: Arc<SourceNode<T>>, reducer: R) -> Self {
+        SliceTap {
+            slice,
+            reducer,
+            pending: None,
+        }
+    }
+}
+
+impl<S, T, A, R> Tap<S, A> for SliceTap<T, R>
+where
+    S: Value,
+    T: Value,
+    R: FnMut(T, &A) -> T + Send,
+{
+    fn before(&mut self, action: &A, _: &SourceNode<S>) -> bool {
+        self.pending = Some((self.reducer)(self.slice.get(), action));
+        true
+    }
+
+    fn after(&mut self, _: &SourceNode<S>) {
+        if let Some(value) = self.pending.take() {
+            self.slice.set(value);
+        }
+    }
+}
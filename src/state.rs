@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use crate::node::{ReadableNode, SourceNode};
 use crate::reader::Reader;
+use crate::subscription::Subscription;
 use crate::{Read, Value, Write};
 
 pub struct State<T: Value> {
@@ -32,14 +33,12 @@ impl<T: Value> Read<T> for State<T> {
         self.reader.get()
     }
 
-    fn watch<F: Fn(&T) + Send + Sync + 'static>(&self, f: F) -> &Self {
-        self.reader.watch(f);
-        self
+    fn watch<F: Fn(&T) + Send + Sync + 'static>(&self, f: F) -> Subscription {
+        self.reader.watch(f)
     }
 
-    fn bind<F: Fn(&T) + Send + Sync + 'static>(&self, f: F) -> &Self {
-        self.reader.bind(f);
-        self
+    fn bind<F: Fn(&T) + Send + Sync + 'static>(&self, f: F) -> Subscription {
+        self.reader.bind(f)
     }
 
     fn unbind(&self) {
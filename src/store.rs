@@ -1,53 +1,430 @@
+use std::collections::HashMap;
+use std::future::{Future, poll_fn};
+use std::hash::Hash;
 use std::marker::PhantomData;
-use std::sync::Arc;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::{Arc, Mutex};
+use std::task::{Poll, Waker};
+use std::time::Duration;
 
-use futures::StreamExt;
-use futures::channel::mpsc::{Sender, channel};
+use futures::channel::mpsc::unbounded;
+use futures::stream::{PollNext, select_with_strategy};
+use futures::{FutureExt, Stream, StreamExt};
+use web_time::Instant;
 
-use crate::node::{ReadableNode, SourceNode};
+use crate::activity::{Activity, Shutdown};
+use crate::arc_state::ArcState;
+use crate::changes::Changes;
+use crate::channel::{ChannelSender, channel};
+use crate::dispatcher::{ChannelSink, DispatchError, Dispatcher, OverflowPolicy};
+use crate::event_log::{EventLog, EventSourcing};
+use crate::keyed::{CollectionDiff, Differ, KeyedReaders};
+use crate::keyed_effects::{Concurrency, EffectKey, KeyedEffects};
+use crate::latest::Latest;
+use crate::lens::Lens;
+use crate::metrics::StoreMetrics;
+use crate::middleware::{self, Middleware};
+use crate::node::{ReadableNode, RegionNode, SourceNode, WatchSlot};
+use crate::panic::{self, Panic, PanicHook, PanicOrigin};
+#[cfg(feature = "persist")]
+use crate::persist::{Rehydration, RehydrationError};
+use crate::prism::Prism;
+use crate::projection::{Projection, ProjectionNode};
 use crate::reader::Reader;
+use crate::region::Changed;
+use crate::schedule::ScheduleHandle;
+use crate::scope::ScopedStore;
+use crate::selector_cache::SelectorCache;
+use crate::slices::{SliceTap, Slices};
+use crate::subscription::{Connection, Subscription};
+use crate::supervisor::Supervisor;
+use crate::tap::{Inspector, Taps};
+use crate::time::{Clock, SystemClock};
+use crate::trace;
 use crate::{
-    Action, Context, Deps, Dispatch, Effect, EffectReducer, Read, Reducer, Value,
-    handle_dispatch_result,
+    Action, Context, Crdt, Deps, DepsReducer, Dispatch, Effect, EffectReducer, Read, Reducer, Value,
 };
 
 pub struct Store<S: Value, A: Action, D: Deps = ()> {
     source: Arc<SourceNode<S>>,
     self_reader: Reader<S>,
-    sender: Sender<A>,
+    sender: ChannelSender<Command<S, A>>,
+    priority: ChannelSender<Command<S, A>>,
+    dispatcher: Dispatcher<A>,
     deps: D,
+    clock: Arc<dyn Clock>,
+    activity: Activity,
+    taps: Taps<S, A>,
+    selectors: SelectorCache,
+    slices: Slices,
+    keyed_effects: Arc<KeyedEffects>,
+    #[cfg(feature = "persist")]
+    rehydration: Vec<Arc<SourceNode<Rehydration>>>,
+}
+
+/// Messages processed, in order, by the reducer task.
+pub(crate) enum Command<S, A> {
+    /// Reduce an action.
+    Action(A),
+    /// Reduce several actions, notifying watchers once at the end.
+    Batch(Vec<A>),
+    /// Replace the state wholesale, bypassing the reducer.
+    Replace(S),
+    /// Replace the state with one computed from it, bypassing the reducer.
+    Update(Update<S>),
+}
+
+pub(crate) type Update<S> = Box<dyn FnOnce(&S) -> S + Send>;
+
+/// Resolves with the first state of `source`, starting from the current one,
+/// which satisfies `predicate`. See [`Store::wait_for`].
+pub(crate) fn wait_for<S, F>(
+    source: &Arc<SourceNode<S>>,
+    predicate: F,
+) -> impl Future<Output = S> + Send + use<S, F>
+where
+    S: Value,
+    F: Fn(&S) -> bool + Send + Sync + 'static,
+{
+    struct Waiting<S> {
+        found: Option<S>,
+        waker: Option<Waker>,
+    }
+
+    let waiting = Arc::new(Mutex::new(Waiting {
+        found: None,
+        waker: None,
+    }));
+    let predicate = Arc::new(predicate);
+    let (connection, alive) = Connection::new();
+    let slot = Arc::downgrade(&waiting);
+    let check = predicate.clone();
+    // Watch before checking the current state, so that no change is missed.
+    source.add_arc_watcher(WatchSlot {
+        alive,
+        callback: Arc::new(move |state: &Arc<S>| {
+            let Some(waiting) = slot.upgrade() else {
+                return;
+            };
+            if !check(state) {
+                return;
+            }
+            let mut guard = waiting.lock().unwrap();
+            if guard.found.is_none() {
+                guard.found = Some(S::clone(state));
+                if let Some(waker) = guard.waker.take() {
+                    waker.wake();
+                }
+            }
+        }),
+    });
+    let current = source.get_arc();
+    if predicate(&current) {
+        waiting.lock().unwrap().found = Some(S::clone(&current));
+    }
+    poll_fn(move |cx| {
+        let _watching = &connection;
+        let mut guard = waiting.lock().unwrap();
+        match guard.found.take() {
+            Some(state) => Poll::Ready(state),
+            None => {
+                guard.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    })
+}
+
+/// Combines the statuses of several `Persist`s: pending while any is, and
+/// failed on the slices of every one which failed.
+#[cfg(feature = "persist")]
+fn rehydration(statuses: &[Arc<SourceNode<Rehydration>>]) -> Rehydration {
+    let mut failed = Vec::new();
+    for status in statuses {
+        match &*status.get_arc() {
+            Rehydration::Pending => return Rehydration::Pending,
+            Rehydration::Done(Err(e)) => failed.extend(e.keys.iter().cloned()),
+            Rehydration::Done(Ok(())) => {}
+        }
+    }
+    if failed.is_empty() {
+        Rehydration::Done(Ok(()))
+    } else {
+        Rehydration::Done(Err(RehydrationError { keys: failed }))
+    }
+}
+
+/// Returns a dispatcher which forwards actions into the reducer task.
+fn dispatcher<S: Value, A: Action>(
+    sender: &ChannelSender<Command<S, A>>,
+    priority: &ChannelSender<Command<S, A>>,
+    clock: &Arc<dyn Clock>,
+) -> Dispatcher<A> {
+    let sink = ChannelSink::new(sender.clone(), Command::Action).with_priority(priority.clone());
+    Dispatcher::new(Arc::new(sink), clock.clone())
+}
+
+/// Applies one command to `source`, collecting the effects to run.
+///
+/// A panic in the reducer abandons the rest of the command and is returned,
+/// so that the reducer task keeps running.
+fn apply<S, A, D, R>(
+    store: &str,
+    reducer: &mut R,
+    source: &SourceNode<S>,
+    taps: &Taps<S, A>,
+    command: Command<S, A>,
+    effects: &mut Vec<Effect<A, D>>,
+) -> Result<(), Panic>
+where
+    S: Value,
+    A: Action,
+    D: Deps,
+    R: Reduction<S, A, D>,
+{
+    let mut reduce = |action: A| {
+        let _span = trace::reduce(store, &action);
+        taps.before(&action, source);
+        effects.push(reducer.reduce(source, action));
+        taps.after(source);
+    };
+    let result = catch_unwind(AssertUnwindSafe(|| match command {
+        Command::Action(action) => reduce(action),
+        Command::Batch(actions) => source.batch(|| actions.into_iter().for_each(&mut reduce)),
+        Command::Replace(state) => source.set(state),
+        Command::Update(update) => source.set(update(&source.get_arc())),
+    }));
+    result.map_err(|payload| Panic::new(PanicOrigin::Reducer, payload))
+}
+
+/// Construction options shared by the `Store` constructors and `StoreBuilder`.
+struct Options {
+    /// `None` for an unbounded queue.
+    capacity: Option<usize>,
+    clock: Arc<dyn Clock>,
+    /// Notify once per drained run of queued commands instead of once per
+    /// action.
+    coalesce: bool,
+    on_panic: Option<PanicHook>,
+    supervisor: Option<Supervisor>,
+    name: Arc<str>,
+    overflow: OverflowPolicy,
+    concurrency: HashMap<EffectKey, Concurrency>,
+    /// The status of each `Persist` attached with the builder.
+    #[cfg(feature = "persist")]
+    rehydration: Vec<Arc<SourceNode<Rehydration>>>,
+}
+
+/// Upper bound on the commands coalesced into one notification, so that a
+/// steady stream of dispatches cannot postpone notifications forever.
+const MAX_COALESCED: usize = 1024;
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            capacity: Some(128),
+            clock: Arc::new(SystemClock),
+            coalesce: false,
+            on_panic: None,
+            supervisor: None,
+            name: Arc::from("store"),
+            overflow: OverflowPolicy::default(),
+            concurrency: HashMap::new(),
+            #[cfg(feature = "persist")]
+            rehydration: Vec::new(),
+        }
+    }
+}
+
+/// How the reducer task applies an action to the store state.
+trait Reduction<S: Value, A: Action, D: Deps>: Send + 'static {
+    fn reduce(&mut self, source: &SourceNode<S>, action: A) -> Effect<A, D>;
+}
+
+/// Reducers which take the state by value; changes are detected by comparing
+/// the new state against the old one.
+struct ByValue<R>(R);
+
+impl<S: Value, A: Action, D: Deps, R: EffectReducer<S, A, D>> Reduction<S, A, D> for ByValue<R> {
+    fn reduce(&mut self, source: &SourceNode<S>, action: A) -> Effect<A, D> {
+        let (new_state, effect) = (self.0)(source.get(), action);
+        source.set(new_state);
+        effect
+    }
+}
+
+/// The reducer of a builder made by
+/// [`builder_with_regions`](Store::builder_with_regions): it mutates the state
+/// in place and declares the regions it touched.
+pub struct InPlace<R>(R);
+
+impl<S, A, D, R> Reduction<S, A, D> for InPlace<R>
+where
+    S: Value,
+    A: Action,
+    D: Deps,
+    R: FnMut(&mut S, A) -> Changed + Send + 'static,
+{
+    fn reduce(&mut self, source: &SourceNode<S>, action: A) -> Effect<A, D> {
+        source.modify(|state| (self.0)(state, action));
+        Effect::none()
+    }
+}
+
+/// The reducer of a builder made by
+/// [`builder_fallible`](Store::builder_fallible): it may reject an action,
+/// which leaves the state untouched and hands its error to `on_error`.
+pub struct Fallible<R, H> {
+    reducer: R,
+    on_error: H,
+}
+
+/// The error handler of a fallible builder which was given none.
+type DropError<E> = fn(E);
+
+impl<S, A, D, E, R, H> Reduction<S, A, D> for Fallible<R, H>
+where
+    S: Value,
+    A: Action,
+    D: Deps,
+    R: Fn(S, A) -> Result<S, E> + Send + 'static,
+    H: Fn(E) + Send + 'static,
+{
+    fn reduce(&mut self, source: &SourceNode<S>, action: A) -> Effect<A, D> {
+        match (self.reducer)(source.get(), action) {
+            Ok(state) => source.set(state),
+            Err(error) => (self.on_error)(error),
+        }
+        Effect::none()
+    }
 }
 
 impl<S: Value, A: Action> Store<S, A, ()> {
     pub fn new<R: Reducer<S, A>>(state: S, reducer: R) -> Self {
-        let effect_reducer =
-            move |s: S, a: A| -> (S, Effect<A, ()>) { (reducer(s, a), Effect::none()) };
-        Store::<S, A, ()>::new_with_deps_and_capacity(state, effect_reducer, (), 128)
+        Self::builder(state, reducer).build()
     }
 
     pub fn new_with_capacity<R: Reducer<S, A>>(state: S, reducer: R, capacity: usize) -> Self {
-        let effect_reducer =
-            move |s: S, a: A| -> (S, Effect<A, ()>) { (reducer(s, a), Effect::none()) };
-        Store::<S, A, ()>::new_with_deps_and_capacity(state, effect_reducer, (), capacity)
+        Self::builder(state, reducer).capacity(capacity).build()
+    }
+
+    /// Creates a store with an unbounded queue: dispatching never drops
+    /// actions, at the cost of unbounded memory growth if producers outpace
+    /// the reducer.
+    pub fn new_unbounded<R: Reducer<S, A>>(state: S, reducer: R) -> Self {
+        Self::builder(state, reducer).unbounded().build()
+    }
+
+    /// Creates a store whose reducer mutates the state in place and reports
+    /// which [`Region`](crate::Region)s it touched.
+    ///
+    /// The state is neither cloned nor compared to detect changes: watchers
+    /// fire whenever the reducer reports any change, and readers created with
+    /// [`reader_in_region`](Store::reader_in_region) only re-evaluate when one
+    /// of their regions is reported.
+    ///
+    /// A panicking reducer leaves the state as it left it, unless the store
+    /// is [supervised](StoreBuilder::supervise), which rolls it back.
+    pub fn new_with_regions<R>(state: S, reducer: R) -> Self
+    where
+        R: FnMut(&mut S, A) -> Changed + Send + 'static,
+    {
+        Self::builder_with_regions(state, reducer).build()
+    }
+
+    /// Like [`builder`](Store::builder), for reducers which mutate the state
+    /// in place. See [`new_with_regions`](Store::new_with_regions).
+    ///
+    /// In-place reducers cannot be wrapped, so middlewares and persistence
+    /// are not available on the builder.
+    pub fn builder_with_regions<R>(state: S, reducer: R) -> StoreBuilder<S, A, InPlace<R>, ()>
+    where
+        R: FnMut(&mut S, A) -> Changed + Send + 'static,
+    {
+        StoreBuilder::from_parts(state, InPlace(reducer), ())
+    }
+
+    /// Creates a store whose reducer mutates the state in place.
+    ///
+    /// Large states are neither moved through the reducer nor cloned to
+    /// reduce an action, and the reducer may keep mutable state of its own.
+    /// Every action is treated as a change; readers still only notify when
+    /// their selected value changes.
+    pub fn new_mut<R>(state: S, mut reducer: R) -> Self
+    where
+        R: FnMut(&mut S, A) + Send + 'static,
+    {
+        Self::builder_with_regions(state, move |s: &mut S, a: A| {
+            reducer(s, a);
+            Changed::ALL
+        })
+        .build()
+    }
+
+    /// Creates a store whose reducer may reject actions.
+    ///
+    /// When the reducer returns an error the state is left as it was, watchers
+    /// are not notified, and the error is passed to `on_error` on the reducer
+    /// task.
+    pub fn new_fallible<E, R, H>(state: S, reducer: R, on_error: H) -> Self
+    where
+        R: Fn(S, A) -> Result<S, E> + Send + 'static,
+        H: Fn(E) + Send + 'static,
+    {
+        Self::builder_fallible(state, reducer)
+            .on_error(on_error)
+            .build()
+    }
+
+    /// Like [`builder`](Store::builder), for reducers which may reject
+    /// actions. Errors are dropped unless a handler is set with
+    /// [`on_error`](StoreBuilder::on_error). See
+    /// [`new_fallible`](Store::new_fallible).
+    pub fn builder_fallible<E, R>(
+        state: S,
+        reducer: R,
+    ) -> StoreBuilder<S, A, Fallible<R, DropError<E>>, ()>
+    where
+        R: Fn(S, A) -> Result<S, E> + Send + 'static,
+    {
+        let on_error: DropError<E> = drop;
+        StoreBuilder::from_parts(state, Fallible { reducer, on_error }, ())
     }
 
     pub fn builder<R: Reducer<S, A>>(
         state: S,
         reducer: R,
     ) -> StoreBuilder<S, A, impl EffectReducer<S, A, ()>, ()> {
-        StoreBuilder {
-            state,
-            reducer: move |s: S, a: A| -> (S, Effect<A, ()>) { (reducer(s, a), Effect::none()) },
-            deps: (),
-            capacity: 128,
-            _action: PhantomData,
-        }
+        let reducer = move |s: S, a: A| -> (S, Effect<A, ()>) { (reducer(s, a), Effect::none()) };
+        StoreBuilder::from_parts(state, reducer, ())
+    }
+}
+
+impl<T, A: Action> Store<ArcState<T>, A, ()>
+where
+    T: Clone + PartialEq + Send + Sync + 'static,
+{
+    /// Creates a store over copy-on-write [`ArcState`].
+    ///
+    /// The reducer receives `&mut T` obtained through [`Arc::make_mut`]: when
+    /// no snapshot of the state is held elsewhere it is mutated in place, and
+    /// otherwise it is copied exactly once. Every action is treated as a
+    /// change; readers still only notify when their selected value changes.
+    pub fn new_arc<R>(state: T, reducer: R) -> Self
+    where
+        R: Fn(&mut T, A) + Send + 'static,
+    {
+        Self::builder_with_regions(ArcState::new(state), move |s: &mut ArcState<T>, a: A| {
+            reducer(s.make_mut(), a);
+            Changed::ALL
+        })
+        .build()
     }
 }
 
 impl<S: Value, A: Action, D: Deps> Store<S, A, D> {
     pub fn new_with_deps<R: EffectReducer<S, A, D>>(state: S, reducer: R, deps: D) -> Self {
-        Self::new_with_deps_and_capacity(state, reducer, deps, 128)
+        Self::builder_with_deps(state, reducer, deps).build()
     }
 
     pub fn builder_with_deps<R: EffectReducer<S, A, D>>(
@@ -55,13 +432,25 @@ impl<S: Value, A: Action, D: Deps> Store<S, A, D> {
         reducer: R,
         deps: D,
     ) -> StoreBuilder<S, A, R, D> {
-        StoreBuilder {
-            state,
-            reducer,
-            deps,
-            capacity: 128,
-            _action: PhantomData,
-        }
+        StoreBuilder::from_parts(state, reducer, deps)
+    }
+
+    /// Creates a store whose reducer also receives the deps, so that it can
+    /// read configuration without keeping it in the state or deferring to an
+    /// effect.
+    pub fn new_with_deps_reducer<R: DepsReducer<S, A, D>>(state: S, reducer: R, deps: D) -> Self {
+        Self::builder_with_deps_reducer(state, reducer, deps).build()
+    }
+
+    /// Like [`builder_with_deps`](Store::builder_with_deps), for reducers
+    /// which receive the deps. See [`new_with_deps_reducer`](Store::new_with_deps_reducer).
+    pub fn builder_with_deps_reducer<R: DepsReducer<S, A, D>>(
+        state: S,
+        reducer: R,
+        deps: D,
+    ) -> StoreBuilder<S, A, impl EffectReducer<S, A, D>, D> {
+        let own = deps.clone();
+        Self::builder_with_deps(state, move |s: S, a: A| reducer(s, a, &own), deps)
     }
 
     pub fn new_with_deps_and_capacity<R: EffectReducer<S, A, D>>(
@@ -70,52 +459,262 @@ impl<S: Value, A: Action, D: Deps> Store<S, A, D> {
         deps: D,
         capacity: usize,
     ) -> Self {
+        Self::builder_with_deps(state, reducer, deps)
+            .capacity(capacity)
+            .build()
+    }
+
+    fn new_with_options<R: Reduction<S, A, D>>(
+        state: S,
+        mut reducer: R,
+        deps: D,
+        options: Options,
+    ) -> Self {
+        let Options {
+            capacity,
+            clock,
+            coalesce,
+            on_panic,
+            mut supervisor,
+            name,
+            overflow,
+            concurrency,
+            #[cfg(feature = "persist")]
+            rehydration,
+        } = options;
         let source = SourceNode::new(state);
         let self_reader: Reader<S> = Reader::new(source.clone() as Arc<dyn ReadableNode<S>>);
-        let (sender, mut receiver) = channel(capacity);
+        let activity = Activity::new(name);
+        let (sender, receiver) = channel(capacity, activity.clone());
+        let (priority, priority_receiver) = channel(capacity, activity.clone());
+        let (sender, priority) = (
+            sender.with_overflow(overflow),
+            priority.with_overflow(overflow),
+        );
+        // Always poll the priority lane first; fall back to the normal queue
+        // only when it is empty.
+        let mut receiver =
+            select_with_strategy(priority_receiver, receiver, |_: &mut ()| PollNext::Left);
         let reducer_source = source.clone();
-        let effect_sender = sender.clone();
+        let effect_dispatcher = dispatcher(&sender, &priority, &clock);
         let deps_for_task = deps.clone();
+        let clock_for_task = clock.clone();
+        let keyed_effects = Arc::new(KeyedEffects::new(concurrency));
+        let keyed_for_task = keyed_effects.clone();
+        let state_for_task = source.clone();
+        let task_activity = activity.clone();
+        let taps = Taps::new(on_panic.clone());
+        let task_taps = taps.clone();
         any_spawner::Executor::spawn(async move {
-            while let Some(action) = receiver.next().await {
-                let current = reducer_source.get();
-                let (new_state, effect) = reducer(current, action);
-                reducer_source.set(new_state);
-
-                let ctx = {
-                    let sender = effect_sender.clone();
-                    let deps = deps_for_task.clone();
-                    Context {
-                        dispatcher: Arc::new(move |action: A| {
-                            let mut s = sender.clone();
-                            let result = s.try_send(action);
-                            handle_dispatch_result(result);
-                        }),
-                        deps,
+            let run = |effect: Effect<A, D>| {
+                let ctx = Context {
+                    dispatcher: effect_dispatcher.clone(),
+                    deps: deps_for_task.clone(),
+                    clock: clock_for_task.clone(),
+                    keyed_effects: keyed_for_task.clone(),
+                    state: state_for_task.clone(),
+                };
+                effect.run(ctx, on_panic.clone(), task_activity.effect());
+            };
+            let supervised = supervisor.is_some();
+            while let Some(command) = receiver.next().await {
+                let mut effects = Vec::new();
+                let mut step = |command| {
+                    // A pointer copy, but one an in-place reducer has to copy
+                    // the state around to mutate it.
+                    let known_good = supervised.then(|| reducer_source.get_arc());
+                    let kept = effects.len();
+                    let started = Instant::now();
+                    let result = apply(
+                        task_activity.name(),
+                        &mut reducer,
+                        &reducer_source,
+                        &task_taps,
+                        command,
+                        &mut effects,
+                    );
+                    task_activity.reduced(started.elapsed());
+                    if let Err(panic) = &result {
+                        panic::report(on_panic.as_ref(), panic.clone());
+                        if let Some(known_good) = known_good {
+                            reducer_source.restore(known_good);
+                            effects.truncate(kept);
+                        }
                     }
+                    result.is_ok()
                 };
-                effect.run(ctx);
+                let mut ok = true;
+                let mut processed = 1;
+                if coalesce {
+                    reducer_source.batch(|| {
+                        ok = step(command);
+                        for _ in 1..MAX_COALESCED {
+                            if !ok {
+                                break;
+                            }
+                            match receiver.next().now_or_never() {
+                                Some(Some(command)) => {
+                                    processed += 1;
+                                    ok = step(command);
+                                }
+                                _ => break,
+                            }
+                        }
+                    });
+                } else {
+                    ok = step(command);
+                }
+                effects.into_iter().for_each(run);
+                task_activity.processed(processed);
+                if let (false, Some(supervisor)) = (ok, &mut supervisor) {
+                    match supervisor.restart(clock_for_task.now()) {
+                        Some(backoff) if backoff.is_zero() => {}
+                        Some(backoff) => clock_for_task.sleep(backoff).await,
+                        None => break,
+                    }
+                }
             }
+            drop(receiver);
+            task_activity.finish();
         });
         Self {
             source,
             self_reader,
+            dispatcher: dispatcher(&sender, &priority, &clock),
             sender,
+            priority,
             deps,
+            clock,
+            activity,
+            taps,
+            selectors: SelectorCache::default(),
+            slices: Slices::default(),
+            keyed_effects,
+            #[cfg(feature = "persist")]
+            rehydration,
+        }
+    }
+
+    /// The name given with [`StoreBuilder::name`], or `"store"`.
+    pub fn name(&self) -> &str {
+        self.activity.name()
+    }
+
+    /// Returns a snapshot of the store's queue depth, command counts and
+    /// reduce latencies.
+    pub fn metrics(&self) -> StoreMetrics {
+        self.activity.metrics()
+    }
+
+    /// Wraps a watcher callback so that it runs in a `watch` span.
+    fn traced<T, F: Fn(&T) + Send + Sync + 'static>(
+        &self,
+        f: F,
+    ) -> impl Fn(&T) + Send + Sync + 'static {
+        let name = self.activity.name().clone();
+        move |value| {
+            let _span = trace::watch(&name);
+            f(value)
         }
     }
 
     /// Returns a `Context<A, D>` that dispatches into this store.
     pub fn context(&self) -> Context<A, D> {
-        let deps = self.deps.clone();
-        let sender = self.sender.clone();
         Context {
-            dispatcher: Arc::new(move |action: A| {
-                let mut s = sender.clone();
-                let result = s.try_send(action);
-                handle_dispatch_result(result);
-            }),
-            deps,
+            dispatcher: self.dispatcher.clone(),
+            deps: self.deps.clone(),
+            clock: self.clock.clone(),
+            keyed_effects: self.keyed_effects.clone(),
+            state: self.source.clone(),
+        }
+    }
+
+    /// Returns a cloneable handle which dispatches into this store.
+    pub fn dispatcher(&self) -> Dispatcher<A> {
+        self.dispatcher.clone()
+    }
+
+    /// Dispatches the action returned by `f` every `interval` on the store's
+    /// clock. See [`Dispatcher::schedule_every`].
+    pub fn schedule_every<F>(&self, interval: Duration, f: F) -> ScheduleHandle
+    where
+        F: Fn() -> A + Send + 'static,
+    {
+        self.dispatcher.schedule_every(interval, f)
+    }
+
+    /// Queues `actions` to be reduced back to back as one unit.
+    ///
+    /// Watchers and readers are notified once, with the state after the last
+    /// action, so multi-step updates never expose intermediate states. Effects
+    /// returned by the reducer run after the whole batch has been applied.
+    pub fn dispatch_batch(&self, actions: impl IntoIterator<Item = A>) {
+        let actions: Vec<A> = actions.into_iter().collect();
+        if actions.is_empty() {
+            return;
+        }
+        self.sender.clone().dispatch(Command::Batch(actions));
+    }
+
+    /// Queues `action` on the high-priority lane.
+    ///
+    /// The reducer drains the priority lane before the normal queue, so
+    /// urgent actions such as user input overtake bulk background dispatches.
+    /// Actions within one lane keep their order.
+    pub fn dispatch_priority(&self, action: A) {
+        self.dispatcher.dispatch_priority(action);
+    }
+
+    /// Queues `action` if the queue has room, or hands it back.
+    ///
+    /// Unlike [`dispatch`](Dispatch::dispatch), which applies the
+    /// [`OverflowPolicy`] when the queue is full and silently drops actions
+    /// once the store has shut down, this reports why.
+    pub fn try_dispatch(&self, action: A) -> Result<(), DispatchError<A>> {
+        self.dispatcher.try_dispatch(action)
+    }
+
+    /// Queues `action`, waiting for room in the queue if it is full.
+    ///
+    /// Unlike [`dispatch`](Dispatch::dispatch), which applies the
+    /// [`OverflowPolicy`] when the queue is full, this applies backpressure to
+    /// the caller.
+    pub async fn dispatch_async(&self, action: A) {
+        let _ = self.sender.clone().send(Command::Action(action)).await;
+    }
+
+    pub(crate) fn clock_handle(&self) -> Arc<dyn Clock> {
+        self.clock.clone()
+    }
+
+    /// Replaces the store state wholesale, bypassing the reducer.
+    ///
+    /// The replacement is queued behind already dispatched actions; watchers
+    /// and readers are notified as for any other state change.
+    pub fn replace_state(&self, state: S) {
+        (self.state_setter())(state);
+    }
+
+    /// Merges `state`, edited by another replica, into the store state,
+    /// bypassing the reducer.
+    ///
+    /// Like [`replace_state`](Store::replace_state), the merge is queued behind
+    /// already dispatched actions, so it sees their changes. Watchers are only
+    /// notified if the merge changes the state.
+    pub fn merge(&self, state: S)
+    where
+        S: Crdt,
+    {
+        let update: Update<S> = Box::new(move |ours: &S| ours.merged(&state));
+        self.sender.clone().dispatch(Command::Update(update));
+    }
+
+    /// Returns a function which performs [`replace_state`](Store::replace_state)
+    /// independently of the store handle.
+    pub(crate) fn state_setter(&self) -> impl Fn(S) + Send + Sync + 'static {
+        let sender = self.sender.clone();
+        move |state: S| {
+            sender.clone().dispatch(Command::Replace(state));
         }
     }
 
@@ -124,6 +723,153 @@ impl<S: Value, A: Action, D: Deps> Store<S, A, D> {
         Reader::new(self.source.clone() as Arc<dyn ReadableNode<S>>)
     }
 
+    /// Like [`watch`](Read::watch), but `f` receives the shared state itself
+    /// rather than a reference to a copy of it.
+    ///
+    /// The state is never cloned to notify watchers, however large it is.
+    /// Keeping the `Arc` alive past the callback is cheap, but forces the next
+    /// in-place reduction to copy the state once. The subscription is released
+    /// on [`unbind`](Read::unbind).
+    pub fn watch_arc<F: Fn(Arc<S>) + Send + Sync + 'static>(&self, f: F) -> Subscription {
+        let (subscription, alive) = self.self_reader.connect();
+        self.source.add_arc_watcher(WatchSlot {
+            alive,
+            callback: Arc::new(self.traced(move |state: &Arc<S>| f(state.clone()))),
+        });
+        subscription
+    }
+
+    /// Resolves with the first state, starting from the current one, which
+    /// satisfies `predicate`.
+    ///
+    /// The predicate is re-checked whenever the state changes; dropping the
+    /// future stops watching.
+    pub fn wait_for<F>(&self, predicate: F) -> impl Future<Output = S> + Send + 'static
+    where
+        F: Fn(&S) -> bool + Send + Sync + 'static,
+    {
+        wait_for(&self.source, predicate)
+    }
+
+    /// Whether the slices of every [`Persist`](crate::persist::Persist)
+    /// attached with [`StoreBuilder::persist`] have been restored. Stores
+    /// without persistence are always done.
+    #[cfg(feature = "persist")]
+    pub fn rehydration(&self) -> Rehydration {
+        rehydration(&self.rehydration)
+    }
+
+    /// Resolves once the store is [rehydrated](Store::rehydration), so that
+    /// a UI can hold off rendering or dispatching until the persisted state
+    /// is in place.
+    #[cfg(feature = "persist")]
+    pub fn rehydrated(
+        &self,
+    ) -> impl Future<Output = Result<(), RehydrationError>> + Send + 'static {
+        let statuses = self.rehydration.clone();
+        let pending = statuses
+            .iter()
+            .map(|status| wait_for(status, |r| *r != Rehydration::Pending))
+            .collect::<Vec<_>>();
+        async move {
+            futures::future::join_all(pending).await;
+            match rehydration(&statuses) {
+                Rehydration::Done(result) => result,
+                Rehydration::Pending => unreachable!("rehydration never restarts"),
+            }
+        }
+    }
+
+    /// Returns a stream of the actions reduced by the store, in order.
+    ///
+    /// Every subscriber receives a copy of each action reduced after the call,
+    /// including those dispatched by effects and on the priority lane. Actions
+    /// are buffered until consumed. The stream ends once the store has shut
+    /// down.
+    pub fn actions(&self) -> impl Stream<Item = A> + Send + Unpin + 'static
+    where
+        A: Clone,
+    {
+        let (sender, receiver) = unbounded();
+        self.taps.add(sender);
+        receiver
+    }
+
+    /// Calls `f` with every action reduced by the store, together with the
+    /// state before and after reducing it.
+    ///
+    /// `f` runs synchronously on the reducer task and must not call `inspect`
+    /// or `actions` itself. If `f` panics, the panic is reported to the
+    /// store's panic hook and `f` is removed; the action is still reduced.
+    /// Keeping the previous state makes in-place reducers copy the state once
+    /// per action.
+    pub fn inspect<F>(&self, f: F) -> &Self
+    where
+        A: Clone,
+        F: FnMut(&A, &S, &S) + Send + 'static,
+    {
+        self.taps.add(Inspector::new(f));
+        self
+    }
+
+    /// Adds a slice of state under `key`, reduced by `reducer` with every
+    /// action the store reduces from now on, and returns a reader over it.
+    ///
+    /// Lets features which are loaded at runtime, such as plugins, bring
+    /// their own state without being part of `S`. Injecting a key again
+    /// returns the existing slice and ignores `initial` and `reducer`. Slices
+    /// are not part of the store state, so they are not snapshotted, persisted
+    /// or replaced with it. `reducer` runs on the reducer task and must not
+    /// call `inject_slice`, `inspect` or `actions` itself. If `reducer`
+    /// panics, the panic is reported to the store's panic hook and the slice
+    /// keeps its last value from then on.
+    ///
+    /// Panics if `key` was injected with another type.
+    pub fn inject_slice<T, R>(&self, key: impl Into<Arc<str>>, initial: T, reducer: R) -> Reader<T>
+    where
+        T: Value,
+        R: FnMut(T, &A) -> T + Send + 'static,
+    {
+        let (slice, created) = self
+            .slices
+            .get_or_insert(key.into(), || SourceNode::new(initial));
+        if created {
+            self.taps.add(SliceTap::new(slice.clone(), reducer));
+        }
+        Reader::new(slice)
+    }
+
+    /// Returns a reader over the slice injected under `key`, or `None` if
+    /// there is none of type `T`. See [`inject_slice`](Store::inject_slice).
+    pub fn slice<T: Value>(&self, key: &str) -> Option<Reader<T>> {
+        let slice: Arc<dyn ReadableNode<T>> = self.slices.get::<T>(key)?;
+        Some(Reader::new(slice))
+    }
+
+    /// Returns a conflated mailbox which always holds the latest store state.
+    pub fn latest(&self) -> Latest<S> {
+        Latest::new(self.source.as_ref())
+    }
+
+    /// Returns a stream which yields every new state, skipping states equal
+    /// to the one yielded before.
+    ///
+    /// The current state is not yielded. Unlike [`latest`](Store::latest),
+    /// no intermediate state is dropped: states are buffered until consumed.
+    /// The stream ends once the store has been dropped.
+    pub fn state_stream(&self) -> Changes<S> {
+        Changes::new(self.source.as_ref())
+    }
+
+    /// Returns the current state without cloning it.
+    ///
+    /// The store keeps its state behind an `Arc`, so this is a pointer copy.
+    /// Holding on to the snapshot is cheap, but forces the next in-place
+    /// reduction to copy the state once.
+    pub fn get_arc(&self) -> Arc<S> {
+        self.source.get_arc()
+    }
+
     /// Returns a `Reader<T>` that projects the store state through `f`.
     pub fn derived<T, F>(&self, f: F) -> Reader<T>
     where
@@ -133,14 +879,218 @@ impl<S: Value, A: Action, D: Deps> Store<S, A, D> {
         self.reader().map(move |v| f(&v))
     }
 
+    /// Like [`derived`](Store::derived), but evaluates `f` against the
+    /// stored state in place, so the state is not cloned to evaluate it.
+    ///
+    /// The state is locked while `f` runs: `f` must not access the store,
+    /// through a reader or otherwise, or it deadlocks.
+    pub fn derived_in_place<T, F>(&self, f: F) -> Reader<T>
+    where
+        T: Clone + PartialEq + Send + Sync + 'static,
+        F: Fn(&S) -> T + Send + Sync + 'static,
+    {
+        self.reader_in_region(Changed::ALL, f)
+    }
+
+    /// Returns a borrow-based [`Projection`] of the part of the state selected
+    /// by `f`. Unlike [`derived`](Store::derived), the selected value is not
+    /// cloned to decide whether it changed.
+    pub fn project<R, F>(&self, f: F) -> Projection<R>
+    where
+        R: Value,
+        F: Fn(&S) -> &R + Send + Sync + 'static,
+    {
+        Projection::new(ProjectionNode::new(self.source.clone(), f))
+    }
+
+    /// Returns a `Reader<T>` which only re-evaluates `f` when the reducer
+    /// reports a change to `regions`. See [`Store::new_with_regions`].
+    ///
+    /// Stores with by-value reducers report every change as touching all
+    /// regions. `f` is evaluated in place, as by
+    /// [`derived_in_place`](Store::derived_in_place), and must not access the
+    /// store.
+    pub fn reader_in_region<T, F>(&self, regions: impl Into<Changed>, f: F) -> Reader<T>
+    where
+        T: Value,
+        F: Fn(&S) -> T + Send + Sync + 'static,
+    {
+        Reader::new(RegionNode::new(self.source.clone(), regions.into(), f))
+    }
+
+    /// Returns a `Reader<T>` over the part of the state focused by `lens`.
+    /// See [`Lens`].
+    pub fn reader_at<T: Value>(&self, lens: impl Into<Lens<S, T>>) -> Reader<T> {
+        let lens = lens.into();
+        self.derived(move |s: &S| lens.get(s).clone())
+    }
+
+    /// Returns a view of the store narrowed to the part of the state focused
+    /// by `state` and the case of the action type selected by `action`. See
+    /// [`ScopedStore`].
+    pub fn scope<T, B>(
+        &self,
+        state: impl Into<Lens<S, T>>,
+        action: Prism<A, B>,
+    ) -> ScopedStore<T, B>
+    where
+        T: Value,
+        B: Action,
+    {
+        ScopedStore::new(
+            self.reader_at(state),
+            self.dispatcher().map(move |b| action.embed(b)),
+        )
+    }
+
+    /// Like [`derived`](Store::derived), but treats the selected value as
+    /// unchanged whenever `eq` returns `true`, instead of comparing it with
+    /// `PartialEq`.
+    ///
+    /// Use it for cheaper comparisons, such as version counters, or looser
+    /// ones, such as comparing floats within an epsilon.
+    pub fn reader_with_eq<T, F, E>(&self, f: F, eq: E) -> Reader<T>
+    where
+        T: Value,
+        F: Fn(&S) -> T + Send + Sync + 'static,
+        E: Fn(&T, &T) -> bool + Send + Sync + 'static,
+    {
+        Reader::new(RegionNode::new_with_eq(
+            self.source.clone(),
+            Changed::ALL,
+            f,
+            eq,
+        ))
+    }
+
+    /// Returns a reader over `f(state, &arg)`, reusing the selection made
+    /// earlier for the same selector and an equal `arg` while any reader over
+    /// it is alive.
+    ///
+    /// Suits per-item readers, such as one per entity ID in a list: each item
+    /// shares one memoized selection however often its view asks for it. The
+    /// cache is keyed by the type of `f`, so `f` must be a function item or a
+    /// closure capturing nothing, whose type is unique to its source; pass
+    /// anything varying through `arg` instead. Other selectors, such as
+    /// function pointers, fail to compile:
+    ///
+    /// ```compile_fail
+    /// # let store = uniflow::Store::new(vec![1, 2], |s: Vec<i32>, _: ()| s);
+    /// let first: fn(&Vec<i32>, &usize) -> i32 = |s, i| s[*i];
+    /// store.reader_with(0, first);
+    /// ```
+    pub fn reader_with<K, T, F>(&self, arg: K, f: F) -> Reader<T>
+    where
+        K: Hash + Eq + Clone + Send + Sync + 'static,
+        T: Value,
+        F: Fn(&S, &K) -> T + Send + Sync + 'static,
+    {
+        const {
+            assert!(
+                size_of::<F>() == 0,
+                "reader_with needs a selector capturing nothing; pass varying values through `arg`",
+            )
+        };
+        let node = self.selectors.get_or_insert::<F, K, T>(arg, |arg| {
+            RegionNode::new(self.source.clone(), Changed::ALL, move |s: &S| f(s, &arg))
+        });
+        Reader::new(node)
+    }
+
+    /// Returns per-item readers over the collection selected by `collection`,
+    /// identifying items by `key`. See [`KeyedReaders`].
+    pub fn reader_each<K, I, C, F>(&self, collection: C, key: F) -> KeyedReaders<K, I>
+    where
+        K: Value + Hash + Eq,
+        I: Value,
+        C: Fn(&S) -> &Vec<I> + Send + Sync + 'static,
+        F: Fn(&I) -> K + Send + Sync + 'static,
+    {
+        KeyedReaders::new(self.source.clone(), collection, key)
+    }
+
+    /// Calls `diff`'s callbacks with the items inserted into, updated in and
+    /// removed from the collection selected by `collection` whenever the
+    /// state changes, identifying items by `key`. See [`CollectionDiff`].
+    pub fn watch_diff<K, I, C, F>(
+        &self,
+        collection: C,
+        key: F,
+        diff: CollectionDiff<I>,
+    ) -> Subscription
+    where
+        K: Hash + Eq + Send + 'static,
+        I: Value,
+        C: Fn(&S) -> &Vec<I> + Send + Sync + 'static,
+        F: Fn(&I) -> K + Send + Sync + 'static,
+    {
+        let differ = self.source.with(|s| Differ::new(collection(s), &key, diff));
+        let differ = Mutex::new(differ);
+        let (subscription, alive) = self.self_reader.connect();
+        self.source.add_arc_watcher(WatchSlot {
+            alive,
+            callback: Arc::new(self.traced(move |state: &Arc<S>| {
+                differ.lock().unwrap().update(collection(state), &key);
+            })),
+        });
+        subscription
+    }
+
     pub fn commit(&self) {
         self.source.send_down();
         self.source.notify();
     }
 
-    pub fn shutdown(&self) {
-        let mut sender = self.sender.clone();
-        sender.close_channel();
+    /// Resolves once the store is quiescent: every queued action has been
+    /// reduced and every effect has completed, including the actions those
+    /// effects dispatched.
+    ///
+    /// Effects waiting on timers keep the store unsettled until they fire.
+    pub fn settle(&self) -> impl Future<Output = ()> + Send + 'static {
+        let activity = self.activity.clone();
+        poll_fn(move |cx| activity.poll_settled(cx))
+    }
+
+    /// Resolves once no effect spawned by the store is running.
+    ///
+    /// Unlike [`settle`](Store::settle), actions still queued, and the effects
+    /// they will spawn, are not waited for.
+    pub fn effects_idle(&self) -> impl Future<Output = ()> + Send + 'static {
+        let activity = self.activity.clone();
+        poll_fn(move |cx| activity.poll_effects_idle(cx))
+    }
+
+    /// Stops accepting actions and shuts the store down gracefully.
+    ///
+    /// The queues close immediately: later dispatches are dropped. Actions
+    /// already queued are still reduced, and the returned future resolves once
+    /// they have been and every effect they spawned has completed.
+    pub fn shutdown(&self) -> Shutdown {
+        self.sender.clone().close_channel();
+        self.priority.clone().close_channel();
+        Shutdown::new(self.activity.clone())
+    }
+
+    /// Shuts the store down like [`shutdown`](Store::shutdown), but aborts
+    /// its effects instead of waiting for them.
+    ///
+    /// Running effects stop at their next suspension point, and effects of the
+    /// actions still queued are aborted before they start, so that background
+    /// work such as network calls does not outlive the store.
+    pub fn shutdown_now(&self) -> Shutdown {
+        let shutdown = self.shutdown();
+        self.activity.abort_effects();
+        shutdown
+    }
+}
+
+/// Dropping the store shuts it down, as [`shutdown`](Store::shutdown) does:
+/// queued actions are still reduced, after which the reducer task exits and
+/// releases the deps. Dispatchers and contexts which outlive the store
+/// silently drop their actions.
+impl<S: Value, A: Action, D: Deps> Drop for Store<S, A, D> {
+    fn drop(&mut self) {
+        self.shutdown();
     }
 }
 
@@ -149,14 +1099,12 @@ impl<S: Value, A: Action, D: Deps> Read<S> for Store<S, A, D> {
         self.self_reader.get()
     }
 
-    fn watch<F: Fn(&S) + Send + Sync + 'static>(&self, f: F) -> &Self {
-        self.self_reader.watch(f);
-        self
+    fn watch<F: Fn(&S) + Send + Sync + 'static>(&self, f: F) -> Subscription {
+        self.self_reader.watch(self.traced(f))
     }
 
-    fn bind<F: Fn(&S) + Send + Sync + 'static>(&self, f: F) -> &Self {
-        self.self_reader.bind(f);
-        self
+    fn bind<F: Fn(&S) + Send + Sync + 'static>(&self, f: F) -> Subscription {
+        self.self_reader.bind(self.traced(f))
     }
 
     fn unbind(&self) {
@@ -166,9 +1114,7 @@ impl<S: Value, A: Action, D: Deps> Read<S> for Store<S, A, D> {
 
 impl<S: Value, A: Action, D: Deps> Dispatch<A> for Store<S, A, D> {
     fn dispatch(&self, action: A) {
-        let mut sender = self.sender.clone();
-        let result = sender.try_send(action);
-        handle_dispatch_result(result);
+        self.sender.clone().dispatch(Command::Action(action));
     }
 }
 
@@ -178,7 +1124,9 @@ pub struct StoreBuilder<S, A, R, D = ()> {
     state: S,
     reducer: R,
     deps: D,
-    capacity: usize,
+    options: Options,
+    /// Rehydrations left for the reducer task, in order.
+    deferred: Vec<Update<S>>,
     _action: PhantomData<fn(A)>,
 }
 
@@ -200,8 +1148,8 @@ where
     /// For middlewares that leave the state type unchanged, pass it through:
     /// `.wrap(|inner, state| (my_middleware(inner), state))`.
     ///
-    /// Wraps are applied left-to-right: the first `.wrap` call produces the
-    /// outermost layer that dispatched actions encounter first.
+    /// Each `.wrap` call wraps everything before it: the last `.wrap` call
+    /// produces the outermost layer that dispatched actions encounter first.
     pub fn wrap<T, B, R2, F>(self, f: F) -> StoreBuilder<T, B, R2, D>
     where
         T: Value,
@@ -209,23 +1157,266 @@ where
         R2: EffectReducer<T, B, D>,
         F: FnOnce(R, S) -> (R2, T),
     {
-        let (new_reducer, new_state) = f(self.reducer, self.state);
+        let state = self
+            .deferred
+            .into_iter()
+            .fold(self.state, |s, update| update(&s));
+        let (new_reducer, new_state) = f(self.reducer, state);
         StoreBuilder {
             state: new_state,
             reducer: new_reducer,
             deps: self.deps,
-            capacity: self.capacity,
+            options: self.options,
+            deferred: Vec::new(),
             _action: PhantomData,
         }
     }
 
-    pub fn with_capacity(mut self, capacity: usize) -> Self {
-        self.capacity = capacity;
-        self
+    /// Wraps the reducer, leaving the state and any deferred rehydration
+    /// alone.
+    fn layer<R2, F>(self, f: F) -> StoreBuilder<S, A, R2, D>
+    where
+        R2: EffectReducer<S, A, D>,
+        F: FnOnce(R) -> R2,
+    {
+        StoreBuilder {
+            state: self.state,
+            reducer: f(self.reducer),
+            deps: self.deps,
+            options: self.options,
+            deferred: self.deferred,
+            _action: PhantomData,
+        }
     }
 
+    /// Routes every action through `middleware` before it reaches the reducer.
+    ///
+    /// Middlewares layer like [`wrap`](StoreBuilder::wrap): the most recently
+    /// attached middleware is the outermost one and sees actions first.
+    pub fn middleware<M: Middleware<S, A>>(
+        self,
+        middleware: M,
+    ) -> StoreBuilder<S, A, impl EffectReducer<S, A, D>, D> {
+        self.layer(move |inner| middleware::apply(middleware, inner))
+    }
+
+    /// Rebuilds the initial state from the event log of `sourcing`, and appends
+    /// every action to it before reduction.
+    pub fn event_sourced<L: EventLog<S, A>>(
+        self,
+        sourcing: EventSourcing<L>,
+    ) -> StoreBuilder<S, A, impl EffectReducer<S, A, D>, D> {
+        self.wrap(move |inner, state| {
+            let state = sourcing.rebuild(state, &inner);
+            (middleware::apply(sourcing, inner), state)
+        })
+    }
+
+    /// Restores the initial state from `persist` and saves the persisted
+    /// slices whenever they change. The store's
+    /// [`rehydration`](Store::rehydration) reports how restoring went.
+    #[cfg(feature = "persist")]
+    pub fn persist(
+        mut self,
+        persist: crate::persist::Persist<S>,
+    ) -> StoreBuilder<S, A, impl EffectReducer<S, A, D>, D> {
+        self.options.rehydration.push(persist.status());
+        let persist = Arc::new(persist);
+        if persist.is_deferred() {
+            let p = persist.clone();
+            self.deferred
+                .push(Box::new(move |state: &S| p.rehydrate(state.clone())));
+        } else {
+            self.state = persist.rehydrate(self.state);
+        }
+        self.layer(move |inner| middleware::apply(crate::persist::Shared(persist), inner))
+    }
+
+    pub fn build(self) -> Store<S, A, D> {
+        self.build_with(ByValue)
+    }
+}
+
+impl<S, A, R> StoreBuilder<S, A, R, ()>
+where
+    S: Value,
+    A: Action,
+    R: EffectReducer<S, A, ()>,
+{
+    /// Gives the store `deps`, for effects run through its
+    /// [`context`](Store::context). The reducer's own effects need no deps.
+    ///
+    /// Reducers whose effects read the deps are built with
+    /// [`builder_with_deps`](Store::builder_with_deps) instead.
+    pub fn deps<D: Deps>(self, deps: D) -> StoreBuilder<S, A, impl EffectReducer<S, A, D>, D> {
+        let reducer = self.reducer;
+        StoreBuilder {
+            state: self.state,
+            reducer: move |s: S, a: A| {
+                let (state, effect) = reducer(s, a);
+                (state, effect.with_deps())
+            },
+            deps,
+            options: self.options,
+            deferred: self.deferred,
+            _action: PhantomData,
+        }
+    }
+}
+
+impl<S, A, R, H, D> StoreBuilder<S, A, Fallible<R, H>, D>
+where
+    S: Value,
+    A: Action,
+    D: Deps,
+{
+    /// Passes the error of every rejected action to `on_error`, on the
+    /// reducer task.
+    pub fn on_error<E, H2>(self, on_error: H2) -> StoreBuilder<S, A, Fallible<R, H2>, D>
+    where
+        R: Fn(S, A) -> Result<S, E> + Send + 'static,
+        H2: Fn(E) + Send + 'static,
+    {
+        StoreBuilder {
+            state: self.state,
+            reducer: Fallible {
+                reducer: self.reducer.reducer,
+                on_error,
+            },
+            deps: self.deps,
+            options: self.options,
+            deferred: self.deferred,
+            _action: PhantomData,
+        }
+    }
+
+    pub fn build<E>(self) -> Store<S, A, D>
+    where
+        R: Fn(S, A) -> Result<S, E> + Send + 'static,
+        H: Fn(E) + Send + 'static,
+    {
+        self.build_with(|reducer| reducer)
+    }
+}
+
+impl<S, A, R, D> StoreBuilder<S, A, InPlace<R>, D>
+where
+    S: Value,
+    A: Action,
+    D: Deps,
+    R: FnMut(&mut S, A) -> Changed + Send + 'static,
+{
     pub fn build(self) -> Store<S, A, D> {
-        Store::new_with_deps_and_capacity(self.state, self.reducer, self.deps, self.capacity)
+        self.build_with(|reducer| reducer)
+    }
+}
+
+impl<S, A, R, D> StoreBuilder<S, A, R, D> {
+    fn from_parts(state: S, reducer: R, deps: D) -> Self {
+        StoreBuilder {
+            state,
+            reducer,
+            deps,
+            options: Options::default(),
+            deferred: Vec::new(),
+            _action: PhantomData,
+        }
+    }
+
+    fn build_with<Rd, F>(self, reduction: F) -> Store<S, A, D>
+    where
+        S: Value,
+        A: Action,
+        D: Deps,
+        Rd: Reduction<S, A, D>,
+        F: FnOnce(R) -> Rd,
+    {
+        let store =
+            Store::new_with_options(self.state, reduction(self.reducer), self.deps, self.options);
+        for update in self.deferred {
+            store.sender.clone().dispatch(Command::Update(update));
+        }
+        store
+    }
+
+    /// Sets how many actions the queue holds before
+    /// [`overflow_policy`](StoreBuilder::overflow_policy) applies. Defaults
+    /// to 128.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.options.capacity = Some(capacity);
+        self
+    }
+
+    /// Same as [`capacity`](StoreBuilder::capacity).
+    pub fn with_capacity(self, capacity: usize) -> Self {
+        self.capacity(capacity)
+    }
+
+    /// Sets what [`dispatch`](Dispatch::dispatch) does with actions which do
+    /// not fit in the queue. See [`OverflowPolicy`].
+    pub fn overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.options.overflow = policy;
+        self
+    }
+
+    /// Limits how many effects made [`cancellable`](Effect::cancellable)
+    /// under `key` run at once, so that a burst of identical actions does not
+    /// stampede a backend. See [`Concurrency`].
+    pub fn concurrency<K>(mut self, key: K, policy: Concurrency) -> Self
+    where
+        K: Hash + Eq + Send + Sync + 'static,
+    {
+        self.options.concurrency.insert(EffectKey::new(key), policy);
+        self
+    }
+
+    /// Uses an unbounded queue: dispatching never drops actions, at the cost
+    /// of unbounded memory growth if producers outpace the reducer.
+    pub fn unbounded(mut self) -> Self {
+        self.options.capacity = None;
+        self
+    }
+
+    /// Coalesces notifications: every action already queued when the reducer
+    /// task wakes up is reduced before watchers and readers are notified, once,
+    /// with the final state. Useful for high-frequency dispatches such as drag
+    /// events.
+    pub fn coalesce_notifications(mut self) -> Self {
+        self.options.coalesce = true;
+        self
+    }
+
+    /// Calls `f` with every panic caught in the reducer or in an effect.
+    ///
+    /// Panics never stop the store: the offending action or effect is
+    /// abandoned and later dispatches are reduced as usual. See [`Panic`].
+    pub fn on_panic<F: Fn(&Panic) + Send + Sync + 'static>(mut self, f: F) -> Self {
+        self.options.on_panic = Some(Arc::new(f));
+        self
+    }
+
+    /// Names the store. Instrumentation emitted with the `tracing` feature
+    /// is tagged with the name.
+    pub fn name(mut self, name: impl Into<Arc<str>>) -> Self {
+        self.options.name = name.into();
+        self
+    }
+
+    /// Restarts the reducer from its last known-good state when it panics,
+    /// as configured by `supervisor`. See [`Supervisor`].
+    ///
+    /// The state is snapshotted before every command, so that stores with
+    /// in-place reducers copy it once per action.
+    pub fn supervise(mut self, supervisor: Supervisor) -> Self {
+        self.options.supervisor = Some(supervisor);
+        self
+    }
+
+    /// Routes every timer of the store, including those created by effects
+    /// through [`Context::clock`], through `clock`.
+    pub fn with_clock<C: Clock>(mut self, clock: C) -> Self {
+        self.options.clock = Arc::new(clock);
+        self
     }
 }
 
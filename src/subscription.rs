@@ -1,13 +1,98 @@
-use std::sync::{Arc, Weak};
+use std::sync::{Arc, Mutex, Weak};
 
+/// Keeps a watcher connected while alive. Watchers hold the matching
+/// `Weak<()>` and are pruned once it can no longer be upgraded.
 #[allow(dead_code)]
-pub(crate) struct Subscription(Arc<()>);
+pub(crate) struct Connection(Arc<()>);
 
-impl Subscription {
+impl Connection {
     pub(crate) fn new() -> (Self, Weak<()>) {
         let inner = Arc::new(());
         let weak = Arc::downgrade(&inner);
-        (Subscription(inner), weak)
+        (Connection(inner), weak)
+    }
+}
+
+/// The connections made through one reader, released together on
+/// [`unbind`](crate::Read::unbind).
+pub(crate) struct Connections(Arc<Mutex<Vec<Connection>>>);
+
+impl Connections {
+    pub(crate) fn new() -> Self {
+        Connections(Arc::new(Mutex::new(Vec::new())))
+    }
+
+    /// Makes a new connection, returning its handle and the liveness token
+    /// for the watcher.
+    pub(crate) fn connect(&self) -> (Subscription, Weak<()>) {
+        let (connection, alive) = Connection::new();
+        self.0.lock().unwrap().push(connection);
+        let subscription = Subscription {
+            alive: alive.clone(),
+            connections: Arc::downgrade(&self.0),
+        };
+        (subscription, alive)
+    }
+
+    pub(crate) fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
+}
+
+/// A handle to a single watcher callback.
+///
+/// Returned by [`watch`](crate::Read::watch) and its relatives.
+/// [`unsubscribe`](Subscription::unsubscribe) removes only this callback,
+/// leaving the reader's other watchers connected. Dropping the handle does not
+/// disconnect the callback: it stays until it is unsubscribed, the reader is
+/// unbound, or the reader is dropped.
+#[derive(Clone)]
+pub struct Subscription {
+    alive: Weak<()>,
+    connections: Weak<Mutex<Vec<Connection>>>,
+}
+
+impl Subscription {
+    /// Removes the callback, so that it is not called again.
+    pub fn unsubscribe(self) {
+        if let Some(connections) = self.connections.upgrade() {
+            connections.lock().unwrap().retain(|connection| {
+                !std::ptr::eq(Arc::as_ptr(&connection.0), self.alive.as_ptr())
+            });
+        }
+    }
+
+    /// Whether the callback is still connected.
+    pub fn is_active(&self) -> bool {
+        self.alive.strong_count() > 0
+    }
+}
+
+/// Disconnects a watcher callback when dropped.
+///
+/// Returned by [`watch_guarded`](crate::Read::watch_guarded). Store the guard
+/// in the UI component which the callback updates, so that the callback goes
+/// away together with the component.
+#[must_use = "the watcher is disconnected as soon as the guard is dropped"]
+pub struct WatchGuard(Option<Subscription>);
+
+impl WatchGuard {
+    pub(crate) fn new(subscription: Subscription) -> Self {
+        WatchGuard(Some(subscription))
+    }
+
+    /// Keeps the callback connected after the guard is dropped, returning its
+    /// handle.
+    pub fn release(mut self) -> Subscription {
+        self.0.take().expect("only taken here or on drop")
+    }
+}
+
+impl Drop for WatchGuard {
+    fn drop(&mut self) {
+        if let Some(subscription) = self.0.take() {
+            subscription.unsubscribe();
+        }
     }
 }
 
@@ -17,14 +102,36 @@ mod tests {
 
     #[test]
     fn new_returns_live_weak() {
-        let (_sub, weak) = Subscription::new();
+        let (_sub, weak) = Connection::new();
         assert!(weak.upgrade().is_some());
     }
 
     #[test]
     fn drop_makes_weak_dead() {
-        let (sub, weak) = Subscription::new();
+        let (sub, weak) = Connection::new();
         drop(sub);
         assert!(weak.upgrade().is_none());
     }
+
+    #[test]
+    fn unsubscribe_releases_only_its_connection() {
+        let connections = Connections::new();
+        let (first, first_alive) = connections.connect();
+        let (second, second_alive) = connections.connect();
+        first.unsubscribe();
+        assert!(first_alive.upgrade().is_none());
+        assert!(second.is_active() && second_alive.upgrade().is_some());
+    }
+
+    #[test]
+    fn guard_unsubscribes_on_drop() {
+        let connections = Connections::new();
+        let (subscription, alive) = connections.connect();
+        drop(WatchGuard::new(subscription));
+        assert!(alive.upgrade().is_none());
+
+        let (subscription, alive) = connections.connect();
+        let subscription = WatchGuard::new(subscription).release();
+        assert!(subscription.is_active() && alive.upgrade().is_some());
+    }
 }
@@ -0,0 +1,105 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use web_time::Instant;
+
+/// Restarts a store's reducer from its last known-good state when it panics,
+/// in the manner of actor supervision.
+///
+/// Installed with [`StoreBuilder::supervise`](crate::StoreBuilder::supervise).
+/// When the reducer panics, the state is rolled back to what it was before
+/// the failed command, its effects are discarded, and the reducer task pauses
+/// for the backoff before reducing the next queued action. Commands reduced
+/// before it in the same run, for stores which coalesce notifications, are
+/// kept. Once more than
+/// `max_restarts` restarts happen within the window, the supervisor gives up
+/// and the store shuts down.
+#[derive(Clone, Debug)]
+pub struct Supervisor {
+    max_restarts: usize,
+    window: Duration,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    restarts: VecDeque<Instant>,
+}
+
+impl Supervisor {
+    /// Allows three restarts per minute, without backoff.
+    pub fn new() -> Self {
+        Supervisor {
+            max_restarts: 3,
+            window: Duration::from_secs(60),
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+            restarts: VecDeque::new(),
+        }
+    }
+
+    /// Gives up once more than `restarts` restarts happen within `window`.
+    pub fn max_restarts(mut self, restarts: usize, window: Duration) -> Self {
+        self.max_restarts = restarts;
+        self.window = window;
+        self
+    }
+
+    /// Pauses `initial` before the first restart within the window, doubling
+    /// for every further one up to `max`.
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Records a restart at `now`, returning the backoff to wait before
+    /// resuming, or `None` if the restart budget is exhausted.
+    pub(crate) fn restart(&mut self, now: Instant) -> Option<Duration> {
+        while let Some(&at) = self.restarts.front() {
+            if now.duration_since(at) < self.window {
+                break;
+            }
+            self.restarts.pop_front();
+        }
+        if self.restarts.len() >= self.max_restarts {
+            return None;
+        }
+        let factor = 2u32.saturating_pow(self.restarts.len() as u32);
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff);
+        self.restarts.push_back(now);
+        Some(backoff)
+    }
+}
+
+impl Default for Supervisor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum() {
+        let mut supervisor = Supervisor::new()
+            .max_restarts(10, Duration::from_secs(60))
+            .backoff(Duration::from_secs(1), Duration::from_secs(3));
+        let now = Instant::now();
+        let delays: Vec<_> = (0..3).map(|_| supervisor.restart(now).unwrap()).collect();
+        assert_eq!(delays, [1, 2, 3].map(Duration::from_secs).to_vec());
+    }
+
+    #[test]
+    fn restarts_outside_the_window_are_forgotten() {
+        let mut supervisor = Supervisor::new().max_restarts(1, Duration::from_secs(10));
+        let now = Instant::now();
+        assert!(supervisor.restart(now).is_some());
+        assert!(supervisor.restart(now + Duration::from_secs(5)).is_none());
+        assert!(supervisor.restart(now + Duration::from_secs(10)).is_some());
+    }
+}
@@ -0,0 +1,428 @@
+//! Keeping two stores converged, such as one per window or process of a
+//! desktop app, over a WebSocket (feature `sync`).
+//!
+//! Each store gets a [`Peer`], attached to it as a [`Middleware`] so that
+//! every reduced action is shipped to the other side, either as the new
+//! state or as the action itself (see [`Shipping`]). One peer is the
+//! primary: the replica starts from the primary's state, and by default the
+//! primary's state wins when both stores change at once.
+//!
+//! ```no_run
+//! use std::net::TcpListener;
+//! use uniflow::sync::{Peer, WebSocketTransport};
+//!
+//! // In the main window.
+//! let listener = TcpListener::bind("127.0.0.1:9001")?;
+//! let (stream, _) = listener.accept()?;
+//! let peer = Peer::primary(WebSocketTransport::accept(stream)?);
+//! let store = uniflow::Store::builder(0, |s: i32, n: i32| s + n)
+//!     .middleware(peer.clone())
+//!     .build();
+//! peer.attach(&store);
+//!
+//! // In the other window.
+//! let peer = Peer::replica(WebSocketTransport::connect("ws://127.0.0.1:9001")?);
+//! let store = uniflow::Store::builder(0, |s: i32, n: i32| s + n)
+//!     .middleware(peer.clone())
+//!     .build();
+//! peer.attach(&store);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::mpsc::{Sender, channel};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Value as Json, json};
+
+use crate::transport::run;
+use crate::{Action, Crdt, Deps, Dispatcher, Middleware, Read, Reader, Store, Value};
+
+pub use crate::transport::{Transport, WebSocketTransport};
+
+/// What a [`Peer`] ships to the other side when its store reduces an action.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Shipping {
+    /// The new state. Changes made on both sides at once are reconciled by
+    /// the conflict policy.
+    #[default]
+    State,
+    /// The action, for the other side to reduce too. Cheaper for large
+    /// states, but actions reduced on both sides at once are not reconciled,
+    /// so the stores only stay converged if such actions commute.
+    Actions,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Primary,
+    Replica,
+}
+
+type Resolve<S> = Box<dyn Fn(&S, S) -> S + Send + Sync>;
+
+struct Attached<S: Value, A: Action> {
+    reader: Reader<S>,
+    set_state: Box<dyn Fn(S) + Send + Sync>,
+    dispatcher: Dispatcher<A>,
+}
+
+/// How many messages each side has shipped. Every message carries the number
+/// its sender had received, so that a state can be recognised as made
+/// without knowledge of the latest local changes.
+#[derive(Default)]
+struct Versions {
+    sent: u64,
+    received: u64,
+    /// Actions shipped from here, numbered, which the other side had not yet
+    /// received when it last shipped.
+    unseen: VecDeque<(u64, Json)>,
+}
+
+struct Shared<S: Value, A: Action> {
+    role: Role,
+    shipping: Mutex<Shipping>,
+    resolve: Mutex<Option<Resolve<S>>>,
+    outgoing: Mutex<Sender<String>>,
+    versions: Mutex<Versions>,
+    store: Mutex<Option<Attached<S, A>>>,
+    /// Messages received before the store was attached.
+    early: Mutex<Vec<String>>,
+    /// Actions received from the other side and not yet reduced, which must
+    /// not be shipped back.
+    echoes: Mutex<Vec<Json>>,
+}
+
+impl<S, A> Shared<S, A>
+where
+    S: Value + Serialize + DeserializeOwned,
+    A: Action + Serialize + DeserializeOwned,
+{
+    fn send(&self, message: Json) {
+        let _ = self.outgoing.lock().unwrap().send(message.to_string());
+    }
+
+    fn send_state(&self, versions: &mut Versions, state: &S) {
+        let Ok(state) = serde_json::to_value(state) else {
+            return;
+        };
+        versions.sent += 1;
+        self.send(json!({ "state": state, "seen": versions.received }));
+    }
+
+    fn send_action(&self, action: Json) {
+        let mut versions = self.versions.lock().unwrap();
+        versions.sent += 1;
+        let sent = versions.sent;
+        self.send(json!({ "action": action, "seen": versions.received }));
+        versions.unseen.push_back((sent, action));
+    }
+
+    fn handle(&self, raw: &str) {
+        match self.store.lock().unwrap().as_ref() {
+            Some(store) => self.apply(store, raw),
+            None => self.early.lock().unwrap().push(raw.to_owned()),
+        }
+    }
+
+    fn apply(&self, store: &Attached<S, A>, raw: &str) {
+        let Ok(mut message) = serde_json::from_str::<Json>(raw) else {
+            return;
+        };
+        let Some(seen) = message["seen"].as_u64() else {
+            return;
+        };
+        let mut versions = self.versions.lock().unwrap();
+        versions.received += 1;
+        versions.unseen.retain(|(sent, _)| *sent > seen);
+        if let Some(action) = message.get_mut("action").map(Json::take) {
+            self.reduce(store, action);
+        } else if let Some(state) = message.get_mut("state")
+            && let Ok(incoming) = serde_json::from_value::<S>(state.take())
+        {
+            self.receive_state(store, &mut versions, incoming, seen);
+        }
+    }
+
+    /// Reduces an action shipped from the other side, without shipping it
+    /// back.
+    fn reduce(&self, store: &Attached<S, A>, action: Json) {
+        if let Ok(decoded) = serde_json::from_value::<A>(action.clone()) {
+            self.echoes.lock().unwrap().push(action);
+            store.dispatcher.dispatch(decoded);
+        }
+    }
+
+    fn receive_state(
+        &self,
+        store: &Attached<S, A>,
+        versions: &mut Versions,
+        incoming: S,
+        seen: u64,
+    ) {
+        if *self.shipping.lock().unwrap() == Shipping::Actions {
+            // The other side reduces the actions it had not seen on top of
+            // its state when they arrive, so do the same here.
+            (store.set_state)(incoming);
+            for (_, action) in &versions.unseen {
+                self.reduce(store, action.clone());
+            }
+            return;
+        }
+        let local = store.reader.get();
+        // The other side had not seen every state shipped from here.
+        let concurrent = seen < versions.sent && local != incoming;
+        let resolved = if concurrent {
+            self.resolve(&local, incoming.clone())
+        } else {
+            incoming.clone()
+        };
+        if resolved != local {
+            (store.set_state)(resolved.clone());
+        }
+        if resolved != incoming {
+            self.send_state(versions, &resolved);
+        }
+    }
+
+    fn resolve(&self, local: &S, incoming: S) -> S {
+        match (self.resolve.lock().unwrap().as_ref(), self.role) {
+            (Some(resolve), _) => resolve(local, incoming),
+            (None, Role::Primary) => local.clone(),
+            (None, Role::Replica) => incoming,
+        }
+    }
+}
+
+/// One side of a synchronised pair of stores.
+///
+/// Attach it to a store as a [`Middleware`], then call
+/// [`attach`](Peer::attach) on the built store to start syncing.
+pub struct Peer<S: Value, A: Action> {
+    shared: Arc<Shared<S, A>>,
+}
+
+impl<S, A> Peer<S, A>
+where
+    S: Value + Serialize + DeserializeOwned,
+    A: Action + Serialize + DeserializeOwned,
+{
+    /// Starts a background thread serving `transport` as the primary peer,
+    /// whose state the replica starts from.
+    pub fn primary<T: Transport>(transport: T) -> Self {
+        Self::connect(transport, Role::Primary)
+    }
+
+    /// Starts a background thread serving `transport` as the replica peer.
+    pub fn replica<T: Transport>(transport: T) -> Self {
+        Self::connect(transport, Role::Replica)
+    }
+
+    fn connect<T: Transport>(transport: T, role: Role) -> Self {
+        let (sender, receiver) = channel();
+        let shared = Arc::new(Shared {
+            role,
+            shipping: Mutex::new(Shipping::default()),
+            resolve: Mutex::new(None),
+            outgoing: Mutex::new(sender),
+            versions: Mutex::new(Versions::default()),
+            store: Mutex::new(None),
+            early: Mutex::new(Vec::new()),
+            echoes: Mutex::new(Vec::new()),
+        });
+        let weak = Arc::downgrade(&shared);
+        std::thread::spawn(move || run(transport, receiver, weak, Shared::handle));
+        Peer { shared }
+    }
+
+    /// Sets what is shipped to the other side. Both peers must agree.
+    pub fn shipping(self, shipping: Shipping) -> Self {
+        *self.shared.shipping.lock().unwrap() = shipping;
+        self
+    }
+
+    /// Resolves a state received from the other side while a local change
+    /// was still on its way there. `resolve` is called with the local and
+    /// the received state and returns the state both sides should settle
+    /// on; it must resolve the same way on both peers, whichever way round
+    /// the states are. By default the primary's state wins.
+    pub fn on_conflict<F>(self, resolve: F) -> Self
+    where
+        F: Fn(&S, S) -> S + Send + Sync + 'static,
+    {
+        *self.shared.resolve.lock().unwrap() = Some(Box::new(resolve));
+        self
+    }
+
+    /// Merges concurrent states rather than picking one, for states which
+    /// are [CRDTs](Crdt).
+    pub fn merging(self) -> Self
+    where
+        S: Crdt,
+    {
+        self.on_conflict(|local: &S, incoming| local.merged(&incoming))
+    }
+
+    /// Starts syncing `store`: the primary ships its current state, and
+    /// messages received so far are applied.
+    pub fn attach<D: Deps>(&self, store: &Store<S, A, D>) {
+        let attached = Attached {
+            reader: store.reader(),
+            set_state: Box::new(store.state_setter()),
+            dispatcher: store.dispatcher(),
+        };
+        if self.shared.role == Role::Primary {
+            let mut versions = self.shared.versions.lock().unwrap();
+            self.shared
+                .send_state(&mut versions, &attached.reader.get());
+        }
+        let mut store = self.shared.store.lock().unwrap();
+        let attached = store.insert(attached);
+        for raw in std::mem::take(&mut *self.shared.early.lock().unwrap()) {
+            self.shared.apply(attached, &raw);
+        }
+    }
+}
+
+impl<S: Value, A: Action> Clone for Peer<S, A> {
+    fn clone(&self) -> Self {
+        Peer {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<S, A> Middleware<S, A> for Peer<S, A>
+where
+    S: Value + Serialize + DeserializeOwned,
+    A: Action + Serialize + DeserializeOwned,
+{
+    fn before(&self, _: &S, action: A) -> Option<A> {
+        if *self.shared.shipping.lock().unwrap() != Shipping::Actions {
+            return Some(action);
+        }
+        let Ok(json) = serde_json::to_value(&action) else {
+            return Some(action);
+        };
+        let echo = {
+            let mut echoes = self.shared.echoes.lock().unwrap();
+            let index = echoes.iter().position(|echo| *echo == json);
+            index.map(|index| echoes.remove(index))
+        };
+        if echo.is_none() {
+            self.shared.send_action(json);
+        }
+        Some(action)
+    }
+
+    fn after(&self, previous: &S, next: &S) {
+        if *self.shared.shipping.lock().unwrap() == Shipping::State && previous != next {
+            let mut versions = self.shared.versions.lock().unwrap();
+            self.shared.send_state(&mut versions, next);
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Dispatch;
+    use crate::executor::{init as init_executor, tick};
+    use crate::transport::ChannelTransport;
+    use std::net::TcpListener;
+    use std::time::{Duration, Instant};
+
+    fn pair() -> (ChannelTransport, ChannelTransport) {
+        let (a_tx, a_rx) = channel();
+        let (b_tx, b_rx) = channel();
+        (
+            ChannelTransport {
+                sent: a_tx,
+                incoming: b_rx,
+            },
+            ChannelTransport {
+                sent: b_tx,
+                incoming: a_rx,
+            },
+        )
+    }
+
+    fn synced(peer: Peer<i32, i32>, initial: i32) -> Store<i32, i32> {
+        let store = Store::builder(initial, |s: i32, n: i32| s + n)
+            .middleware(peer.clone())
+            .build();
+        peer.attach(&store);
+        store
+    }
+
+    /// Ticks until `done`, or panics after a second.
+    fn tick_until(done: impl Fn() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while !done() {
+            assert!(Instant::now() < deadline, "timed out");
+            tick();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn replica_starts_from_the_primary_and_ships_changes_back() {
+        init_executor();
+        let (a, b) = pair();
+        let primary = synced(Peer::primary(a), 5);
+        let replica = synced(Peer::replica(b), 0);
+        tick_until(|| replica.get() == 5);
+
+        replica.dispatch(2);
+        tick_until(|| primary.get() == 7);
+        assert_eq!(replica.get(), 7);
+    }
+
+    #[test]
+    fn concurrent_changes_settle_on_the_resolved_state() {
+        init_executor();
+        let (a, b) = pair();
+        let primary = synced(
+            Peer::primary(a).on_conflict(|local, incoming: i32| incoming.max(*local)),
+            0,
+        );
+        let replica = synced(
+            Peer::replica(b).on_conflict(|local, incoming: i32| incoming.max(*local)),
+            0,
+        );
+        tick_until(|| replica.get() == 0);
+
+        primary.dispatch(3);
+        replica.dispatch(8);
+        tick();
+        tick_until(|| primary.get() == 8 && replica.get() == 8);
+    }
+
+    #[test]
+    fn shipped_actions_are_not_shipped_back() {
+        init_executor();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            WebSocketTransport::accept(stream).unwrap()
+        });
+        let client = WebSocketTransport::connect(&url).unwrap();
+        let primary = synced(
+            Peer::primary(server.join().unwrap()).shipping(Shipping::Actions),
+            0,
+        );
+        let replica = synced(Peer::replica(client).shipping(Shipping::Actions), 0);
+
+        primary.dispatch(1);
+        replica.dispatch(2);
+        tick_until(|| primary.get() == 3 && replica.get() == 3);
+        std::thread::sleep(Duration::from_millis(50));
+        tick();
+        assert_eq!((primary.get(), replica.get()), (3, 3));
+    }
+}
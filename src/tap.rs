@@ -0,0 +1,123 @@
+use std::mem;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use futures::channel::mpsc::UnboundedSender;
+
+use crate::node::SourceNode;
+use crate::panic::{self, Panic, PanicHook, PanicOrigin};
+use crate::{Action, Value};
+
+/// Observes the actions reduced by a store's reducer task.
+pub(crate) trait Tap<S: Value, A>: Send {
+    /// Called just before `action` is reduced. Returns `false` once the tap
+    /// is no longer interested, to have it removed.
+    fn before(&mut self, action: &A, source: &SourceNode<S>) -> bool;
+
+    /// Called once the action passed to `before` has been reduced.
+    fn after(&mut self, _source: &SourceNode<S>) {}
+}
+
+/// Broadcasts a copy of every action, until the receiver is dropped.
+impl<S: Value, A: Clone + Send> Tap<S, A> for UnboundedSender<A> {
+    fn before(&mut self, action: &A, _: &SourceNode<S>) -> bool {
+        self.unbounded_send(action.clone()).is_ok()
+    }
+}
+
+/// Calls `f` with each action and the states before and after reducing it.
+pub(crate) struct Inspector<S, A, F> {
+    f: F,
+    pending: Option<(A, Arc<S>)>,
+}
+
+impl<S, A, F> Inspector<S, A, F> {
+    pub(crate) fn new(f: F) -> Self {
+        Inspector { f, pending: None }
+    }
+}
+
+impl<S, A, F> Tap<S, A> for Inspector<S, A, F>
+where
+    S: Value,
+    A: Clone + Send,
+    F: FnMut(&A, &S, &S) + Send,
+{
+    fn before(&mut self, action: &A, source: &SourceNode<S>) -> bool {
+        self.pending = Some((action.clone(), source.get_arc()));
+        true
+    }
+
+    fn after(&mut self, source: &SourceNode<S>) {
+        if let Some((action, previous)) = self.pending.take() {
+            (self.f)(&action, &previous, &source.get_arc());
+        }
+    }
+}
+
+type BoxedTap<S, A> = Box<dyn Tap<S, A>>;
+
+/// The taps installed on a store, shared between the store and its reducer
+/// task.
+///
+/// Taps run user code, so they are called outside of the lock and each one
+/// under `catch_unwind`: a tap which panics is reported to the store's panic
+/// hook and removed, and the others keep running.
+pub(crate) struct Taps<S: Value, A> {
+    taps: Arc<Mutex<Vec<BoxedTap<S, A>>>>,
+    on_panic: Option<PanicHook>,
+}
+
+impl<S: Value, A: Action> Taps<S, A> {
+    pub(crate) fn new(on_panic: Option<PanicHook>) -> Self {
+        Taps {
+            taps: Arc::new(Mutex::new(Vec::new())),
+            on_panic,
+        }
+    }
+
+    pub(crate) fn add(&self, tap: impl Tap<S, A> + 'static) {
+        self.lock().push(Box::new(tap));
+    }
+
+    pub(crate) fn before(&self, action: &A, source: &SourceNode<S>) {
+        self.each(|tap| tap.before(action, source));
+    }
+
+    pub(crate) fn after(&self, source: &SourceNode<S>) {
+        self.each(|tap| {
+            tap.after(source);
+            true
+        });
+    }
+
+    /// Calls `f` with each tap, keeping those for which it returns `true`.
+    fn each(&self, mut f: impl FnMut(&mut BoxedTap<S, A>) -> bool) {
+        let mut taps = mem::take(&mut *self.lock());
+        taps.retain_mut(|tap| match catch_unwind(AssertUnwindSafe(|| f(tap))) {
+            Ok(keep) => keep,
+            Err(payload) => {
+                let panic = Panic::new(PanicOrigin::Tap, payload);
+                panic::report(self.on_panic.as_ref(), panic);
+                false
+            }
+        });
+        // Keep the taps added while these were running after them.
+        let mut installed = self.lock();
+        let added = mem::replace(&mut *installed, taps);
+        installed.extend(added);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<BoxedTap<S, A>>> {
+        self.taps.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<S: Value, A> Clone for Taps<S, A> {
+    fn clone(&self) -> Self {
+        Taps {
+            taps: self.taps.clone(),
+            on_panic: self.on_panic.clone(),
+        }
+    }
+}
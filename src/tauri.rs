@@ -0,0 +1,204 @@
+//! A bridge between a store and a [Tauri](https://tauri.app) webview.
+//!
+//! The webview dispatches actions as JSON through a Tauri command, and
+//! receives the selected state as JSON events whenever it changes.
+//! [`plugin`] registers the commands and emits the events through the app:
+//!
+//! ```ignore
+//! tauri::Builder::default()
+//!     .plugin(uniflow::tauri::plugin(&store, |s: &State| s.visible.clone()))
+//!     .run(tauri::generate_context!())
+//!     .expect("run app");
+//! ```
+//!
+//! The commands are declared to the app's permissions from its `build.rs`:
+//!
+//! ```ignore
+//! use tauri_build::{Attributes, DefaultPermissionRule, InlinedPlugin};
+//!
+//! let plugin = InlinedPlugin::new()
+//!     .commands(uniflow::tauri::COMMANDS)
+//!     .default_permission(DefaultPermissionRule::AllowAllCommands);
+//! tauri_build::try_build(Attributes::new().plugin(uniflow::tauri::PLUGIN, plugin))
+//!     .expect("build app");
+//! ```
+//!
+//! In the webview, `invoke("plugin:uniflow|dispatch", { action })`
+//! dispatches, `invoke("plugin:uniflow|state")` returns the state to start
+//! from, and `listen(STATE_EVENT, ...)` follows it. Apps wiring their own
+//! commands use a [`TauriBridge`] instead.
+
+use ::tauri::plugin::{Builder, TauriPlugin};
+use ::tauri::{Emitter, Manager, Runtime};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value as Json;
+
+use crate::{Action, Deps, Dispatcher, Read, Reader, Store, Value};
+
+/// The event the selected state is emitted under.
+pub const STATE_EVENT: &str = "uniflow://state";
+
+/// The name [`plugin`] registers under.
+pub const PLUGIN: &str = "uniflow";
+
+/// The commands [`plugin`] registers, to allow in the app's permissions.
+pub const COMMANDS: &[&str] = &["dispatch", "state"];
+
+/// A Tauri plugin bridging `store`: it registers the `dispatch` and `state`
+/// commands of a [`TauriBridge`], and emits [`STATE_EVENT`] with the state
+/// selected by `select` through the app whenever it changes.
+pub fn plugin<R, S, A, D, T, F>(store: &Store<S, A, D>, select: F) -> TauriPlugin<R>
+where
+    R: Runtime,
+    S: Value,
+    A: Action + DeserializeOwned,
+    D: Deps,
+    T: Value + Serialize,
+    F: Fn(&S) -> T + Send + Sync + 'static,
+{
+    let dispatcher = store.dispatcher();
+    let reader = store.derived(select);
+    Builder::new(PLUGIN)
+        .invoke_handler(::tauri::generate_handler![dispatch, state])
+        .setup(move |app, _| {
+            let app_handle = app.clone();
+            let emit = move |event: &str, payload: Json| {
+                let _ = app_handle.emit(event, payload);
+            };
+            let bridge = TauriBridge::from_reader(dispatcher, reader, emit);
+            app.manage(Managed(Box::new(bridge)));
+            Ok(())
+        })
+        .build()
+}
+
+/// The bridge [`plugin`] manages, with its action type erased so that the
+/// commands need not be generic.
+struct Managed(Box<dyn Bridge>);
+
+trait Bridge: Send + Sync {
+    fn dispatch(&self, action: Json) -> Result<(), String>;
+    fn state(&self) -> Result<Json, String>;
+}
+
+impl<A: Action + DeserializeOwned> Bridge for TauriBridge<A> {
+    fn dispatch(&self, action: Json) -> Result<(), String> {
+        TauriBridge::dispatch(self, action)
+    }
+
+    fn state(&self) -> Result<Json, String> {
+        TauriBridge::state(self)
+    }
+}
+
+#[::tauri::command]
+fn dispatch(bridge: ::tauri::State<'_, Managed>, action: Json) -> Result<(), String> {
+    bridge.0.dispatch(action)
+}
+
+#[::tauri::command]
+fn state(bridge: ::tauri::State<'_, Managed>) -> Result<Json, String> {
+    bridge.0.state()
+}
+
+/// Dispatches actions deserialized from JSON into a store, and emits the
+/// state selected from it as JSON whenever it changes.
+pub struct TauriBridge<A: Action> {
+    dispatcher: Dispatcher<A>,
+    state: Box<dyn Fn() -> Result<Json, String> + Send + Sync>,
+}
+
+impl<A: Action + DeserializeOwned> TauriBridge<A> {
+    /// Bridges `store`, calling `emit` with [`STATE_EVENT`] and the state
+    /// selected by `select` whenever it changes.
+    pub fn new<S, D, T, F, E>(store: &Store<S, A, D>, select: F, emit: E) -> Self
+    where
+        S: Value,
+        D: Deps,
+        T: Value + Serialize,
+        F: Fn(&S) -> T + Send + Sync + 'static,
+        E: Fn(&str, Json) + Send + Sync + 'static,
+    {
+        Self::from_reader(store.dispatcher(), store.derived(select), emit)
+    }
+
+    fn from_reader<T, E>(dispatcher: Dispatcher<A>, reader: Reader<T>, emit: E) -> Self
+    where
+        T: Value + Serialize,
+        E: Fn(&str, Json) + Send + Sync + 'static,
+    {
+        reader.watch(move |selected| {
+            if let Ok(payload) = serde_json::to_value(selected) {
+                emit(STATE_EVENT, payload);
+            }
+        });
+        TauriBridge {
+            dispatcher,
+            state: Box::new(move || serde_json::to_value(reader.get()).map_err(|e| e.to_string())),
+        }
+    }
+
+    /// Dispatches the action `action` deserializes to, or reports why it does
+    /// not deserialize. The body of the app's dispatch command.
+    pub fn dispatch(&self, action: Json) -> Result<(), String> {
+        let action = serde_json::from_value(action).map_err(|e| e.to_string())?;
+        self.dispatcher.dispatch(action);
+        Ok(())
+    }
+
+    /// The selected state, for the webview to start from, or why it does not
+    /// serialize. The body of the app's state command.
+    pub fn state(&self) -> Result<Json, String> {
+        (self.state)()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{init as init_executor, tick};
+    use serde::Deserialize;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Deserialize)]
+    enum Counter {
+        Add(i32),
+    }
+
+    #[test]
+    fn dispatches_json_actions_and_emits_the_selected_state() {
+        init_executor();
+        let store = Store::new((0, "hidden"), |(count, hidden), Counter::Add(n)| {
+            (count + n, hidden)
+        });
+        let emitted = Arc::new(Mutex::new(Vec::new()));
+        let sink = emitted.clone();
+        let bridge = TauriBridge::new(
+            &store,
+            |s: &(i32, &str)| s.0,
+            move |event, payload| {
+                sink.lock().unwrap().push((event.to_string(), payload));
+            },
+        );
+        assert_eq!(bridge.state(), Ok(json!(0)));
+
+        bridge.dispatch(json!({ "Add": 2 })).unwrap();
+        assert!(bridge.dispatch(json!("Reset")).is_err());
+        tick();
+        assert_eq!(
+            *emitted.lock().unwrap(),
+            vec![(STATE_EVENT.to_string(), json!(2))]
+        );
+    }
+
+    #[test]
+    fn unserializable_states_are_errors() {
+        init_executor();
+        let store = Store::new(BTreeMap::from([((0, 0), 0)]), |s, Counter::Add(_)| s);
+        let bridge = TauriBridge::new(&store, BTreeMap::clone, |_, _| {});
+        assert!(bridge.state().is_err());
+    }
+}
@@ -0,0 +1,258 @@
+//! Utilities for testing stores deterministically.
+//!
+//! These helpers are designed to be used together with the
+//! [`manual_spawner`](crate::manual_spawner): time only moves when the test
+//! says so, and every tick of virtual time drives the local executor until no
+//! further progress can be made.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Poll, Waker};
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use web_time::Instant;
+
+use crate::time::Clock;
+
+// ── TestClock ─────────────────────────────────────────────────────────────────
+
+struct Timer {
+    fired: bool,
+    waker: Option<Waker>,
+}
+
+struct TestClockInner {
+    origin: Instant,
+    elapsed: Duration,
+    next_id: u64,
+    timers: BTreeMap<(Duration, u64), Timer>,
+}
+
+/// A virtual-time [`Clock`] for tests.
+///
+/// Time stands still until [`advance`](TestClock::advance) is called. Timers
+/// due within the advanced window fire in deadline order — timers sharing a
+/// deadline fire in the order they were created — and the
+/// [`manual_spawner`](crate::manual_spawner) is stepped after each one, so
+/// effects woken by a timer run before the next timer fires.
+///
+/// Cloning a `TestClock` shares the underlying timeline.
+#[derive(Clone)]
+pub struct TestClock {
+    inner: Arc<Mutex<TestClockInner>>,
+}
+
+impl TestClock {
+    pub fn new() -> Self {
+        TestClock {
+            inner: Arc::new(Mutex::new(TestClockInner {
+                origin: Instant::now(),
+                elapsed: Duration::ZERO,
+                next_id: 0,
+                timers: BTreeMap::new(),
+            })),
+        }
+    }
+
+    /// Total virtual time elapsed since the clock was created.
+    pub fn elapsed(&self) -> Duration {
+        self.inner.lock().unwrap().elapsed
+    }
+
+    /// Moves virtual time forward by `duration`, firing every timer that
+    /// becomes due and stepping the manual spawner after each.
+    pub fn advance(&self, duration: Duration) {
+        let target = self.inner.lock().unwrap().elapsed + duration;
+        loop {
+            let waker = {
+                let mut guard = self.inner.lock().unwrap();
+                let due = guard
+                    .timers
+                    .iter_mut()
+                    .find(|(_, timer)| !timer.fired)
+                    .filter(|((deadline, _), _)| *deadline <= target);
+                let Some((&(deadline, _), timer)) = due else {
+                    break;
+                };
+                timer.fired = true;
+                let waker = timer.waker.take();
+                guard.elapsed = deadline;
+                waker
+            };
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+            crate::manual_spawner::step();
+        }
+        self.inner.lock().unwrap().elapsed = target;
+        crate::manual_spawner::step();
+    }
+}
+
+impl Default for TestClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for TestClock {
+    fn now(&self) -> Instant {
+        let guard = self.inner.lock().unwrap();
+        guard.origin + guard.elapsed
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        let key = {
+            let mut guard = self.inner.lock().unwrap();
+            let key = (guard.elapsed + duration, guard.next_id);
+            guard.next_id += 1;
+            guard.timers.insert(
+                key,
+                Timer {
+                    fired: duration.is_zero(),
+                    waker: None,
+                },
+            );
+            key
+        };
+        Box::pin(TestSleep {
+            inner: self.inner.clone(),
+            key,
+        })
+    }
+}
+
+struct TestSleep {
+    inner: Arc<Mutex<TestClockInner>>,
+    key: (Duration, u64),
+}
+
+impl Future for TestSleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<()> {
+        let mut guard = self.inner.lock().unwrap();
+        match guard.timers.get_mut(&self.key) {
+            Some(timer) if !timer.fired => {
+                timer.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+            _ => Poll::Ready(()),
+        }
+    }
+}
+
+impl Drop for TestSleep {
+    fn drop(&mut self) {
+        self.inner.lock().unwrap().timers.remove(&self.key);
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::init as init_executor;
+    use crate::{Context, Dispatch, Effect, Read, Store};
+
+    const WINDOW: Duration = Duration::from_millis(300);
+
+    fn delayed_store(clock: &TestClock) -> Store<Vec<&'static str>, &'static str> {
+        Store::builder_with_deps(
+            Vec::new(),
+            |mut state: Vec<&'static str>, action: &'static str| {
+                if let Some(label) = action.strip_prefix("delay:") {
+                    let label: &'static str = label;
+                    (
+                        state,
+                        Effect::new(move |ctx: Context<&'static str>| async move {
+                            ctx.clock().sleep(WINDOW).await;
+                            ctx.dispatch(label);
+                        }),
+                    )
+                } else {
+                    state.push(action);
+                    (state, Effect::none())
+                }
+            },
+            (),
+        )
+        .with_clock(clock.clone())
+        .build()
+    }
+
+    #[test]
+    fn now_starts_still_and_advances() {
+        init_executor();
+        let clock = TestClock::new();
+        let start = clock.now();
+        assert_eq!(clock.now(), start);
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(clock.now() - start, Duration::from_secs(2));
+        assert_eq!(clock.elapsed(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn advancing_exactly_the_window_fires_the_timer() {
+        init_executor();
+        let clock = TestClock::new();
+        let store = delayed_store(&clock);
+        store.dispatch("delay:fired");
+        crate::manual_spawner::step();
+
+        clock.advance(WINDOW);
+        assert_eq!(store.get(), vec!["fired"]);
+    }
+
+    #[test]
+    fn advancing_one_nanosecond_short_does_not_fire() {
+        init_executor();
+        let clock = TestClock::new();
+        let store = delayed_store(&clock);
+        store.dispatch("delay:fired");
+        crate::manual_spawner::step();
+
+        clock.advance(WINDOW - Duration::from_nanos(1));
+        assert!(store.get().is_empty());
+
+        clock.advance(Duration::from_nanos(1));
+        assert_eq!(store.get(), vec!["fired"]);
+    }
+
+    #[test]
+    fn timers_due_at_the_same_instant_fire_in_registration_order() {
+        init_executor();
+        let clock = TestClock::new();
+        let store = delayed_store(&clock);
+        store.dispatch("delay:first");
+        store.dispatch("delay:second");
+        crate::manual_spawner::step();
+
+        clock.advance(WINDOW);
+        assert_eq!(store.get(), vec!["first", "second"]);
+    }
+
+    #[test]
+    fn timers_fire_in_deadline_order() {
+        let clock = TestClock::new();
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let late = clock.sleep(Duration::from_secs(2));
+        let early = clock.sleep(Duration::from_secs(1));
+        init_executor();
+        for (label, sleep) in [("late", late), ("early", early)] {
+            let order = order.clone();
+            any_spawner::Executor::spawn(async move {
+                sleep.await;
+                order.lock().unwrap().push(label);
+            });
+        }
+        crate::manual_spawner::step();
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(*order.lock().unwrap(), vec!["early", "late"]);
+    }
+}
@@ -0,0 +1,48 @@
+/// The actions an [`Effect::thunk`](crate::Effect::thunk) dispatches for one
+/// async call: `Started` as it begins, then `Succeeded` or `Failed` with its
+/// outcome.
+///
+/// Wrap it in a variant of the app's action, one per kind of call, and
+/// reduce the three cases like any other actions:
+///
+/// ```
+/// use uniflow::AsyncAction;
+///
+/// enum Action {
+///     FetchUser(AsyncAction<String, String>),
+/// }
+///
+/// #[derive(Clone, PartialEq)]
+/// struct State {
+///     loading: bool,
+///     user: Option<String>,
+///     error: Option<String>,
+/// }
+///
+/// fn reduce(s: State, action: Action) -> State {
+///     match action {
+///         Action::FetchUser(AsyncAction::Started) => State { loading: true, ..s },
+///         Action::FetchUser(AsyncAction::Succeeded(user)) => {
+///             State { loading: false, user: Some(user), error: None }
+///         }
+///         Action::FetchUser(AsyncAction::Failed(error)) => {
+///             State { loading: false, error: Some(error), ..s }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AsyncAction<T, E> {
+    Started,
+    Succeeded(T),
+    Failed(E),
+}
+
+impl<T, E> From<Result<T, E>> for AsyncAction<T, E> {
+    fn from(result: Result<T, E>) -> Self {
+        match result {
+            Ok(value) => AsyncAction::Succeeded(value),
+            Err(error) => AsyncAction::Failed(error),
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use web_time::Instant;
+
+/// Source of time and timers for a [`Store`](crate::Store).
+///
+/// Every timer the store creates internally, and every timer an effect creates
+/// via [`Context::clock`](crate::Context::clock), is routed through the store's
+/// clock. Production stores use [`SystemClock`]; tests can substitute
+/// [`TestClock`](crate::test::TestClock) to drive time deterministically.
+pub trait Clock: Send + Sync + 'static {
+    /// The current instant as seen by this clock.
+    fn now(&self) -> Instant;
+
+    /// Returns a future which resolves once `duration` has elapsed on this clock.
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+/// Wall-clock time backed by [`futures_timer`], independent of the async runtime.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(futures_timer::Delay::new(duration))
+    }
+}
@@ -0,0 +1,54 @@
+//! Spans and events emitted with the `tracing` feature, tagged with the name
+//! of the store. Without the feature they compile to nothing.
+
+#[cfg(feature = "tracing")]
+mod enabled {
+    use std::any::type_name;
+    use std::future::Future;
+
+    use tracing::Instrument;
+    use tracing::span::EnteredSpan;
+
+    pub(crate) fn dispatched(store: &str) {
+        tracing::trace!(store, "dispatch");
+    }
+
+    pub(crate) fn reduce<A>(store: &str, _action: &A) -> EnteredSpan {
+        tracing::debug_span!("reduce", store, action = type_name::<A>()).entered()
+    }
+
+    pub(crate) fn watch(store: &str) -> EnteredSpan {
+        tracing::trace_span!("watch", store).entered()
+    }
+
+    pub(crate) fn effect<F: Future>(
+        store: &str,
+        future: F,
+    ) -> impl Future<Output = F::Output> + use<F> {
+        tracing::debug!(store, "effect spawned");
+        future.instrument(tracing::debug_span!("effect", store))
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+mod enabled {
+    use std::future::Future;
+
+    pub(crate) struct EnteredSpan;
+
+    pub(crate) fn dispatched(_store: &str) {}
+
+    pub(crate) fn reduce<A>(_store: &str, _action: &A) -> EnteredSpan {
+        EnteredSpan
+    }
+
+    pub(crate) fn watch(_store: &str) -> EnteredSpan {
+        EnteredSpan
+    }
+
+    pub(crate) fn effect<F: Future>(_store: &str, future: F) -> F {
+        future
+    }
+}
+
+pub(crate) use enabled::*;
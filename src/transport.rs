@@ -0,0 +1,157 @@
+use std::io;
+use std::net::TcpStream;
+use std::sync::Weak;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::Duration;
+
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// A bidirectional text message channel, such as a WebSocket connection.
+pub trait Transport: Send + 'static {
+    fn send(&mut self, message: &str) -> io::Result<()>;
+
+    /// Waits briefly for the next incoming message, returning `Ok(None)` if
+    /// none arrived. Must not block indefinitely: outgoing messages are only
+    /// flushed between calls.
+    fn receive(&mut self) -> io::Result<Option<String>>;
+}
+
+/// A [`Transport`] over a plain WebSocket connection.
+pub struct WebSocketTransport {
+    socket: WebSocket<MaybeTlsStream<TcpStream>>,
+}
+
+impl WebSocketTransport {
+    /// Connects to a WebSocket server, e.g. `ws://localhost:8000/socketcluster/`.
+    ///
+    /// `wss://` URLs are rejected with [`io::ErrorKind::Unsupported`]: reads
+    /// through TLS cannot be given the timeout that polling relies on.
+    pub fn connect(url: &str) -> io::Result<Self> {
+        if url
+            .get(..6)
+            .is_some_and(|scheme| scheme.eq_ignore_ascii_case("wss://"))
+        {
+            return Err(tls_unsupported());
+        }
+        let (socket, _) = tungstenite::connect(url).map_err(io::Error::other)?;
+        Self::new(socket)
+    }
+
+    /// Completes the WebSocket handshake on a connection accepted by a
+    /// server, such as another instance of the app.
+    pub fn accept(stream: TcpStream) -> io::Result<Self> {
+        let socket =
+            tungstenite::accept(MaybeTlsStream::Plain(stream)).map_err(io::Error::other)?;
+        Self::new(socket)
+    }
+
+    fn new(socket: WebSocket<MaybeTlsStream<TcpStream>>) -> io::Result<Self> {
+        match socket.get_ref() {
+            MaybeTlsStream::Plain(stream) => stream.set_read_timeout(Some(POLL_INTERVAL))?,
+            #[allow(unreachable_patterns)]
+            _ => return Err(tls_unsupported()),
+        }
+        Ok(WebSocketTransport { socket })
+    }
+}
+
+fn tls_unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "TLS WebSocket connections are not supported",
+    )
+}
+
+impl Transport for WebSocketTransport {
+    fn send(&mut self, message: &str) -> io::Result<()> {
+        self.socket
+            .send(Message::text(message))
+            .map_err(io::Error::other)
+    }
+
+    fn receive(&mut self) -> io::Result<Option<String>> {
+        match self.socket.read() {
+            Ok(Message::Text(text)) => Ok(Some(text.as_str().to_owned())),
+            Ok(_) => Ok(None),
+            Err(tungstenite::Error::Io(e))
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                Ok(None)
+            }
+            Err(e) => Err(io::Error::other(e)),
+        }
+    }
+}
+
+/// Serves `transport` on the calling thread: sends the `outgoing` messages
+/// and passes incoming ones to `handle`, until either side disconnects or the
+/// `handler` is dropped.
+pub(crate) fn run<T: Transport, H>(
+    mut transport: T,
+    outgoing: Receiver<String>,
+    handler: Weak<H>,
+    handle: impl Fn(&H, &str),
+) {
+    loop {
+        loop {
+            match outgoing.try_recv() {
+                Ok(message) => {
+                    if transport.send(&message).is_err() {
+                        return;
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return,
+            }
+        }
+        let incoming = match transport.receive() {
+            Ok(incoming) => incoming,
+            Err(_) => return,
+        };
+        let Some(handler) = handler.upgrade() else {
+            return;
+        };
+        if let Some(message) = incoming {
+            handle(&handler, &message);
+        }
+    }
+}
+
+/// A [`Transport`] over in-memory channels, for tests.
+#[cfg(test)]
+pub(crate) struct ChannelTransport {
+    pub(crate) sent: std::sync::mpsc::Sender<String>,
+    pub(crate) incoming: Receiver<String>,
+}
+
+#[cfg(test)]
+impl Transport for ChannelTransport {
+    fn send(&mut self, message: &str) -> io::Result<()> {
+        self.sent.send(message.to_owned()).map_err(io::Error::other)
+    }
+
+    fn receive(&mut self) -> io::Result<Option<String>> {
+        Ok(self.incoming.recv_timeout(Duration::from_millis(5)).ok())
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tls_urls_are_rejected_rather_than_left_to_block() {
+        let error = WebSocketTransport::connect("WSS://127.0.0.1:1/")
+            .err()
+            .expect("wss is rejected");
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+    }
+}
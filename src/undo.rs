@@ -0,0 +1,273 @@
+use std::sync::Arc;
+
+use crate::{Action, Reducer, Value};
+
+type Filter<A> = Arc<dyn Fn(&A) -> bool + Send + Sync>;
+type GroupBy<A> = Arc<dyn Fn(&A) -> Option<u64> + Send + Sync>;
+
+/// State wrapped with undo / redo history. See [`undoable`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Undoable<S> {
+    past: Vec<S>,
+    present: S,
+    future: Vec<S>,
+    group: Option<u64>,
+}
+
+impl<S> Undoable<S> {
+    pub fn new(present: S) -> Self {
+        Undoable {
+            past: Vec::new(),
+            present,
+            future: Vec::new(),
+            group: None,
+        }
+    }
+
+    pub fn present(&self) -> &S {
+        &self.present
+    }
+
+    pub fn past(&self) -> &[S] {
+        &self.past
+    }
+
+    pub fn future(&self) -> &[S] {
+        &self.future
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.past.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.future.is_empty()
+    }
+}
+
+/// Actions understood by an [`undoable`] reducer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum UndoableAction<A> {
+    /// Reduce the wrapped action, recording an undo step.
+    Do(A),
+    Undo,
+    Redo,
+    /// Forget all past and future states, keeping the present.
+    ClearHistory,
+}
+
+impl<A> From<A> for UndoableAction<A> {
+    fn from(action: A) -> Self {
+        UndoableAction::Do(action)
+    }
+}
+
+/// Configuration for [`undoable_with`].
+pub struct UndoOptions<A> {
+    limit: Option<usize>,
+    filter: Option<Filter<A>>,
+    group_by: Option<GroupBy<A>>,
+}
+
+impl<A> UndoOptions<A> {
+    pub fn new() -> Self {
+        UndoOptions {
+            limit: None,
+            filter: None,
+            group_by: None,
+        }
+    }
+
+    /// Keeps at most `limit` past states.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Only actions for which `filter` returns true create an undo step.
+    /// Other actions still update the present state.
+    pub fn filter(mut self, filter: impl Fn(&A) -> bool + Send + Sync + 'static) -> Self {
+        self.filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Consecutive actions mapped to the same group key are merged into a
+    /// single undo step, e.g. the keystrokes of one word.
+    pub fn group_by(
+        mut self,
+        group_by: impl Fn(&A) -> Option<u64> + Send + Sync + 'static,
+    ) -> Self {
+        self.group_by = Some(Arc::new(group_by));
+        self
+    }
+}
+
+impl<A> Default for UndoOptions<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lifts `reducer` into a reducer over [`Undoable`] state with unlimited history.
+///
+/// ```
+/// use uniflow::{Dispatch, Read, Store, Undoable, UndoableAction, undoable};
+///
+/// uniflow::manual_spawner::init().expect("init");
+///
+/// let store = Store::new(Undoable::new(0), undoable(|s: i32, n: i32| s + n));
+/// store.dispatch(UndoableAction::Do(5));
+/// store.dispatch(UndoableAction::Undo);
+/// uniflow::manual_spawner::step();
+/// assert_eq!(*store.get().present(), 0);
+/// ```
+pub fn undoable<S, A, R>(reducer: R) -> impl Reducer<Undoable<S>, UndoableAction<A>>
+where
+    S: Value,
+    A: Action,
+    R: Reducer<S, A>,
+{
+    undoable_with(reducer, UndoOptions::new())
+}
+
+/// Like [`undoable`], configured by `options`.
+pub fn undoable_with<S, A, R>(
+    reducer: R,
+    options: UndoOptions<A>,
+) -> impl Reducer<Undoable<S>, UndoableAction<A>>
+where
+    S: Value,
+    A: Action,
+    R: Reducer<S, A>,
+{
+    move |mut state: Undoable<S>, action: UndoableAction<A>| {
+        match action {
+            UndoableAction::Do(action) => {
+                let recorded = options.filter.as_ref().is_none_or(|f| f(&action));
+                let group = options.group_by.as_ref().and_then(|g| g(&action));
+                let previous = state.present.clone();
+                state.present = reducer(state.present, action);
+                if !recorded || state.present == previous {
+                    return state;
+                }
+                let merge = group.is_some() && group == state.group && state.can_undo();
+                if !merge {
+                    state.past.push(previous);
+                    if let Some(limit) = options.limit
+                        && state.past.len() > limit
+                    {
+                        let excess = state.past.len() - limit;
+                        state.past.drain(..excess);
+                    }
+                }
+                state.group = group;
+                state.future.clear();
+            }
+            UndoableAction::Undo => {
+                if let Some(previous) = state.past.pop() {
+                    let present = std::mem::replace(&mut state.present, previous);
+                    state.future.push(present);
+                    state.group = None;
+                }
+            }
+            UndoableAction::Redo => {
+                if let Some(next) = state.future.pop() {
+                    let present = std::mem::replace(&mut state.present, next);
+                    state.past.push(present);
+                    state.group = None;
+                }
+            }
+            UndoableAction::ClearHistory => {
+                state.past.clear();
+                state.future.clear();
+                state.group = None;
+            }
+        }
+        state
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add(s: i32, n: i32) -> i32 {
+        s + n
+    }
+
+    fn run<R: Reducer<Undoable<i32>, UndoableAction<i32>>>(
+        reducer: R,
+        actions: impl IntoIterator<Item = UndoableAction<i32>>,
+    ) -> Undoable<i32> {
+        actions.into_iter().fold(Undoable::new(0), &reducer)
+    }
+
+    use UndoableAction::*;
+
+    #[test]
+    fn undo_restores_previous_state() {
+        let state = run(undoable(add), [Do(1), Do(2), Undo]);
+        assert_eq!(*state.present(), 1);
+        assert!(state.can_redo());
+    }
+
+    #[test]
+    fn redo_reapplies_undone_state() {
+        let state = run(undoable(add), [Do(1), Do(2), Undo, Undo, Redo]);
+        assert_eq!(*state.present(), 1);
+        assert_eq!(state.future(), &[3]);
+    }
+
+    #[test]
+    fn new_action_clears_redo_stack() {
+        let state = run(undoable(add), [Do(1), Undo, Do(5)]);
+        assert!(!state.can_redo());
+        assert_eq!(state.past(), &[0]);
+    }
+
+    #[test]
+    fn undo_without_history_is_noop() {
+        let state = run(undoable(add), [Undo, Redo]);
+        assert_eq!(state, Undoable::new(0));
+    }
+
+    #[test]
+    fn limit_caps_past_states() {
+        let state = run(
+            undoable_with(add, UndoOptions::new().limit(2)),
+            [Do(1), Do(1), Do(1), Do(1)],
+        );
+        assert_eq!(state.past(), &[2, 3]);
+    }
+
+    #[test]
+    fn filtered_actions_do_not_create_undo_steps() {
+        let state = run(
+            undoable_with(add, UndoOptions::new().filter(|n: &i32| *n != 100)),
+            [Do(1), Do(100), Undo],
+        );
+        assert_eq!(*state.present(), 0);
+        assert!(!state.can_undo());
+    }
+
+    #[test]
+    fn grouped_actions_undo_together() {
+        let state = run(
+            undoable_with(
+                add,
+                UndoOptions::new().group_by(|n: &i32| (*n < 10).then_some(0)),
+            ),
+            [Do(10), Do(1), Do(1), Do(1), Undo],
+        );
+        assert_eq!(*state.present(), 10);
+    }
+
+    #[test]
+    fn clear_history_keeps_present() {
+        let state = run(undoable(add), [Do(1), Do(2), Undo, ClearHistory]);
+        assert_eq!(*state.present(), 1);
+        assert!(!state.can_undo() && !state.can_redo());
+    }
+}
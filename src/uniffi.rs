@@ -0,0 +1,175 @@
+//! Bindings for Swift and Kotlin apps through
+//! [UniFFI](https://mozilla.github.io/uniffi-rs) (feature `uniffi`).
+//!
+//! Stores are generic, which foreign languages cannot express, so they cross
+//! the boundary as an [`FfiStore`]: actions go in and state comes out as
+//! JSON. The app's own UniFFI crate builds its store, wraps it, and exports a
+//! constructor:
+//!
+//! ```ignore
+//! #[uniffi::export]
+//! fn new_store() -> Arc<uniflow::uniffi::FfiStore> {
+//!     FfiStore::new(Store::new(State::default(), reducer))
+//! }
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::json_store::JsonStore;
+use crate::subscription::Subscription;
+use crate::{Action, Deps, Store, Value};
+
+/// Implemented by the foreign app to be told of state changes.
+#[uniffi::export(callback_interface)]
+pub trait StateWatcher: Send + Sync {
+    /// Called with the new state, as JSON, after every change.
+    fn on_change(&self, state: String);
+
+    /// Called instead of [`on_change`](StateWatcher::on_change) when the new
+    /// state fails to serialize.
+    fn on_error(&self, error: FfiError);
+}
+
+#[derive(Debug, uniffi::Error)]
+pub enum FfiError {
+    /// The action was not valid JSON for the store's action type.
+    InvalidAction { reason: String },
+    /// The state could not be serialized as JSON, e.g. a map with
+    /// non-string keys.
+    InvalidState { reason: String },
+}
+
+impl fmt::Display for FfiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FfiError::InvalidAction { reason } => write!(f, "invalid action: {reason}"),
+            FfiError::InvalidState { reason } => write!(f, "invalid state: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for FfiError {}
+
+/// A store whose actions and state cross the FFI boundary as JSON.
+#[derive(uniffi::Object)]
+pub struct FfiStore(JsonStore);
+
+impl FfiStore {
+    pub fn new<S, A, D>(store: Store<S, A, D>) -> Arc<Self>
+    where
+        S: Value + Serialize,
+        A: Action + DeserializeOwned,
+        D: Deps,
+    {
+        Arc::new(FfiStore(JsonStore::new(store)))
+    }
+}
+
+#[uniffi::export]
+impl FfiStore {
+    /// Dispatches the action serialized as JSON in `action`.
+    pub fn dispatch(&self, action: String) -> Result<(), FfiError> {
+        self.0
+            .dispatch(&action)
+            .map_err(|e| FfiError::InvalidAction {
+                reason: e.to_string(),
+            })
+    }
+
+    /// The current state, as JSON.
+    pub fn state(&self) -> Result<String, FfiError> {
+        self.0.state().map_err(invalid_state)
+    }
+
+    /// Calls `watcher` with the new state after every change, until the
+    /// returned subscription is unsubscribed.
+    pub fn watch(&self, watcher: Box<dyn StateWatcher>) -> Arc<FfiSubscription> {
+        let subscription = self.0.watch(move |state| match state {
+            Ok(state) => watcher.on_change(state),
+            Err(e) => watcher.on_error(invalid_state(e)),
+        });
+        Arc::new(FfiSubscription(subscription))
+    }
+}
+
+fn invalid_state(e: serde_json::Error) -> FfiError {
+    FfiError::InvalidState {
+        reason: e.to_string(),
+    }
+}
+
+/// A watcher registered with [`FfiStore::watch`].
+#[derive(uniffi::Object)]
+pub struct FfiSubscription(Subscription);
+
+#[uniffi::export]
+impl FfiSubscription {
+    pub fn unsubscribe(&self) {
+        self.0.clone().unsubscribe();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{init as init_executor, tick};
+    use std::sync::Mutex;
+
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl StateWatcher for Recorder {
+        fn on_change(&self, state: String) {
+            self.0.lock().unwrap().push(state);
+        }
+
+        fn on_error(&self, error: FfiError) {
+            self.0.lock().unwrap().push(error.to_string());
+        }
+    }
+
+    #[test]
+    fn dispatches_and_watches_through_json() {
+        init_executor();
+        let store = FfiStore::new(Store::new(vec![1], |mut s: Vec<i32>, n: i32| {
+            s.push(n);
+            s
+        }));
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let subscription = store.watch(Box::new(Recorder(seen.clone())));
+
+        store.dispatch("2".into()).unwrap();
+        assert!(store.dispatch("\"three\"".into()).is_err());
+        tick();
+        subscription.unsubscribe();
+        store.dispatch("4".into()).unwrap();
+        tick();
+        assert_eq!(store.state().unwrap(), "[1,2,4]");
+        assert_eq!(*seen.lock().unwrap(), vec!["[1,2]".to_string()]);
+    }
+
+    #[test]
+    fn unserializable_states_are_errors() {
+        use std::collections::BTreeMap;
+
+        init_executor();
+        let store = FfiStore::new(Store::new(
+            BTreeMap::new(),
+            |mut s: BTreeMap<(i32, i32), i32>, n: i32| {
+                s.insert((n, n), n);
+                s
+            },
+        ));
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let _subscription = store.watch(Box::new(Recorder(seen.clone())));
+        store.dispatch("1".into()).unwrap();
+        tick();
+
+        assert!(matches!(store.state(), Err(FfiError::InvalidState { .. })));
+        let seen = seen.lock().unwrap();
+        assert!(seen.len() == 1 && seen[0].starts_with("invalid state"));
+    }
+}
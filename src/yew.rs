@@ -0,0 +1,138 @@
+//! A context provider and hook for [Yew](https://yew.rs) function components
+//! (feature `yew`).
+
+use std::rc::Rc;
+
+use futures::StreamExt;
+use futures::future::abortable;
+use yew::prelude::*;
+
+use crate::{Action, Deps, Dispatcher, Read, Reader, Store, Value};
+
+/// The store a [`StoreProvider`] hands down, compared by identity.
+pub struct StoreContext<S: Value, A: Action, D: Deps = ()>(pub Rc<Store<S, A, D>>);
+
+impl<S: Value, A: Action, D: Deps> Clone for StoreContext<S, A, D> {
+    fn clone(&self) -> Self {
+        StoreContext(self.0.clone())
+    }
+}
+
+impl<S: Value, A: Action, D: Deps> PartialEq for StoreContext<S, A, D> {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[derive(Properties)]
+pub struct StoreProviderProps<S: Value, A: Action, D: Deps = ()> {
+    pub store: Rc<Store<S, A, D>>,
+    #[prop_or_default]
+    pub children: Html,
+}
+
+impl<S: Value, A: Action, D: Deps> PartialEq for StoreProviderProps<S, A, D> {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.store, &other.store) && self.children == other.children
+    }
+}
+
+/// Makes `store` available to [`use_uniflow`] in every component below it.
+#[function_component]
+pub fn StoreProvider<S, A, D>(props: &StoreProviderProps<S, A, D>) -> Html
+where
+    S: Value,
+    A: Action,
+    D: Deps,
+{
+    html! {
+        <ContextProvider<StoreContext<S, A, D>> context={StoreContext(props.store.clone())}>
+            { props.children.clone() }
+        </ContextProvider<StoreContext<S, A, D>>>
+    }
+}
+
+/// Returns the part of the state selected by `select` from the store of the
+/// nearest [`StoreProvider`], and a dispatcher into it. The component
+/// re-renders only when the selected part changes.
+///
+/// Like every hook, `select` is only taken on the first render. Panics outside
+/// a `StoreProvider` of the same store type.
+#[hook]
+pub fn use_uniflow<S, A, D, T, F>(select: F) -> (T, Dispatcher<A>)
+where
+    S: Value,
+    A: Action,
+    D: Deps,
+    T: Value,
+    F: Fn(&S) -> T + Send + Sync + 'static,
+{
+    let store = use_context::<StoreContext<S, A, D>>()
+        .expect("use_uniflow must be called below a StoreProvider")
+        .0;
+    let reader = use_memo((), |_| store.derived(select));
+    let update = use_force_update();
+    let value = reader.get();
+    {
+        let reader = reader.clone();
+        let rendered = value.clone();
+        use_effect_with((), move |_| {
+            let (rerender, abort) =
+                abortable(follow(&reader, &rendered, move || update.force_update()));
+            yew::platform::spawn_local(async move {
+                let _ = rerender.await;
+            });
+            move || abort.abort()
+        });
+    }
+    (value, store.dispatcher())
+}
+
+/// Calls `update` whenever `reader` changes, and straight away if it already
+/// differs from the `rendered` value: effects run after rendering, so the
+/// state may change before the component subscribes.
+fn follow<T: Value>(
+    reader: &Reader<T>,
+    rendered: &T,
+    update: impl Fn() + 'static,
+) -> impl Future<Output = ()> + 'static {
+    let mut changes = reader.stream();
+    if reader.get() != *rendered {
+        update();
+    }
+    async move {
+        while changes.next().await.is_some() {
+            update();
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Dispatch;
+    use crate::executor::{init as init_executor, tick};
+    use std::cell::Cell;
+
+    #[test]
+    fn changes_between_render_and_subscription_rerender() {
+        init_executor();
+        let store = Store::new(0, |s: i32, n: i32| s + n);
+        let reader = store.derived(|s| *s);
+        let rendered = reader.get();
+        store.dispatch(1);
+        tick();
+
+        let updates = Rc::new(Cell::new(0));
+        let u = updates.clone();
+        let _follow = follow(&reader, &rendered, move || u.set(u.get() + 1));
+        assert_eq!(updates.get(), 1);
+
+        let unchanged = Rc::new(Cell::new(0));
+        let u = unchanged.clone();
+        let _follow = follow(&reader, &reader.get(), move || u.set(u.get() + 1));
+        assert_eq!(unchanged.get(), 0);
+    }
+}